@@ -1,14 +1,552 @@
-use crate::ipapi::IpApiCache;
-use crate::parser::{AuditGroup, AuditLogParser};
-use anyhow::Result;
-use std::time::{Duration, Instant};
-use std::io;
+use crate::clipboard;
+use crate::config;
+use crate::crs;
+use crate::ipapi::{self, GeoProviderKind, IpApiCache, DEFAULT_FIELDS};
+use crate::loading::LoadingScreen;
+use crate::output;
+use crate::parser::{action_badge, short_id_len, AuditEntry, AuditGroup, AuditLogParser, HttpStatus, SHORT_ID_LEN};
+use crate::rdap::{AbuseContact, RdapCache};
+use crate::rdns::ReverseDnsCache;
+use crate::watch::{file_id, FileId, WatchDir};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant, SystemTime};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::{backend::CrosstermBackend, Terminal};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppView {
     TableView,
     DetailView,
+    DomainView,
+    StatsView,
+}
+
+/// What makes transactions "similar" for the collapsed table: the same
+/// client, the same rules and the same request path (query string ignored)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimilarKey {
+    pub client_ip: String,
+    pub rule_ids: Vec<String>,
+    pub path: Option<String>,
+}
+
+impl SimilarKey {
+    pub fn of(group: &AuditGroup) -> Self {
+        Self {
+            client_ip: group.client_ip.clone(),
+            rule_ids: group.primary_rule_ids.clone(),
+            path: group.request_uri.as_deref()
+                .map(|uri| uri.split('?').next().unwrap_or(uri).to_string()),
+        }
+    }
+}
+
+/// Transactions sharing a `SimilarKey`, shown as one row when collapsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarGroup {
+    pub key: SimilarKey,
+    pub indices: Vec<usize>, // Into audit_groups, most recent first
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl SimilarGroup {
+    pub fn count(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+/// State of the saved-filter picker (`f`), drawn over the table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterPicker {
+    pub selected: usize,
+    pub naming: Option<String>, // Name being typed for the current query (`s`)
+}
+
+/// Aggregate counts for one domain, shown in the domain rollup view
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainSummary {
+    pub domain: String,
+    pub hits: usize,
+    pub distinct_ips: usize,
+    pub top_rule: Option<String>,
+}
+
+/// Breakdown of the currently filtered groups, shown in the stats view.
+/// Each list is (key, count), most frequent first, capped at `STATS_TOP_N`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub total: usize,
+    pub blocked: usize, // The rest were only detected
+    pub time_range: Option<(DateTime<Utc>, DateTime<Utc>)>, // First and last transaction
+    pub status_classes: Vec<(String, usize)>,
+    pub top_ips: Vec<(String, usize)>,
+    pub top_rules: Vec<(String, usize)>,
+    pub top_categories: Vec<(String, usize)>, // CRS attack categories, each counted once per group
+    pub top_domains: Vec<(String, usize)>,
+    pub top_asns: Vec<(String, usize)>,
+    pub noisiest_ips: Vec<(String, f64)>, // Peak requests per second, busiest first
+    pub unresolved_geo: usize, // Groups whose IP has no cached geolocation yet
+}
+
+/// Transactions that fired one rule (`--rule`), broken down by domain and
+/// client IP to tell a targeted attack from a false positive
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleStats {
+    pub rule_id: String,
+    pub transactions: usize,
+    pub blocked: usize,
+    pub domains: Vec<RuleDomainStats>, // Most hits first
+    pub client_ips: Vec<RuleIpStats>,  // Most hits first
+}
+
+/// One domain's share of a rule's hits
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleDomainStats {
+    pub domain: String,
+    pub hits: usize,
+    pub blocked: usize,
+    pub distinct_ips: usize,
+    // Fraction of the domain's filtered transactions that fired the rule; a
+    // high share from many IPs usually means ordinary traffic trips it
+    pub share: f64,
+}
+
+/// One client IP's hits on a rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleIpStats {
+    pub client_ip: String,
+    pub hits: usize,
+    pub domains: usize,
+}
+
+/// Whether any part of the chain fired `rule_id` (exact match, unlike the
+/// `rule:` search token)
+pub fn fired_rule(group: &AuditGroup, rule_id: &str) -> bool {
+    group.entries.iter().flat_map(|e| &e.rule_ids).any(|id| id == rule_id)
+}
+
+/// CRS attack categories of the group's primary rules (`crs::rule_category`),
+/// each once, in rule order
+pub fn group_categories(group: &AuditGroup) -> Vec<&'static str> {
+    let mut categories = Vec::new();
+    for category in group.primary_rule_ids.iter().filter_map(|id| crs::rule_category(id)) {
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    categories
+}
+
+/// Rule IDs in ascending numeric order for display; IDs that are not
+/// numbers (custom rules) come last, alphabetically
+pub fn sorted_rule_ids(ids: &[String]) -> Vec<&str> {
+    let mut sorted: Vec<&str> = ids.iter().map(String::as_str).collect();
+    sorted.sort_by_key(|id| match id.parse::<u64>() {
+        Ok(number) => (false, number, *id),
+        Err(_) => (true, 0, *id),
+    });
+    sorted
+}
+
+/// The search value that pivots on a stats row: the key itself, the AS
+/// number of an ASN (`as13335`), or nothing for status classes without a
+/// code pattern ("no response")
+pub fn stats_pivot_value(field: &str, key: &str) -> Option<String> {
+    match field {
+        "status" => key.ends_with("xx").then(|| key.to_string()),
+        "asn" => key.split_whitespace().next().map(str::to_lowercase),
+        _ => Some(key.to_string()),
+    }
+}
+
+/// Entries per "top" list in `Stats`
+pub const STATS_TOP_N: usize = 10;
+
+/// Sliding window over which client IP request rates are measured
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Requests per second (within `RATE_WINDOW`) from which a client IP is
+/// marked in the table, unless `--rate-threshold` says otherwise
+pub const DEFAULT_RATE_THRESHOLD: f64 = 2.0;
+
+/// Peak request rate of every client IP: the most transactions that fall
+/// within one `RATE_WINDOW`, per second of the window
+pub fn ip_rates<'a>(groups: impl IntoIterator<Item = &'a AuditGroup>) -> HashMap<String, f64> {
+    let mut times: HashMap<&str, Vec<DateTime<Utc>>> = HashMap::new();
+    for group in groups {
+        times.entry(&group.client_ip).or_default().push(group.first_timestamp);
+    }
+    let window = chrono::Duration::from_std(RATE_WINDOW).unwrap_or(chrono::Duration::MAX);
+    times
+        .into_iter()
+        .map(|(ip, mut times)| {
+            times.sort_unstable();
+            let mut start = 0;
+            let mut peak = 0;
+            for end in 0..times.len() {
+                while times[end] - times[start] >= window {
+                    start += 1;
+                }
+                peak = peak.max(end - start + 1);
+            }
+            (ip.to_string(), peak as f64 / RATE_WINDOW.as_secs_f64())
+        })
+        .collect()
+}
+
+/// Fit a list's selection and scroll offset to `len` rows shown `height` at a time
+fn clamp_window(selected: &mut usize, scroll: &mut usize, len: usize, height: usize) {
+    *selected = (*selected).min(len.saturating_sub(1));
+    *scroll = (*scroll).min(len.saturating_sub(height));
+    if *selected < *scroll {
+        *scroll = *selected;
+    } else if *selected >= *scroll + height {
+        *scroll = *selected + 1 - height;
+    }
+}
+
+/// Count occurrences, most frequent first (ties by key), keeping the top `STATS_TOP_N`
+fn top_counts<'a>(keys: impl Iterator<Item = &'a str>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for key in keys {
+        *counts.entry(key).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(key, count)| (key.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(STATS_TOP_N);
+    counts
+}
+
+/// A table column; `--columns` picks which ones are shown and in what order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum Column {
+    AuditId,
+    Timestamp,
+    Domain,
+    ClientIp,
+    Country,
+    Status,
+    Action,
+    Flags,
+    RuleIds,
+    Category,
+}
+
+impl Column {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::AuditId => "Audit ID",
+            Self::Timestamp => "Timestamp",
+            Self::Domain => "Domain",
+            Self::ClientIp => "Client IP",
+            Self::Country => "Country",
+            Self::Status => "Status",
+            Self::Action => "Action",
+            Self::Flags => "Flags",
+            Self::RuleIds => "Rule IDs",
+            Self::Category => "Category",
+        }
+    }
+}
+
+/// Columns `<`/`>` scroll the unwrapped detail view by
+pub const DETAIL_HSCROLL_STEP: isize = 8;
+
+/// Narrowest a column can be dragged to
+pub const MIN_COLUMN_WIDTH: u16 = 3;
+
+/// The columns shown unless `--columns` says otherwise, in order. Country
+/// only shows while `g` is on; Category has to be asked for.
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::AuditId,
+    Column::Timestamp,
+    Column::Domain,
+    Column::ClientIp,
+    Column::Country,
+    Column::Status,
+    Column::Action,
+    Column::Flags,
+    Column::RuleIds,
+];
+
+/// Parse a comma-separated column list (`domain,client-ip,rule-ids`)
+pub fn parse_columns(text: &str) -> Result<Vec<Column>> {
+    use clap::ValueEnum;
+
+    let mut columns = Vec::new();
+    for name in text.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let column = Column::from_str(name, true).map_err(|_| {
+            let names: Vec<String> = Column::value_variants().iter()
+                .filter_map(|c| c.to_possible_value())
+                .map(|v| v.get_name().to_string())
+                .collect();
+            anyhow::anyhow!("unknown column '{}' (expected {})", name, names.join(", "))
+        })?;
+        if columns.contains(&column) {
+            anyhow::bail!("column '{}' is listed twice", name);
+        }
+        columns.push(column);
+    }
+    if columns.is_empty() {
+        anyhow::bail!("at least one column is needed");
+    }
+    Ok(columns)
+}
+
+/// Page opened by `o` for the selected client IP; `{ip}` is replaced
+pub const DEFAULT_IP_URL_TEMPLATE: &str = "https://ip-api.com/#{ip}";
+
+/// Sections kept by the detail view's audit-only filter: the audit trailer
+/// (H, rule messages) and the matched rules (K)
+pub const AUDIT_SECTIONS: &[char] = &['H', 'K'];
+
+/// Two clicks on the same row within this window open the detail view
+pub const DEFAULT_DOUBLE_CLICK_MS: u64 = 500;
+
+/// Parse a short duration: a whole number followed by `ms`, `s`, `m`, `h`,
+/// `d` or `w` (`500ms`, `30m`, `2d`)
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse()
+        .map_err(|_| anyhow::anyhow!("invalid duration '{}' (expected e.g. 30m, 1h, 2d)", text))?;
+    let seconds = match unit {
+        "ms" => return Ok(Duration::from_millis(number)),
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => anyhow::bail!("invalid duration unit in '{}' (use ms, s, m, h, d or w)", text),
+    };
+    number.checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow::anyhow!("duration '{}' is too long", text))
+}
+
+/// `--report-stray`: how much of the log was outside any entry (see
+/// `AuditLogParser::stray_bytes`)
+pub fn stray_bytes_note(bytes: usize) -> String {
+    match bytes {
+        0 => "Every non-blank line belongs to an audit entry".to_string(),
+        1 => "1 byte outside any audit entry (skipped)".to_string(),
+        n => format!("{} bytes outside any audit entry (skipped)", n),
+    }
+}
+
+/// Render a duration the way `parse_duration` reads it (`500ms`, `1s`, `5m`)
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs == 0 || duration.subsec_millis() != 0 {
+        format!("{}ms", duration.as_millis())
+    } else if secs.is_multiple_of(3600) {
+        format!("{}h", secs / 3600)
+    } else if secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// How often follow mode checks the log for changes by default
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `parse_duration` for `--poll-interval`, which must not be zero
+pub fn parse_poll_interval(text: &str) -> Result<Duration> {
+    let interval = parse_duration(text)?;
+    if interval.is_zero() {
+        anyhow::bail!("poll interval must be greater than zero");
+    }
+    Ok(interval)
+}
+
+/// Parse `--rate-threshold`: requests per second, greater than zero
+pub fn parse_rate_threshold(text: &str) -> Result<f64> {
+    let rate: f64 = text.trim().parse()
+        .map_err(|_| anyhow::anyhow!("invalid rate '{}' (expected requests per second, e.g. 2 or 0.5)", text))?;
+    if !(rate > 0.0 && rate.is_finite()) {
+        anyhow::bail!("rate threshold must be greater than zero");
+    }
+    Ok(rate)
+}
+
+/// Size and modification time of the log, to notice when it changed. For a
+/// concurrent storage directory the index file is what grows.
+fn log_stamp(log_path: &str, concurrent: bool) -> Option<(u64, SystemTime)> {
+    let path = Path::new(log_path);
+    let index = path.join("index");
+    let path = if concurrent && index.is_file() { index.as_path() } else { path };
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// `log_stamp` of every log, in order
+fn log_stamps(log_paths: &[String], concurrent: bool) -> Vec<Option<(u64, SystemTime)>> {
+    log_paths.iter().map(|path| log_stamp(path, concurrent)).collect()
+}
+
+/// Hash of the last few KiB of a log before `size`. If it still matches
+/// once the log grew, the log was appended to rather than rewritten.
+fn log_tail_hash(log_path: &str, size: u64) -> Option<u64> {
+    use std::hash::{Hash, Hasher};
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = std::fs::File::open(log_path).ok()?;
+    let start = size.saturating_sub(4096);
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut tail = Vec::new();
+    file.take(size - start).read_to_end(&mut tail).ok()?;
+    if (tail.len() as u64) < size - start {
+        return None;
+    }
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    tail.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Fill an IP lookup URL template (`{ip}` placeholder)
+pub fn ip_lookup_url(template: &str, ip: &str) -> String {
+    template.replace("{ip}", ip)
+}
+
+/// ASCII case-insensitive substring search, without lowercasing (copying)
+/// the haystack
+fn contains_ignore_ascii_case(haystack: &str, needle: &str) -> bool {
+    let (haystack, needle) = (haystack.as_bytes(), needle.as_bytes());
+    needle.is_empty() || haystack.windows(needle.len()).any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// Split a search term into its token, whether it is the exact
+/// `token=value` form, and the value. The first `:` or `=` decides, so
+/// `ip=2001:db8::1` is an exact IP.
+fn split_token(term: &str) -> Option<(&str, bool, &str)> {
+    let at = term.find([':', '='])?;
+    Some((&term[..at], term[at..].starts_with('='), &term[at + 1..]))
+}
+
+/// Whether a search term scans raw entry content (`content:`/`raw:`, also negated)
+fn is_content_term(term: &str) -> bool {
+    let term = term.strip_prefix('-').unwrap_or(term);
+    term.starts_with("content:") || term.starts_with("raw:")
+}
+
+/// Compare a body size against `>N`, `>=N`, `<N`, `<=N` or `N` (exact).
+/// Unknown sizes never match.
+fn size_matches(size: Option<u64>, expr: &str) -> bool {
+    let (op, number) = match expr.find(|c: char| c.is_ascii_digit()) {
+        Some(pos) => expr.split_at(pos),
+        None => return false,
+    };
+    let (Some(size), Ok(limit)) = (size, number.trim().parse::<u64>()) else {
+        return false;
+    };
+    match op.trim() {
+        ">" => size > limit,
+        ">=" => size >= limit,
+        "<" => size < limit,
+        "<=" => size <= limit,
+        "" | "=" => size == limit,
+        _ => false,
+    }
+}
+
+/// Match a status against `403`, `4xx`/`40x` (`x` is any digit) or a comma
+/// list of those (`403,404,5xx`). Every pattern is a whole 3-digit code, so
+/// `status:40` matches nothing rather than 400-409.
+fn status_matches(status: Option<u16>, expr: &str) -> bool {
+    let Some(status) = status else {
+        return false;
+    };
+    let status = status.to_string();
+    expr.split(',').any(|pattern| {
+        pattern.len() == status.len()
+            && pattern.chars().zip(status.chars()).all(|(p, s)| p == 'x' || p == s)
+    })
+}
+
+/// Timings for the debug overlay (`--debug`, F12)
+#[derive(Debug, Clone, Default)]
+pub struct DebugStats {
+    pub load_time: Duration, // Last (re)load of the log, including the loading screen
+    pub draw_time: Duration, // Last `terminal.draw`
+    pub memory: usize, // `App::memory_estimate` as of the last (re)load
+    frames: VecDeque<Instant>, // Draws within the last second
+}
+
+impl DebugStats {
+    /// Remember a finished frame that took `draw_time`
+    pub fn record_frame(&mut self, draw_time: Duration) {
+        let now = Instant::now();
+        self.draw_time = draw_time;
+        self.frames.push_back(now);
+        while self.frames.front().is_some_and(|t| now.duration_since(*t) > Duration::from_secs(1)) {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Frames drawn during the last second
+    pub fn fps(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// Startup options that shape how the log is loaded and presented
+#[derive(Debug, Clone, Default)]
+pub struct AppOptions {
+    pub ip_api_enabled: bool,
+    pub concurrent: bool, // log_paths are concurrent-mode index files or directories
+    pub max_entries: Option<usize>, // Keep only the most recent N groups while parsing
+    pub split_on_ip: bool, // One group per client IP when an audit ID collided
+    pub report_stray: bool, // Say how many bytes of the log were outside any entry
+    pub trusted_ip_headers: Vec<String>, // `--trust-xff`: real client IP headers in order, empty when off
+    pub a_section_ip_field: Option<usize>, // `--a-section-format`: client IP position on the A line
+    pub min_status: Option<u16>,
+    pub max_status: Option<u16>,
+    pub since: Option<Duration>, // Only show groups first seen within this long before now
+    pub geo_prefetch: bool, // Batch-resolve all client IPs right after loading
+    pub geo_provider: GeoProviderKind, // Where lookups go, ip-api by default
+    pub geo_token: Option<String>, // Access token for providers that take one
+    pub geo_fields: Option<String>, // ip-api fields to request, default ipapi::DEFAULT_FIELDS
+    pub dense: bool,        // Borderless table that fits more rows
+    pub show_country: bool, // Country column from cached geolocation
+    pub columns: Option<Vec<Column>>, // Table columns in order, default DEFAULT_COLUMNS
+    pub short_ids: bool,    // Shortened audit IDs in the table
+    pub sort_rule_ids: bool, // Rule IDs shown in numeric order rather than as they fired
+    pub rate_threshold: Option<f64>, // Requests per second that mark an IP, default DEFAULT_RATE_THRESHOLD
+    pub ip_url_template: Option<String>, // Browser URL for `o`, default DEFAULT_IP_URL_TEMPLATE
+    pub filter: Option<String>, // Search query applied at startup, same syntax as `/`
+    pub double_click_ms: Option<u64>, // Default DEFAULT_DOUBLE_CLICK_MS
+    pub no_mouse: bool, // Never capture the mouse (keyboard only)
+    pub follow: bool, // Reload the log whenever it changes
+    pub poll_interval: Option<Duration>, // How often to check in follow mode, default DEFAULT_POLL_INTERVAL
+    pub watch: Option<WatchDir>, // Follow the newest matching log in a directory across rotations
+    pub debug: bool, // Start with the debug overlay shown
+    pub legend: bool, // Start with the color legend shown
+    pub light: bool, // Color scheme for light terminal backgrounds
+    pub saved_filters: Vec<(String, String)>, // (name, query) pairs from filters.toml
+    pub filters_path: Option<PathBuf>, // Where saved filters are written, None to keep them in memory
+}
+
+impl AppOptions {
+    /// The parser these options load the logs with
+    pub fn log_parser(&self) -> AuditLogParser {
+        AuditLogParser::new()
+            .with_max_groups(self.max_entries)
+            .with_split_on_ip(self.split_on_ip)
+            .with_trusted_ip_headers(self.trusted_ip_headers.clone())
+            .with_a_section_ip_field(self.a_section_ip_field)
+    }
 }
 
 pub struct App {
@@ -18,60 +556,517 @@ pub struct App {
     pub scroll_offset: usize,
     pub search_query: String,
     pub search_mode: bool,
+    pub search_cursor: usize, // Byte offset of the edit cursor in search_query
+    pub fuzzy: bool, // Bare words match as subsequences, best matches first
+    pub hosting_only: bool, // Quick filter `H`: only IPs geolocation flags as hosting
+    pub proxy_only: bool,   // Quick filter `P`: only IPs flagged as proxies
+    fuzzy_matcher: SkimMatcherV2,
+    pub min_status: Option<u16>, // Startup status pre-filter, kept when the search is cleared
+    pub max_status: Option<u16>,
+    pub since: Option<DateTime<Utc>>, // Time pre-filter (`--since`), kept like the status range
+    since_window: Option<chrono::Duration>, // `--since` itself; the cutoff is re-measured from now on each refresh
     pub current_view: AppView,
     pub detail_scroll: usize,
+    pub detail_scrolls: HashMap<String, usize>, // Remembered detail scroll per base_id
+    pub detail_sections_filter: Option<&'static [char]>, // Only render these audit log sections
+    pub detail_raw: bool, // Render the chain as plain, unhighlighted log text
+    pub detail_data_expanded: bool, // Show matched data in full instead of truncated
+    pub detail_hexdump: bool, // Show binary-looking sections as a hex dump
+    pub detail_header_table: bool, // Lay out request headers as an aligned name/value table
+    pub detail_parts: bool, // One summary line per entry of the chain instead of the full dump
+    pub detail_part: usize, // Selected entry in the parts layout
+    pub detail_part_open: Option<usize>, // Entry expanded below its summary line
+    pub detail_timeline: bool, // Timeline of the chain's entries above the content
+    pub detail_wrap: bool, // Wrap long lines; off, they are cut and scroll sideways
+    pub detail_hscroll: usize, // Columns scrolled to the right while not wrapping
+    detail_group: Option<String>, // base_id the detail view last showed
+    pub detail_line_count: usize, // Wrapped lines of the detail content, updated on draw
+    pub detail_viewport: usize,   // Detail content rows on screen, updated on draw
     pub should_quit: bool,
-    pub log_path: String,
+    pub log_paths: Vec<String>, // Read together, in order (e.g. a rotated log and the current one)
+    pub concurrent: bool,
+    pub parser: AuditLogParser, // Built once from the options; loads, reloads and appends all use it
+    pub last_refresh: DateTime<Local>, // When the groups were last (re)loaded
     pub last_click_time: Option<Instant>,
     pub last_click_row: Option<usize>,
+    pub double_click_ms: u64,
+    pub mouse_capture: bool, // Capture the mouse in the table view (off with --no-mouse)
+    pub mouse_passthrough: bool, // Temporarily leave the mouse to the terminal for text selection
+    pub follow: bool, // Live mode: `run_app` polls the log and reloads it when it changed
+    pub poll_interval: Duration,
+    log_stamps: Vec<Option<(u64, SystemTime)>>, // Size/mtime of each log at the last (re)load
+    log_tails: Vec<Option<u64>>, // `log_tail_hash` of each log at its stamped size
+    pub watch: Option<WatchDir>, // --watch-dir: log_paths is the newest match, replaced on rotation
+    log_id: Option<FileId>, // Identity of the followed log while watching
+    retired: HashSet<String>, // base_ids of groups read from logs rotated away, kept on reloads
     pub table_area: Option<ratatui::layout::Rect>, // Cached table area for mouse clicks
+    pub column_widths: HashMap<Column, u16>, // Set by dragging a header separator; other columns size themselves
+    pub column_edges: Vec<(Column, u16, u16)>, // Column, x of the separator right of it, its width; updated on draw
+    column_drag: Option<(Column, u16, u16)>, // Column being resized, the x the drag started at, its width then
+    last_edge_click: Option<(Column, Instant)>, // For double-clicks on a separator
+    pub visible_height: usize, // Rows that fit in the current list view, updated on draw
     pub ip_api_enabled: bool,
     pub ip_api_cache: IpApiCache,
     pub current_ip_info: Option<String>, // Cached IP info for current detail view
+    pub ip_info_pending: bool, // Lookup for the detail view not made yet; the event loop draws a placeholder first
+    pub rdns_cache: ReverseDnsCache,
+    pub current_ptr: Option<String>, // Reverse DNS name for current detail view
+    pub rdap_cache: RdapCache,
+    pub current_abuse: Option<AbuseContact>, // RDAP record for current detail view, once looked up (`a`)
+    pub status_message: Option<String>, // One-shot note shown in the help bar, cleared on next key
+    pub debug: bool, // Debug overlay with timings and table state (`--debug`, F12)
+    pub debug_stats: DebugStats,
+    pub show_legend: bool, // Color legend over the current view (`?`, `--legend`)
+    pub stats: Stats,        // Snapshot computed when the stats view opens
+    pub stats_scroll: usize, // Stats view scroll position, kept around `stats_selected` by the draw
+    pub stats_selected: usize, // Cursor into `stats_pivots`
+    pub row_tint: bool, // Tint whole table rows by status class
+    pub dense: bool,    // Table drawn without borders
+    pub show_country: bool, // Country column in the table
+    pub columns: Vec<Column>, // Configured table columns, see visible_columns
+    pub short_ids: bool,    // Table shows audit IDs cut to `short_id_len`
+    pub short_id_len: usize, // Prefix length that keeps every loaded ID unique
+    pub sort_rule_ids: bool, // Show rule IDs numerically, see `display_rule_ids`
+    pub ip_rates: HashMap<String, f64>, // Peak request rate per client IP, see `ip_rates`
+    pub rate_threshold: f64, // Rate from which an IP counts as noisy
+    pub ip_url_template: String, // URL opened in the browser for the selected IP
+    pub collapsed: bool, // One table row per SimilarKey
+    pub similar_groups: Vec<SimilarGroup>, // Parallel to filtered_groups while collapsed
+    pub expanded_similar: Option<SimilarKey>, // Collapsed row expanded into its transactions
+    pub domain_summaries: Vec<DomainSummary>, // Rollup rows, built when entering DomainView
+    pub domain_selected: usize,
+    pub domain_scroll: usize,
+    pub saved_filters: Vec<(String, String)>, // Named search queries: (name, query)
+    pub filters_path: Option<PathBuf>,
+    pub filter_picker: Option<FilterPicker>, // Open over the table
+    pub select_mode: bool, // `v`: Space marks rows
+    pub marked: HashSet<String>, // base_ids of marked transactions; exports use them when any
 }
 
 impl App {
-    pub fn new(log_path: &str, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, ip_api_enabled: bool) -> Result<Self> {
-        let parser = AuditLogParser::new();
-        let audit_groups = parser.parse_log_file(log_path, terminal)?;
+    pub fn new(log_paths: &[String], terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, options: &AppOptions) -> Result<Self> {
+        let started = Instant::now();
+        let stamps = log_stamps(log_paths, options.concurrent);
+        let parser = options.log_parser();
+        let audit_groups = load_groups(log_paths, options.concurrent, &parser, terminal)?;
+        let mut app = Self::with_parser(audit_groups, log_paths, options, parser);
+        app.debug_stats.load_time = started.elapsed();
+        if options.report_stray {
+            app.status_message = Some(stray_bytes_note(app.parser.stray_bytes()));
+        }
+        app.set_log_stamps(stamps);
+        if app.ip_api_enabled && options.geo_prefetch {
+            let loading = LoadingScreen::new();
+            app.prefetch_geo(|resolved, total| {
+                let progress = if total == 0 { 1.0 } else { resolved as f64 / total as f64 };
+                let msg = format!("{}/{} subnets (ESC to skip)", resolved, total);
+                let _ = terminal.draw(|f| loading.draw_task(f, "Resolving geolocation", progress, &msg));
+                !skip_requested()
+            });
+        }
+        Ok(app)
+    }
+
+    /// Build the application state around already-parsed groups
+    pub fn from_groups(audit_groups: Vec<AuditGroup>, log_paths: &[impl AsRef<str>], options: &AppOptions) -> Self {
+        Self::with_parser(audit_groups, log_paths, options, options.log_parser())
+    }
+
+    /// `from_groups` with the parser the groups were read with
+    fn with_parser(
+        audit_groups: Vec<AuditGroup>,
+        log_paths: &[impl AsRef<str>],
+        options: &AppOptions,
+        parser: AuditLogParser,
+    ) -> Self {
         let filtered_groups: Vec<usize> = (0..audit_groups.len()).collect();
 
-        Ok(Self {
+        let mut app = Self {
             audit_groups,
             filtered_groups,
             selected_index: 0,
             scroll_offset: 0,
-            search_query: String::new(),
+            search_query: options.filter.clone().unwrap_or_default(),
             search_mode: false,
+            search_cursor: 0,
+            fuzzy: false,
+            hosting_only: false,
+            proxy_only: false,
+            fuzzy_matcher: SkimMatcherV2::default().ignore_case(),
+            min_status: options.min_status,
+            max_status: options.max_status,
+            since: None,
+            since_window: options.since.and_then(|since| chrono::Duration::from_std(since).ok()),
             current_view: AppView::TableView,
             detail_scroll: 0,
+            detail_scrolls: HashMap::new(),
+            detail_sections_filter: None,
+            detail_raw: false,
+            detail_data_expanded: false,
+            detail_hexdump: true,
+            detail_timeline: false,
+            detail_wrap: true,
+            detail_hscroll: 0,
+            detail_group: None,
+            detail_header_table: false,
+            detail_parts: false,
+            detail_part: 0,
+            detail_part_open: None,
+            detail_line_count: 0,
+            detail_viewport: 0,
             should_quit: false,
-            log_path: log_path.to_string(),
+            log_paths: log_paths.iter().map(|path| path.as_ref().to_string()).collect(),
+            concurrent: options.concurrent,
+            parser,
+            last_refresh: Local::now(),
             last_click_time: None,
             last_click_row: None,
+            double_click_ms: options.double_click_ms.unwrap_or(DEFAULT_DOUBLE_CLICK_MS),
+            mouse_capture: !options.no_mouse,
+            mouse_passthrough: false,
+            follow: options.follow,
+            poll_interval: options.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL),
+            log_stamps: Vec::new(),
+            log_tails: Vec::new(),
+            watch: options.watch.clone(),
+            log_id: options.watch.as_ref()
+                .and_then(|_| log_paths.first())
+                .and_then(|path| file_id(Path::new(path.as_ref()))),
+            retired: HashSet::new(),
             table_area: None,
-            ip_api_enabled,
-            ip_api_cache: IpApiCache::new(),
+            column_widths: HashMap::new(),
+            column_edges: Vec::new(),
+            column_drag: None,
+            last_edge_click: None,
+            visible_height: 20,
+            ip_api_enabled: options.ip_api_enabled,
+            ip_api_cache: IpApiCache::with_provider(options.geo_provider.provider(options.geo_token.clone()))
+                .with_fields(options.geo_fields.as_deref().unwrap_or(DEFAULT_FIELDS)),
             current_ip_info: None,
-        })
+            ip_info_pending: false,
+            rdns_cache: ReverseDnsCache::new(),
+            current_ptr: None,
+            rdap_cache: RdapCache::new(),
+            current_abuse: None,
+            status_message: None,
+            debug: options.debug,
+            debug_stats: DebugStats::default(),
+            show_legend: options.legend,
+            stats: Stats::default(),
+            stats_scroll: 0,
+            stats_selected: 0,
+            row_tint: false,
+            dense: options.dense,
+            show_country: options.show_country,
+            columns: options.columns.clone().unwrap_or_else(|| DEFAULT_COLUMNS.to_vec()),
+            short_ids: options.short_ids,
+            short_id_len: SHORT_ID_LEN,
+            sort_rule_ids: options.sort_rule_ids,
+            ip_rates: HashMap::new(),
+            rate_threshold: options.rate_threshold.unwrap_or(DEFAULT_RATE_THRESHOLD),
+            ip_url_template: options.ip_url_template.clone()
+                .unwrap_or_else(|| DEFAULT_IP_URL_TEMPLATE.to_string()),
+            collapsed: false,
+            similar_groups: Vec::new(),
+            expanded_similar: None,
+            domain_summaries: Vec::new(),
+            domain_selected: 0,
+            domain_scroll: 0,
+            saved_filters: options.saved_filters.clone(),
+            filters_path: options.filters_path.clone(),
+            filter_picker: None,
+            select_mode: false,
+            marked: HashSet::new(),
+        };
+        app.short_id_len = short_id_len(&app.audit_groups);
+        app.ip_rates = app.client_ip_rates();
+        app.debug_stats.memory = app.memory_estimate();
+        app.since = app.since_cutoff();
+        app.apply_search();
+        app
     }
 
+    /// Reload the logs. A serial log that only grew is parsed from its last
+    /// entry on and merged in; anything else is re-parsed with the loading
+    /// screen.
     pub fn refresh(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+        if self.rotate_log()? {
+            return Ok(());
+        }
+        let started = Instant::now();
+        let stamps = log_stamps(&self.log_paths, self.concurrent);
+        match self.appended_entries(&stamps) {
+            Some(entries) => {
+                let entries = entries?;
+                self.debug_stats.load_time = started.elapsed();
+                self.merge_groups(entries);
+            }
+            None => {
+                let groups = load_groups(&self.log_paths, self.concurrent, &self.parser, terminal)?;
+                self.debug_stats.load_time = started.elapsed();
+                self.replace_groups(self.with_retired(groups));
+            }
+        }
+        self.set_log_stamps(stamps);
+        Ok(())
+    }
+
+    /// Follow mode: reload the log (quietly, without the loading screen) if
+    /// its size or modification time changed since the last load. Returns
+    /// whether it did. An append is parsed from the start of the last entry
+    /// on, so an entry that was cut off last time is completed rather than
+    /// duplicated; any other change re-parses the whole log.
+    pub fn poll_log(&mut self) -> Result<bool> {
+        if self.rotate_log()? {
+            return Ok(true);
+        }
+        let stamps = log_stamps(&self.log_paths, self.concurrent);
+        if stamps == self.log_stamps {
+            return Ok(false);
+        }
+
+        let started = Instant::now();
+        match self.appended_entries(&stamps) {
+            Some(entries) => {
+                let entries = entries?;
+                self.debug_stats.load_time = started.elapsed();
+                self.merge_groups(entries);
+            }
+            None => {
+                let groups = read_groups(&self.log_paths, self.concurrent, &self.parser)?;
+                self.debug_stats.load_time = started.elapsed();
+                self.replace_groups(self.with_retired(groups));
+            }
+        }
+        self.set_log_stamps(stamps);
+        Ok(true)
+    }
+
+    /// `--watch-dir`: switch to the newest matching log when it is another
+    /// file than the followed one (a newer name, or rotation put a new file
+    /// behind the name) or the followed one shrank (truncated in place).
+    /// Everything read from the old log stays; if rotation only renamed it,
+    /// it is read once more for what was written since the last check.
+    /// Returns whether it switched.
+    fn rotate_log(&mut self) -> Result<bool> {
+        let Some(watch) = &self.watch else {
+            return Ok(false);
+        };
+        let Some(newest) = watch.newest()? else {
+            // Between moving the old log away and creating the new one
+            return Ok(false);
+        };
+        let newest_path = newest.to_string_lossy().into_owned();
+        let id = file_id(&newest);
+        let same_file = match id {
+            Some(_) => id == self.log_id,
+            None => self.log_paths.first() == Some(&newest_path),
+        };
+        let stamp = log_stamp(&newest_path, false);
+        let shrank = match (self.log_stamps.first(), stamp) {
+            (Some(Some((old_size, _))), Some((new_size, _))) => new_size < *old_size,
+            _ => false,
+        };
+        if same_file && !shrank {
+            // Possibly renamed (the new log isn't there yet); keep following it
+            self.log_paths = vec![newest_path];
+            return Ok(false);
+        }
+
+        let started = Instant::now();
+        let parser = &self.parser;
+        let renamed = match self.log_id {
+            Some(old_id) if !same_file => watch.find(old_id)?,
+            _ => None,
+        };
+        let mut groups = match renamed {
+            Some(old) => {
+                let mut groups = self.retired_groups();
+                groups.extend(read_groups(&[old.to_string_lossy()], false, parser)?);
+                groups
+            }
+            // Truncated in place or deleted: what was read is all there is
+            None => self.audit_groups.clone(),
+        };
+        self.retired = groups.iter().map(|group| group.base_id.clone()).collect();
+        groups.extend(read_groups(&[&newest_path], false, parser)?);
+        groups.sort_by_key(|group| Reverse(group.first_timestamp));
+        if let Some(max) = self.parser.max_groups() {
+            groups.truncate(max);
+        }
+        self.debug_stats.load_time = started.elapsed();
+        self.replace_groups(groups);
+
+        self.status_message = Some(format!("Log rotated; now following {}", newest.display()));
+        self.log_paths = vec![newest_path];
+        self.log_id = id;
+        self.set_log_stamps(vec![stamp]);
+        Ok(true)
+    }
+
+    /// The groups read from logs rotated away
+    fn retired_groups(&self) -> Vec<AuditGroup> {
+        self.audit_groups.iter()
+            .filter(|group| self.retired.contains(&group.base_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Freshly parsed groups of the followed log, with the ones from logs
+    /// rotated away kept next to them
+    fn with_retired(&self, mut groups: Vec<AuditGroup>) -> Vec<AuditGroup> {
+        if self.retired.is_empty() {
+            return groups;
+        }
+        groups.extend(self.retired_groups());
+        groups.sort_by_key(|group| Reverse(group.first_timestamp));
+        if let Some(max) = self.parser.max_groups() {
+            groups.truncate(max);
+        }
+        groups
+    }
+
+    /// Remember the stamps the groups were loaded at, with the tail hashes
+    /// `appended_entries` compares against next time
+    fn set_log_stamps(&mut self, stamps: Vec<Option<(u64, SystemTime)>>) {
+        self.log_tails = self.log_paths.iter()
+            .zip(&stamps)
+            .map(|(path, stamp)| stamp.and_then(|(size, _)| log_tail_hash(path, size)))
+            .collect();
+        self.log_stamps = stamps;
+    }
+
+    /// Entries added since the last load, if the change is a plain append to
+    /// one serial log: it grew and its old tail is unchanged. Parsing starts
+    /// at the old last entry, which may have been cut off mid-write. `None`
+    /// means the logs have to be re-parsed in full (concurrent mode, a
+    /// rotated, truncated or rewritten log, or several logs changed).
+    fn appended_entries(&self, stamps: &[Option<(u64, SystemTime)>]) -> Option<Result<Vec<AuditEntry>>> {
+        // Split groups no longer share the audit ID the merge goes by
+        if self.concurrent || self.parser.split_on_ip() || stamps.len() != self.log_stamps.len() {
+            return None;
+        }
+        let mut changed = (0..stamps.len()).filter(|&i| stamps[i] != self.log_stamps[i]);
+        let Some(i) = changed.next() else {
+            return Some(Ok(Vec::new()));
+        };
+        if changed.next().is_some() {
+            return None;
+        }
+
+        let (old_size, _) = self.log_stamps[i]?;
+        let (new_size, _) = stamps[i]?;
+        let path = &self.log_paths[i];
+        if new_size <= old_size || log_tail_hash(path, old_size) != self.log_tails[i] {
+            return None;
+        }
+        // Appended entries take their client IPs the same way as the rest
+        let parser = &self.parser;
+        Some(parser.last_entry_offset(Path::new(path), old_size)
+            .and_then(|offset| parser.parse_file_from(Path::new(path), offset)))
+    }
+
+    /// Show or hide the debug overlay
+    pub fn toggle_debug(&mut self) {
+        self.debug = !self.debug;
+    }
+
+    /// Rough heap size of the parsed log: raw text plus the extracted strings
+    pub fn memory_estimate(&self) -> usize {
+        self.audit_groups
+            .iter()
+            .flat_map(|g| &g.entries)
+            .map(|e| {
+                e.raw_content.len()
+                    + e.audit_id.len()
+                    + e.domain.len()
+                    + e.client_ip.len()
+                    + e.rule_ids.iter().map(String::len).sum::<usize>()
+                    + std::mem::size_of::<AuditEntry>()
+            })
+            .sum()
+    }
+
+    /// Turn follow mode on or off
+    pub fn toggle_follow(&mut self) {
+        self.follow = !self.follow;
+        self.status_message = Some(if self.follow {
+            format!("Following the log (checking every {})", format_duration(self.poll_interval))
+        } else {
+            "Stopped following the log".to_string()
+        });
+    }
+
+    /// Swap in freshly parsed groups, keeping the selection on the same
+    /// transaction (by `base_id`) even if newer entries were prepended
+    pub fn replace_groups(&mut self, groups: Vec<AuditGroup>) {
+        self.update_groups(|_, audit_groups| *audit_groups = groups);
+    }
+
+    /// Merge freshly parsed entries into the groups by audit ID (see
+    /// `AuditLogParser::merge_entries`), keeping the selection like
+    /// `replace_groups`
+    pub fn merge_groups(&mut self, entries: Vec<AuditEntry>) {
+        self.update_groups(|parser, audit_groups| parser.merge_entries(audit_groups, entries));
+    }
+
+    /// Change the groups in place and redo everything derived from them
+    fn update_groups(&mut self, update: impl FnOnce(&AuditLogParser, &mut Vec<AuditGroup>)) {
         // Save current position
+        let saved_base_id = self.selected_group().map(|g| g.base_id.clone());
         let saved_selected_index = self.selected_index;
         let saved_scroll_offset = self.scroll_offset;
 
-        let parser = AuditLogParser::new();
-        self.audit_groups = parser.parse_log_file(&self.log_path, terminal)?;
+        update(&self.parser, &mut self.audit_groups);
+        self.short_id_len = short_id_len(&self.audit_groups);
+        self.ip_rates = self.client_ip_rates();
+        self.debug_stats.memory = self.memory_estimate();
+        self.last_refresh = Local::now();
+        // A relative `--since` slides along while following the log
+        self.since = self.since_cutoff();
         self.filtered_groups = (0..self.audit_groups.len()).collect();
         self.apply_search();
+        self.restore_selection(saved_base_id, saved_selected_index, saved_scroll_offset);
+    }
 
-        // Restore position, clamping to valid range
-        let max_index = self.filtered_groups.len().saturating_sub(1);
-        self.selected_index = saved_selected_index.min(max_index);
-        self.scroll_offset = saved_scroll_offset.min(max_index);
+    /// `--since` measured back from now
+    fn since_cutoff(&self) -> Option<DateTime<Utc>> {
+        self.since_window.and_then(|window| Utc::now().checked_sub_signed(window))
+    }
 
-        Ok(())
+    /// Re-find a previously selected transaction after the rows changed.
+    /// While collapsed, the row containing it counts as found.
+    fn restore_selection(&mut self, saved_base_id: Option<String>, saved_selected_index: usize, saved_scroll_offset: usize) {
+        let found = saved_base_id.and_then(|base_id| {
+            let is_saved = |idx: &usize| self.audit_groups[*idx].base_id == base_id;
+            if self.similar_groups.is_empty() {
+                self.filtered_groups.iter().position(is_saved)
+            } else {
+                self.similar_groups.iter().position(|s| s.indices.iter().any(is_saved))
+            }
+        });
+        match found {
+            Some(new_index) => {
+                // Keep the selected row at the same height on screen
+                let row_on_screen = saved_selected_index.saturating_sub(saved_scroll_offset);
+                self.selected_index = new_index;
+                self.scroll_offset = new_index.saturating_sub(row_on_screen);
+            }
+            None => {
+                // Group is gone (or filtered out): clamp to valid range
+                let max_index = self.filtered_groups.len().saturating_sub(1);
+                self.selected_index = saved_selected_index.min(max_index);
+                self.scroll_offset = saved_scroll_offset.min(max_index);
+            }
+        }
+    }
+
+    /// Batch-resolve geolocation for every client IP so table columns and
+    /// `flag:` searches have data. Best-effort: network errors are ignored.
+    /// `on_batch` gets (resolved, total) subnets and can stop early.
+    pub fn prefetch_geo(&self, on_batch: impl FnMut(usize, usize) -> bool) {
+        let ips = self.audit_groups.iter().map(|g| g.client_ip.as_str());
+        let _ = self.ip_api_cache.prefetch(ips, on_batch);
     }
 
     pub fn selected_group(&self) -> Option<&AuditGroup> {
@@ -106,6 +1101,59 @@ impl App {
         }
     }
 
+    /// Select the next row below the cursor whose group satisfies `pred`,
+    /// scrolling it into view. Returns false (selection unchanged) when no
+    /// row below matches.
+    pub fn jump_to_next(&mut self, pred: impl Fn(&AuditGroup) -> bool) -> bool {
+        let found = (self.selected_index + 1..self.filtered_groups.len())
+            .find(|&row| pred(&self.audit_groups[self.filtered_groups[row]]));
+        self.jump_to_row(found)
+    }
+
+    /// Like `jump_to_next`, searching upwards from the cursor
+    pub fn jump_to_previous(&mut self, pred: impl Fn(&AuditGroup) -> bool) -> bool {
+        let found = (0..self.selected_index.min(self.filtered_groups.len()))
+            .rev()
+            .find(|&row| pred(&self.audit_groups[self.filtered_groups[row]]));
+        self.jump_to_row(found)
+    }
+
+    fn jump_to_row(&mut self, row: Option<usize>) -> bool {
+        let Some(row) = row else {
+            return false;
+        };
+        self.selected_index = row;
+        if row < self.scroll_offset {
+            self.scroll_offset = row;
+        } else if row >= self.scroll_offset + self.visible_height {
+            self.scroll_offset = row.saturating_sub(self.visible_height.saturating_sub(1));
+        }
+        true
+    }
+
+    /// `n`/`p`: hop to the next or previous 4xx/5xx transaction
+    pub fn jump_to_error(&mut self, forward: bool) {
+        let jumped = if forward {
+            self.jump_to_next(is_error_status)
+        } else {
+            self.jump_to_previous(is_error_status)
+        };
+        if !jumped {
+            let direction = if forward { "below" } else { "above" };
+            self.status_message = Some(format!("No 4xx/5xx entries {}", direction));
+        }
+    }
+
+    /// Fit the list windows to `visible_height`, as measured by the draw
+    /// that calls this before rendering rows: keep the selected row on
+    /// screen, and don't leave rows empty below the last one when the list
+    /// grew taller
+    pub fn clamp_scroll(&mut self) {
+        let height = self.visible_height.max(1);
+        clamp_window(&mut self.selected_index, &mut self.scroll_offset, self.filtered_groups.len(), height);
+        clamp_window(&mut self.domain_selected, &mut self.domain_scroll, self.domain_summaries.len(), height);
+    }
+
     pub fn page_up(&mut self, page_size: usize) {
         self.selected_index = self.selected_index.saturating_sub(page_size);
         self.scroll_offset = self.scroll_offset.saturating_sub(page_size);
@@ -113,15 +1161,21 @@ impl App {
 
     pub fn page_down(&mut self, page_size: usize) {
         self.selected_index = (self.selected_index + page_size).min(self.filtered_groups.len().saturating_sub(1));
-        self.scroll_offset = self.scroll_offset + page_size;
+        // Never scroll past the selection, or it ends up outside the viewport
+        self.scroll_offset = (self.scroll_offset + page_size).min(self.selected_index);
     }
 
     pub fn scroll_detail_up(&mut self) {
         self.detail_scroll = self.detail_scroll.saturating_sub(1);
     }
 
+    /// Furthest detail scroll that still fills the viewport, from the last draw
+    pub fn max_detail_scroll(&self) -> usize {
+        self.detail_line_count.saturating_sub(self.detail_viewport)
+    }
+
     pub fn scroll_detail_down(&mut self) {
-        self.detail_scroll += 1;
+        self.detail_scroll = (self.detail_scroll + 1).min(self.max_detail_scroll());
     }
 
     pub fn page_detail_up(&mut self, page_size: usize) {
@@ -129,7 +1183,7 @@ impl App {
     }
 
     pub fn page_detail_down(&mut self, page_size: usize) {
-        self.detail_scroll += page_size;
+        self.detail_scroll = (self.detail_scroll + page_size).min(self.max_detail_scroll());
     }
 
     pub fn scroll_detail_home(&mut self) {
@@ -137,118 +1191,1289 @@ impl App {
     }
 
     pub fn scroll_detail_end(&mut self) {
-        // Set to a very large value - ratatui will clamp it automatically
-        self.detail_scroll = usize::MAX;
+        self.detail_scroll = self.max_detail_scroll();
     }
 
-    pub fn enter_search_mode(&mut self) {
-        self.search_mode = true;
+    /// Switch the detail view between every section and only `AUDIT_SECTIONS`
+    pub fn toggle_detail_sections(&mut self) {
+        self.detail_sections_filter = match self.detail_sections_filter {
+            Some(_) => None,
+            None => Some(AUDIT_SECTIONS),
+        };
+        // Line offsets of the other layout are meaningless
+        self.detail_scroll = 0;
     }
 
-    pub fn exit_search_mode(&mut self) {
-        self.search_mode = false;
+    /// Switch the detail view between the full chain and one line per entry
+    pub fn toggle_detail_parts(&mut self) {
+        self.detail_parts = !self.detail_parts;
+        self.reset_detail_parts();
+        self.detail_scroll = 0;
     }
 
-    pub fn add_search_char(&mut self, c: char) {
-        self.search_query.push(c);
-        self.apply_search();
+    fn reset_detail_parts(&mut self) {
+        self.detail_part = 0;
+        self.detail_part_open = None;
     }
 
-    pub fn remove_search_char(&mut self) {
-        self.search_query.pop();
-        self.apply_search();
+    /// Select the previous/next entry in the parts layout
+    pub fn move_detail_part(&mut self, forward: bool) {
+        let parts = self.selected_group().map_or(0, |g| g.entries.len());
+        self.detail_part = if forward {
+            (self.detail_part + 1).min(parts.saturating_sub(1))
+        } else {
+            self.detail_part.saturating_sub(1)
+        };
     }
 
-    pub fn clear_search(&mut self) {
-        self.search_query.clear();
-        self.filtered_groups = (0..self.audit_groups.len()).collect();
-        self.selected_index = 0;
-        self.scroll_offset = 0;
+    /// Expand the selected entry in the parts layout, or fold it back
+    pub fn toggle_detail_part_open(&mut self) {
+        self.detail_part_open = match self.detail_part_open {
+            Some(open) if open == self.detail_part => None,
+            _ => Some(self.detail_part),
+        };
     }
 
-    pub fn apply_search(&mut self) {
-        if self.search_query.is_empty() {
-            self.filtered_groups = (0..self.audit_groups.len()).collect();
-        } else {
-            self.filtered_groups = self.audit_groups
-                .iter()
-                .enumerate()
-                .filter(|(_, group)| self.matches_search(group))
-                .map(|(idx, _)| idx)
-                .collect();
-        }
-        self.selected_index = 0;
-        self.scroll_offset = 0;
+    /// Show the request headers as an aligned table instead of plain lines
+    pub fn toggle_detail_header_table(&mut self) {
+        self.detail_header_table = !self.detail_header_table;
     }
 
-    fn matches_search(&self, group: &AuditGroup) -> bool {
-        let query = self.search_query.to_lowercase();
-
-        // Check for tokenized search
-        if let Some((token, value)) = query.split_once(':') {
-            match token.trim() {
-                "domain" => group.domain.to_lowercase().contains(value.trim()),
-                "ip" => group.client_ip.contains(value.trim()),
-                "rule" | "ruleid" | "id" => group.primary_rule_ids.iter()
-                    .any(|id| id.contains(value.trim())),
-                "auditid" => group.base_id.to_lowercase().contains(value.trim()),
-                "status" | "http" => {
-                    if let Some(status) = group.http_status {
-                        status.to_string().contains(value.trim())
-                    } else {
-                        false
-                    }
-                },
-                _ => self.matches_all_fields(group, &query),
-            }
-        } else {
-            self.matches_all_fields(group, &query)
-        }
+    /// Show long matched data values in full, or truncated again
+    pub fn toggle_detail_data_expanded(&mut self) {
+        self.detail_data_expanded = !self.detail_data_expanded;
     }
 
-    fn matches_all_fields(&self, group: &AuditGroup, query: &str) -> bool {
-        group.domain.to_lowercase().contains(query) ||
-        group.client_ip.contains(query) ||
-        group.base_id.to_lowercase().contains(query) ||
-        group.primary_rule_ids.iter().any(|id| id.contains(query)) ||
-        group.http_status.map(|s| s.to_string().contains(query)).unwrap_or(false)
+    /// Hex-dump binary-looking sections, or show them as decoded text
+    pub fn toggle_detail_hexdump(&mut self) {
+        self.detail_hexdump = !self.detail_hexdump;
+        // A dump has a different line count than the text it replaces
+        self.detail_scroll = 0;
     }
 
-    pub fn show_detail_view(&mut self) {
-        self.current_view = AppView::DetailView;
+    /// Wrap long lines, or cut them at the edge and scroll sideways with
+    /// `scroll_detail_sideways`
+    pub fn toggle_detail_wrap(&mut self) {
+        self.detail_wrap = !self.detail_wrap;
+        self.detail_hscroll = 0;
+        // Wrapping changes how many rows the content takes
         self.detail_scroll = 0;
+    }
 
-        // Fetch IP info when entering detail view
-        if self.ip_api_enabled {
-            if let Some(group) = self.selected_group() {
-                self.current_ip_info = self.ip_api_cache.get_ip_info(&group.client_ip).ok();
-            }
-        } else {
-            self.current_ip_info = None;
+    /// Scroll the unwrapped detail content `columns` to the right (left if
+    /// negative); a no-op while lines wrap
+    pub fn scroll_detail_sideways(&mut self, columns: isize) {
+        if !self.detail_wrap {
+            self.detail_hscroll = self.detail_hscroll.saturating_add_signed(columns);
         }
     }
 
-    pub fn show_table_view(&mut self) {
-        self.current_view = AppView::TableView;
-        // Keep current_ip_info - it's just a copy of what's already cached
+    /// Show or hide the timeline of the chain's entries
+    pub fn toggle_detail_timeline(&mut self) {
+        self.detail_timeline = !self.detail_timeline;
     }
 
-    pub fn quit(&mut self) {
-        self.should_quit = true;
+    /// Switch the detail view between highlighted and raw, unprocessed text
+    pub fn toggle_detail_raw(&mut self) {
+        self.detail_raw = !self.detail_raw;
+        // The raw layout drops the rule summary and IP block, so lines shift
+        self.detail_scroll = 0;
     }
 
-    pub fn handle_click(&mut self, row: usize, visible_height: usize) -> bool {
-        const DOUBLE_CLICK_MS: u64 = 500;
-        let now = Instant::now();
-        let mut should_open_detail = false;
+    pub fn enter_search_mode(&mut self) {
+        self.search_mode = true;
+        self.search_cursor = self.search_query.len();
+    }
 
-        // Check for double-click
-        if let (Some(last_time), Some(last_row)) = (self.last_click_time, self.last_click_row) {
-            if last_row == row && now.duration_since(last_time) < Duration::from_millis(DOUBLE_CLICK_MS) {
-                // Double-click detected - open detail view
-                should_open_detail = true;
-            }
-        }
+    pub fn exit_search_mode(&mut self) {
+        self.search_mode = false;
+    }
+
+    /// The edit cursor, kept valid even if search_query was replaced
+    /// (domain drill-down, tests)
+    pub fn search_cursor(&self) -> usize {
+        let mut cursor = self.search_cursor.min(self.search_query.len());
+        while !self.search_query.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        cursor
+    }
+
+    /// Insert at the cursor
+    pub fn add_search_char(&mut self, c: char) {
+        let cursor = self.search_cursor();
+        self.search_query.insert(cursor, c);
+        self.search_cursor = cursor + c.len_utf8();
+        self.apply_search();
+    }
+
+    /// Delete the character before the cursor (Backspace)
+    pub fn remove_search_char(&mut self) {
+        let cursor = self.search_cursor();
+        if let Some(c) = self.search_query[..cursor].chars().next_back() {
+            self.search_cursor = cursor - c.len_utf8();
+            self.search_query.remove(self.search_cursor);
+            self.apply_search();
+        }
+    }
+
+    /// Delete the word before the cursor, and the spaces after it (Ctrl-W)
+    pub fn delete_search_word(&mut self) {
+        let cursor = self.search_cursor();
+        let before = self.search_query[..cursor].trim_end();
+        let start = before.rfind(char::is_whitespace).map_or(0, |pos| pos + 1);
+        self.search_query.replace_range(start..cursor, "");
+        self.search_cursor = start;
+        self.apply_search();
+    }
+
+    /// Move the cursor one character right (`forward`) or left
+    pub fn move_search_cursor(&mut self, forward: bool) {
+        let cursor = self.search_cursor();
+        self.search_cursor = if forward {
+            self.search_query[cursor..].chars().next().map_or(cursor, |c| cursor + c.len_utf8())
+        } else {
+            self.search_query[..cursor].chars().next_back().map_or(cursor, |c| cursor - c.len_utf8())
+        };
+    }
+
+    pub fn search_cursor_home(&mut self) {
+        self.search_cursor = 0;
+    }
+
+    pub fn search_cursor_end(&mut self) {
+        self.search_cursor = self.search_query.len();
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search_query.clear();
+        self.search_cursor = 0;
+        self.apply_search();
+    }
+
+    pub fn has_status_filter(&self) -> bool {
+        self.min_status.is_some() || self.max_status.is_some()
+    }
+
+    pub fn has_geo_filter(&self) -> bool {
+        self.hosting_only || self.proxy_only
+    }
+
+    /// Whether a group passes the `H`/`P` quick filters. With both on, either
+    /// flag will do (the "bots" view). IPs without cached geolocation never
+    /// pass while one is on.
+    fn geo_flags_pass(&self, group: &AuditGroup) -> bool {
+        if !self.has_geo_filter() {
+            return true;
+        }
+        self.ip_api_cache.cached(&group.client_ip).is_some_and(|geo| {
+            (self.hosting_only && geo.hosting == Some(true)) || (self.proxy_only && geo.proxy == Some(true))
+        })
+    }
+
+    /// `H`: only show transactions from hosting/datacenter IPs
+    pub fn toggle_hosting_only(&mut self) {
+        self.hosting_only = !self.hosting_only;
+        self.apply_geo_filter();
+    }
+
+    /// `P`: only show transactions from flagged proxies
+    pub fn toggle_proxy_only(&mut self) {
+        self.proxy_only = !self.proxy_only;
+        self.apply_geo_filter();
+    }
+
+    fn apply_geo_filter(&mut self) {
+        self.apply_search();
+        // The flags only exist for resolved IPs, which may be none yet
+        let resolved = self.audit_groups.iter().any(|g| self.ip_api_cache.cached(&g.client_ip).is_some());
+        if self.has_geo_filter() && !resolved {
+            self.status_message = Some("No geolocation cached yet: use --geo-prefetch or open entries first".to_string());
+        }
+    }
+
+    /// Whether a group passes the startup status pre-filter. Groups without
+    /// a status never pass once a bound is set.
+    fn status_in_range(&self, group: &AuditGroup) -> bool {
+        if !self.has_status_filter() {
+            return true;
+        }
+        match group.http_status.code() {
+            Some(status) => {
+                self.min_status.is_none_or(|min| status >= min)
+                    && self.max_status.is_none_or(|max| status <= max)
+            }
+            None => false,
+        }
+    }
+
+    pub fn apply_search(&mut self) {
+        let mut matching = self.matching_indices();
+        self.similar_groups.clear();
+        if let Some(key) = &self.expanded_similar {
+            matching.retain(|&idx| SimilarKey::of(&self.audit_groups[idx]) == *key);
+        } else if self.collapsed {
+            self.similar_groups = self.group_similar(&matching);
+            matching = self.similar_groups.iter().map(|s| s.indices[0]).collect();
+        }
+        self.filtered_groups = matching;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Indices of every group matching the search and status pre-filter.
+    /// In fuzzy mode, groups matching the bare words best come first.
+    fn matching_indices(&self) -> Vec<usize> {
+        if self.search_query.is_empty() && !self.has_status_filter() && self.since.is_none() && !self.has_geo_filter() {
+            return (0..self.audit_groups.len()).collect();
+        }
+        let mut matching: Vec<usize> = self.audit_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| self.matches_search(group))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let query = self.search_query.to_lowercase();
+        let words: Vec<&str> = query.split_whitespace().filter(|term| split_token(term).is_none()).collect();
+        if self.fuzzy && !words.is_empty() {
+            // Stable sort: equal scores keep the most-recent-first order
+            matching.sort_by_cached_key(|&idx| {
+                let group = &self.audit_groups[idx];
+                Reverse(words.iter().filter_map(|word| self.fuzzy_score(group, word)).sum::<i64>())
+            });
+        }
+        matching
+    }
+
+    /// Switch bare-word search between substring and fuzzy matching
+    pub fn toggle_fuzzy(&mut self) {
+        self.fuzzy = !self.fuzzy;
+        self.apply_search();
+    }
+
+    /// Best fuzzy score of a bare word against domain, IP, rule IDs and audit ID
+    fn fuzzy_score(&self, group: &AuditGroup, word: &str) -> Option<i64> {
+        [group.domain.as_str(), group.client_ip.as_str(), group.base_id.as_str()]
+            .into_iter()
+            .chain(group.primary_rule_ids.iter().map(String::as_str))
+            .filter_map(|field| self.fuzzy_matcher.fuzzy_match(field, word))
+            .max()
+    }
+
+    /// Collapse the groups matching the current search by `SimilarKey`,
+    /// ordered by their most recent transaction
+    pub fn collapse_similar(&self) -> Vec<SimilarGroup> {
+        self.group_similar(&self.matching_indices())
+    }
+
+    fn group_similar(&self, indices: &[usize]) -> Vec<SimilarGroup> {
+        let mut positions: HashMap<SimilarKey, usize> = HashMap::new();
+        let mut similar: Vec<SimilarGroup> = Vec::new();
+        for &idx in indices {
+            let group = &self.audit_groups[idx];
+            let key = SimilarKey::of(group);
+            match positions.get(&key) {
+                Some(&pos) => {
+                    let entry = &mut similar[pos];
+                    entry.indices.push(idx);
+                    entry.first_seen = entry.first_seen.min(group.first_timestamp);
+                    entry.last_seen = entry.last_seen.max(group.first_timestamp);
+                }
+                None => {
+                    positions.insert(key.clone(), similar.len());
+                    similar.push(SimilarGroup {
+                        key,
+                        indices: vec![idx],
+                        first_seen: group.first_timestamp,
+                        last_seen: group.first_timestamp,
+                    });
+                }
+            }
+        }
+        similar
+    }
+
+    /// The collapsed row under the cursor, if the table is collapsed
+    pub fn selected_similar(&self) -> Option<&SimilarGroup> {
+        self.similar_groups.get(self.selected_index)
+    }
+
+    /// Switch between one row per transaction and one row per `SimilarKey`,
+    /// keeping the selected transaction (or its row) selected
+    pub fn toggle_collapse(&mut self) {
+        let saved_base_id = self.selected_group().map(|g| g.base_id.clone());
+        let (saved_selected_index, saved_scroll_offset) = (self.selected_index, self.scroll_offset);
+        self.collapsed = !self.collapsed;
+        self.expanded_similar = None;
+        self.apply_search();
+        self.restore_selection(saved_base_id, saved_selected_index, saved_scroll_offset);
+    }
+
+    /// Show the individual transactions behind the selected collapsed row.
+    /// Returns false when there is nothing to expand (single transaction).
+    pub fn expand_similar(&mut self) -> bool {
+        let Some(similar) = self.selected_similar().filter(|s| s.count() > 1) else {
+            return false;
+        };
+        let count = similar.count();
+        self.expanded_similar = Some(similar.key.clone());
+        self.apply_search();
+        self.status_message = Some(format!("{} similar transactions (ESC to collapse again)", count));
+        true
+    }
+
+    /// Go back from an expanded row to the collapsed table. Returns false
+    /// when no row was expanded.
+    pub fn close_similar(&mut self) -> bool {
+        if self.expanded_similar.take().is_none() {
+            return false;
+        }
+        let saved_base_id = self.selected_group().map(|g| g.base_id.clone());
+        self.apply_search();
+        self.restore_selection(saved_base_id, 0, 0);
+        true
+    }
+
+    /// `v`: start or finish marking rows. Marks outlive the mode, so they
+    /// can be exported afterwards.
+    pub fn toggle_select_mode(&mut self) {
+        self.select_mode = !self.select_mode;
+        self.status_message = Some(if self.select_mode {
+            "Select mode: Space marks rows, v when done, ESC drops the marks".to_string()
+        } else {
+            format!("{} marked", self.marked.len())
+        });
+    }
+
+    /// Space in select mode: mark the transactions of the selected row (all
+    /// of a collapsed row's), or unmark them if they all were, then move down
+    pub fn toggle_mark(&mut self) {
+        let base_ids: Vec<String> = match self.selected_similar() {
+            Some(similar) => similar.indices.iter().map(|&idx| self.audit_groups[idx].base_id.clone()).collect(),
+            None => self.selected_group().map(|g| g.base_id.clone()).into_iter().collect(),
+        };
+        if base_ids.iter().all(|id| self.marked.contains(id)) {
+            for id in &base_ids {
+                self.marked.remove(id);
+            }
+        } else {
+            self.marked.extend(base_ids);
+        }
+        self.move_selection_down(self.visible_height);
+    }
+
+    /// ESC in select mode: leave it and drop every mark
+    pub fn clear_marks(&mut self) {
+        self.select_mode = false;
+        self.marked.clear();
+    }
+
+    /// Whether the table row at `row` holds a marked transaction
+    pub fn is_row_marked(&self, row: usize) -> bool {
+        match self.similar_groups.get(row) {
+            Some(similar) => similar.indices.iter().any(|&idx| self.marked.contains(&self.audit_groups[idx].base_id)),
+            None => self.filtered_groups.get(row)
+                .is_some_and(|&idx| self.marked.contains(&self.audit_groups[idx].base_id)),
+        }
+    }
+
+    /// What exports work on: the marked transactions if there are any (even
+    /// ones the current search hides), else `filtered_transactions`
+    pub fn export_groups(&self) -> Vec<&AuditGroup> {
+        if self.marked.is_empty() {
+            return self.filtered_transactions();
+        }
+        self.audit_groups.iter().filter(|g| self.marked.contains(&g.base_id)).collect()
+    }
+
+    /// Every transaction behind the table rows; collapsed rows count each
+    /// of their transactions
+    fn filtered_transactions(&self) -> Vec<&AuditGroup> {
+        if self.similar_groups.is_empty() {
+            return self.visible_groups();
+        }
+        self.similar_groups.iter()
+            .flat_map(|s| &s.indices)
+            .map(|&idx| &self.audit_groups[idx])
+            .collect()
+    }
+
+    /// Whether a group matches the current search query
+    pub fn matches_search(&self, group: &AuditGroup) -> bool {
+        // The status, time and geo flag filters always apply, independent of the query
+        if !self.status_in_range(group) || self.since.is_some_and(|since| group.first_timestamp < since) {
+            return false;
+        }
+        if !self.geo_flags_pass(group) {
+            return false;
+        }
+
+        // Whitespace-separated terms must all match (`status:403 domain:api.`).
+        // Content scans are O(bytes), so they only run once the cheap terms passed
+        let query = self.search_query.to_lowercase();
+        let (content_terms, terms): (Vec<&str>, Vec<&str>) =
+            query.split_whitespace().partition(|term| is_content_term(term));
+        terms.iter().chain(&content_terms).all(|term| self.matches_term(group, term))
+    }
+
+    /// Match a single search term: a `token:value` filter, its exact
+    /// `token=value` form, or free text
+    fn matches_term(&self, group: &AuditGroup, term: &str) -> bool {
+        // Check for tokenized search; a leading '-' inverts the token (`-rule:949110`)
+        if let Some((token, exact, value)) = split_token(term) {
+            let (negate, token) = match token.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, token),
+            };
+            if exact {
+                return match self.matches_exact(group, token, value) {
+                    Some(matched) => matched != negate,
+                    None => self.matches_all_fields(group, term),
+                };
+            }
+            let matched = match token {
+                "domain" => group.domain.to_lowercase().contains(value),
+                "ip" => group.client_ip.contains(value),
+                // Every rule that fired on any entry of the chain, not just
+                // the deduplicated list shown in the table
+                "rule" | "ruleid" | "id" => group.entries.iter()
+                    .flat_map(|e| &e.rule_ids)
+                    .any(|id| id.contains(value)),
+                "auditid" => group.base_id.to_lowercase().contains(value),
+                // The rule file shown in the info bar, for any entry of the
+                // chain (`file:942-application`); no `[file]` never matches
+                "file" => group.entries.iter()
+                    .filter_map(|e| e.file_path.as_deref())
+                    .any(|path| path.to_lowercase().contains(value)),
+                // CRS attack category of any primary rule (`cat:sqli`); a prefix,
+                // so `cat:leakage` covers every data leakage range
+                "cat" | "category" => group_categories(group).iter().any(|cat| cat.starts_with(value)),
+                // Matches the AS number or name, e.g. `asn:as13335` or `asn:cloudflare`
+                "asn" | "as" => self.ip_api_cache.cached(&group.client_ip)
+                    .and_then(|geo| geo.as_info)
+                    .is_some_and(|info| info.to_lowercase().contains(value)),
+                // Two-letter code (`country:us`) or part of the country name
+                "country" => self.ip_api_cache.cached(&group.client_ip)
+                    .is_some_and(|geo| {
+                        geo.country_code.is_some_and(|code| code.to_lowercase() == value)
+                            || (value.len() > 2
+                                && geo.country.is_some_and(|name| name.to_lowercase().contains(value)))
+                    }),
+                "flag" => self.ip_api_cache.cached(&group.client_ip)
+                    .is_some_and(|geo| match value {
+                        "proxy" | "p" => geo.proxy == Some(true),
+                        "hosting" | "h" => geo.hosting == Some(true),
+                        "mobile" | "m" => geo.mobile == Some(true),
+                        _ => false,
+                    }),
+                // Payload hunting across the raw text of every part
+                "content" | "raw" => group.entries.iter()
+                    .any(|e| contains_ignore_ascii_case(&e.raw_content, value)),
+                // What the rules actually matched, without the surrounding request noise
+                "data" => group.entries.iter()
+                    .flat_map(|e| &e.matched_data)
+                    .any(|data| contains_ignore_ascii_case(data, value)),
+                // Stopped vs. only detected (`blocked:false` for anomaly-mode watching)
+                "blocked" => match value {
+                    "true" | "yes" | "1" => group.blocked,
+                    "false" | "no" | "0" => !group.blocked,
+                    _ => false,
+                },
+                // `action:intercepted` / `action:warning`, or the badge (`action:block`)
+                "action" => group.action.as_ref().is_some_and(|action| {
+                    action.to_lowercase().contains(value) || action_badge(Some(action)).contains(value)
+                }),
+                // `mode:detection` / `mode:enabled`; logs without the trailer never match
+                "mode" | "engine" => group.engine_mode.as_ref()
+                    .is_some_and(|mode| mode.to_lowercase().contains(value)),
+                "reqsize" => size_matches(group.request_size, value),
+                "respsize" => size_matches(group.response_size, value),
+                "status" | "http" => status_matches(group.http_status.code(), value),
+                _ => return self.matches_all_fields(group, term),
+            };
+            matched != negate
+        } else if self.fuzzy {
+            self.fuzzy_score(group, term).is_some()
+        } else {
+            self.matches_all_fields(group, term)
+        }
+    }
+
+    /// Match `token=value`: the whole value instead of a substring or
+    /// prefix, for pivots (`ip=10.0.0.1` leaves out 10.0.0.10). `None` for
+    /// tokens without an exact form.
+    fn matches_exact(&self, group: &AuditGroup, token: &str, value: &str) -> Option<bool> {
+        Some(match token {
+            "domain" => group.domain.eq_ignore_ascii_case(value),
+            "ip" => group.client_ip.eq_ignore_ascii_case(value),
+            "rule" | "ruleid" | "id" => group.primary_rule_ids.iter().any(|id| id == value),
+            "auditid" => group.base_id.eq_ignore_ascii_case(value),
+            "cat" | "category" => group_categories(group).contains(&value),
+            // The AS number (`asn=as13335`) or the whole "AS13335 Cloudflare"
+            "asn" | "as" => self.ip_api_cache.cached(&group.client_ip)
+                .and_then(|geo| geo.as_info)
+                .is_some_and(|info| {
+                    info.eq_ignore_ascii_case(value)
+                        || info.split_whitespace().next().is_some_and(|number| number.eq_ignore_ascii_case(value))
+                }),
+            // Already whole codes or classes
+            "status" | "http" => status_matches(group.http_status.code(), value),
+            _ => return None,
+        })
+    }
+
+    /// What the table should highlight for the current query, per column:
+    /// the value of a `domain:`, `ip:`, `rule:`, `auditid:` or `cat:` term in
+    /// its own column, and a plain term in every column it is matched
+    /// against. Other tokens, negated terms and fuzzy queries highlight
+    /// nothing.
+    pub fn search_highlights(&self) -> Vec<(Column, String)> {
+        let mut highlights = Vec::new();
+        for term in self.search_query.to_lowercase().split_whitespace() {
+            let (columns, value): (&[Column], &str) = match split_token(term) {
+                Some(("domain", _, value)) => (&[Column::Domain], value),
+                Some(("ip", _, value)) => (&[Column::ClientIp], value),
+                Some(("rule" | "ruleid" | "id", _, value)) => (&[Column::RuleIds], value),
+                Some(("auditid", _, value)) => (&[Column::AuditId], value),
+                Some(("cat" | "category", _, value)) => (&[Column::Category], value),
+                Some(_) => continue,
+                None if self.fuzzy => continue,
+                None => (&[Column::Domain, Column::ClientIp, Column::AuditId, Column::RuleIds, Column::Status], term),
+            };
+            if !value.is_empty() {
+                highlights.extend(columns.iter().map(|&column| (column, value.to_string())));
+            }
+        }
+        highlights
+    }
+
+    fn matches_all_fields(&self, group: &AuditGroup, query: &str) -> bool {
+        group.domain.to_lowercase().contains(query) ||
+        group.client_ip.contains(query) ||
+        group.base_id.to_lowercase().contains(query) ||
+        group.primary_rule_ids.iter().any(|id| id.contains(query)) ||
+        group.http_status.code().is_some_and(|s| s.to_string().contains(query))
+    }
+
+    pub fn show_detail_view(&mut self) {
+        // Nothing to show when the filter matched nothing
+        if self.selected_group().is_none() {
+            self.status_message = Some("No entry selected".to_string());
+            return;
+        }
+        self.current_view = AppView::DetailView;
+        // Coming back to the same transaction keeps its place; the reading
+        // mode (raw, sections, wrap, sideways scroll) stays for the session
+        let base_id = self.selected_group().map(|g| g.base_id.clone());
+        if base_id != self.detail_group {
+            self.detail_scroll = 0;
+            self.reset_detail_parts();
+            self.detail_group = base_id;
+        }
+
+        // Fetch IP info when entering detail view
+        self.request_ip_info();
+    }
+
+    /// Move to the previous/next entry from the detail view, remembering the
+    /// scroll position of the group being left and restoring the new one's
+    pub fn step_detail_entry(&mut self, forward: bool) {
+        if let Some(base_id) = self.selected_group().map(|g| g.base_id.clone()) {
+            self.detail_scrolls.insert(base_id, self.detail_scroll);
+        }
+
+        if forward {
+            // Keeps the table scroll in step, using the height from its last draw
+            self.move_selection_down(self.visible_height);
+        } else {
+            self.move_selection_up();
+        }
+
+        self.detail_scroll = self.selected_group()
+            .and_then(|g| self.detail_scrolls.get(&g.base_id).copied())
+            .unwrap_or(0);
+        self.detail_group = self.selected_group().map(|g| g.base_id.clone());
+        self.reset_detail_parts();
+        // Fetch new IP info for the new entry
+        self.request_ip_info();
+    }
+
+    /// Load the selected IP's info now when its subnet is cached, otherwise
+    /// leave it to `load_pending_ip_info` so a frame saying "Fetching
+    /// geolocation…" is drawn before the blocking request
+    fn request_ip_info(&mut self) {
+        let cached = self.selected_group()
+            .is_some_and(|group| self.ip_api_cache.cached(&group.client_ip).is_some());
+        if self.ip_api_enabled && !cached {
+            self.current_ip_info = None;
+            self.current_ptr = None;
+            self.current_abuse = None;
+            self.ip_info_pending = true;
+        } else {
+            self.load_selected_ip_info();
+        }
+    }
+
+    /// Make the lookup `request_ip_info` put off, if any. Called by the event
+    /// loop after each draw.
+    pub fn load_pending_ip_info(&mut self) {
+        if self.ip_info_pending {
+            self.load_selected_ip_info();
+        }
+    }
+
+    /// Fetch geolocation and reverse DNS for the selected group's client IP
+    pub fn load_selected_ip_info(&mut self) {
+        self.ip_info_pending = false;
+        let client_ip = match self.selected_group() {
+            Some(group) if self.ip_api_enabled => group.client_ip.clone(),
+            _ => {
+                self.current_ip_info = None;
+                self.current_ptr = None;
+                self.current_abuse = None;
+                return;
+            }
+        };
+        // Only shown without a request when an earlier `a` covered the network
+        self.current_abuse = self.rdap_cache.cached(&client_ip);
+        self.current_ip_info = match self.ip_api_cache.get_ip_info(&client_ip) {
+            Ok(info) => Some(info),
+            // Say so once and stop, rather than wait on every entry
+            Err(e) if ipapi::is_offline(&e) => {
+                self.ip_api_enabled = false;
+                self.status_message = Some("Geolocation unavailable (offline); IP lookups are off (press I to retry)".to_string());
+                self.current_ptr = None;
+                return;
+            }
+            Err(_) => None,
+        };
+        self.current_ptr = self.rdns_cache.lookup(&client_ip);
+    }
+
+    /// Look up the abuse contact of the selected group's network over RDAP
+    /// (`a` in the detail view) and show it in the IP panel
+    pub fn lookup_abuse_contact(&mut self) {
+        let Some(client_ip) = self.selected_group().map(|g| g.client_ip.clone()) else {
+            return;
+        };
+        if !self.ip_api_enabled {
+            self.status_message = Some("IP lookups are off (press I to enable)".to_string());
+            return;
+        }
+        self.status_message = Some(match self.rdap_cache.lookup(&client_ip) {
+            Ok(contact) => {
+                let message = if contact.emails.is_empty() {
+                    format!("No abuse contact listed for {}", client_ip)
+                } else {
+                    format!("Abuse contact for {}: {}", client_ip, contact.emails.join(", "))
+                };
+                self.current_abuse = Some(contact);
+                message
+            }
+            Err(e) if ipapi::is_offline(&e) => "Abuse lookup failed: registry unreachable (offline?)".to_string(),
+            Err(e) => format!("Abuse lookup failed: {}", e),
+        });
+    }
+
+    /// Turn ip-api lookups on or off mid-session. Cached results stay, but
+    /// nothing new is fetched while off.
+    pub fn toggle_ip_api(&mut self) {
+        self.ip_api_enabled = !self.ip_api_enabled;
+        if self.ip_api_enabled {
+            if self.current_view == AppView::DetailView {
+                self.request_ip_info();
+            }
+        } else {
+            self.current_ip_info = None;
+            self.current_ptr = None;
+            self.current_abuse = None;
+        }
+        self.status_message = Some(format!(
+            "IP lookups {}",
+            if self.ip_api_enabled { "enabled" } else { "disabled" }
+        ));
+    }
+
+    /// Copy text to the system clipboard and leave a note about the outcome
+    pub fn copy_to_clipboard(&mut self, text: &str, what: &str) {
+        self.status_message = Some(match clipboard::copy(text) {
+            Ok(()) => format!(
+                "Copied {} ({} bytes, {} lines) to clipboard",
+                what,
+                text.len(),
+                text.lines().count()
+            ),
+            Err(e) => format!("Copy failed: {}", e),
+        });
+    }
+
+    /// Copy the full raw audit chain of the selected group
+    pub fn copy_selected_chain(&mut self) {
+        match self.selected_group().map(|g| g.raw_chain()) {
+            Some(chain) => self.copy_to_clipboard(&chain, "audit chain"),
+            None => self.status_message = Some("No entry selected".to_string()),
+        }
+    }
+
+    /// Copy the selected transaction's request as a `curl` command (see
+    /// `AuditGroup::to_curl`)
+    pub fn copy_selected_curl(&mut self) {
+        let Some(group) = self.selected_group() else {
+            self.status_message = Some("No entry selected".to_string());
+            return;
+        };
+        match group.to_curl() {
+            Some(command) => self.copy_to_clipboard(&command, "curl command"),
+            None => self.status_message = Some("No request line (section B) to build a curl command from".to_string()),
+        }
+    }
+
+    /// `--report`: `compute_stats` for the current filter, as JSON in `path`
+    pub fn write_report(&self, path: &Path) -> Result<()> {
+        let write_error = || format!("Failed to write report {}", path.display());
+        let mut file = io::BufWriter::new(std::fs::File::create(path).with_context(write_error)?);
+        output::write_stats_report(&self.compute_stats(), &self.log_paths, &self.search_query, &mut file)
+            .with_context(write_error)?;
+        file.flush().with_context(write_error)
+    }
+
+    /// `export_groups` (the marked transactions, else the filtered ones) as
+    /// a GitHub-flavored Markdown table with the visible columns. Cells are
+    /// not shortened: full audit IDs and every rule ID.
+    pub fn export_markdown(&self) -> String {
+        let columns = self.visible_columns();
+        let headers: Vec<&str> = columns.iter().map(|column| column.title()).collect();
+        let rows: Vec<Vec<String>> = self.export_groups()
+            .into_iter()
+            .map(|group| columns.iter().map(|&column| self.export_cell(group, column)).collect())
+            .collect();
+        output::markdown_table(&headers, &rows)
+    }
+
+    /// Plain text of one table cell, as exported
+    fn export_cell(&self, group: &AuditGroup, column: Column) -> String {
+        match column {
+            Column::AuditId => group.base_id.clone(),
+            Column::Timestamp => group.first_timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            Column::Domain => group.domain.clone(),
+            Column::ClientIp => group.client_ip.clone(),
+            Column::Country => self.ip_api_cache.cached(&group.client_ip)
+                .and_then(|geo| geo.country_code)
+                .unwrap_or_default(),
+            Column::Status => {
+                let status = group.http_status.label();
+                if group.blocked { format!("{} ✖", status) } else { status }
+            }
+            Column::Action => action_badge(group.action.as_deref()).to_string(),
+            Column::Flags => self.ip_api_cache.cached(&group.client_ip)
+                .map(|geo| geo.flags())
+                .unwrap_or_default(),
+            Column::RuleIds => self.display_rule_ids(&group.primary_rule_ids).join(", "),
+            Column::Category => group_categories(group).join(", "),
+        }
+    }
+
+    /// `M`: copy `export_markdown` to the clipboard
+    pub fn copy_markdown(&mut self) {
+        let count = self.export_groups().len();
+        if count == 0 {
+            self.status_message = Some("Nothing to export".to_string());
+            return;
+        }
+        let marked = if self.marked.is_empty() { "" } else { "marked " };
+        let what = format!("{} {}transaction{} as Markdown", count, marked, if count == 1 { "" } else { "s" });
+        self.copy_to_clipboard(&self.export_markdown(), &what);
+    }
+
+    /// Open the selected group's client IP in the default browser using
+    /// `ip_url_template`. Fails fast (instead of hanging) without a desktop.
+    pub fn open_ip_in_browser(&self) -> Result<()> {
+        let group = self.selected_group()
+            .ok_or_else(|| anyhow::anyhow!("No entry selected"))?;
+
+        // Over SSH or on a headless box there is nothing to open a browser in
+        if cfg!(all(unix, not(target_os = "macos")))
+            && std::env::var_os("DISPLAY").is_none()
+            && std::env::var_os("WAYLAND_DISPLAY").is_none()
+        {
+            anyhow::bail!("no graphical session (DISPLAY/WAYLAND_DISPLAY unset)");
+        }
+
+        let url = ip_lookup_url(&self.ip_url_template, &group.client_ip);
+        // Detached so a slow or blocking opener never freezes the UI
+        open::that_detached(&url)?;
+        Ok(())
+    }
+
+    pub fn show_table_view(&mut self) {
+        self.current_view = AppView::TableView;
+        // Keep current_ip_info - it's just a copy of what's already cached
+    }
+
+    pub fn toggle_legend(&mut self) {
+        self.show_legend = !self.show_legend;
+    }
+
+    pub fn toggle_country(&mut self) {
+        self.show_country = !self.show_country;
+    }
+
+    /// The table's columns: the configured ones, with Country only while it
+    /// is toggled on (after Client IP when the list leaves it out)
+    pub fn visible_columns(&self) -> Vec<Column> {
+        let mut columns: Vec<Column> = self.columns.iter()
+            .copied()
+            .filter(|&c| c != Column::Country || self.show_country)
+            .collect();
+        if self.show_country && !columns.contains(&Column::Country) {
+            let at = columns.iter()
+                .position(|&c| c == Column::ClientIp)
+                .map_or(columns.len(), |i| i + 1);
+            columns.insert(at, Column::Country);
+        }
+        columns
+    }
+
+    /// Open the saved-filter picker over the table
+    pub fn open_filter_picker(&mut self) {
+        self.filter_picker = Some(FilterPicker::default());
+    }
+
+    pub fn close_filter_picker(&mut self) {
+        self.filter_picker = None;
+    }
+
+    pub fn move_filter_selection(&mut self, forward: bool) {
+        let last = self.saved_filters.len().saturating_sub(1);
+        if let Some(picker) = &mut self.filter_picker {
+            picker.selected = if forward {
+                (picker.selected + 1).min(last)
+            } else {
+                picker.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Save the current search under `name`, replacing a filter of the same
+    /// name, and write the list to `filters_path`
+    pub fn save_filter(&mut self, name: &str) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("a saved filter needs a name");
+        }
+        if self.search_query.trim().is_empty() {
+            anyhow::bail!("type a search first, there is nothing to save");
+        }
+        let query = self.search_query.clone();
+        match self.saved_filters.iter_mut().find(|(existing, _)| existing == name) {
+            Some(filter) => filter.1 = query,
+            None => self.saved_filters.push((name.to_string(), query)),
+        }
+        self.write_filters()
+    }
+
+    /// Replace the search with a saved filter's query and close the picker
+    pub fn apply_saved_filter(&mut self, index: usize) {
+        let Some((name, query)) = self.saved_filters.get(index).cloned() else {
+            return;
+        };
+        self.search_query = query;
+        self.search_cursor = self.search_query.len();
+        self.apply_search();
+        self.filter_picker = None;
+        self.status_message = Some(format!("Filter '{}': {} matches", name, self.filtered_groups.len()));
+    }
+
+    pub fn delete_saved_filter(&mut self, index: usize) -> Result<()> {
+        if index >= self.saved_filters.len() {
+            return Ok(());
+        }
+        self.saved_filters.remove(index);
+        if let Some(picker) = &mut self.filter_picker {
+            picker.selected = picker.selected.min(self.saved_filters.len().saturating_sub(1));
+        }
+        self.write_filters()
+    }
+
+    fn write_filters(&self) -> Result<()> {
+        match &self.filters_path {
+            Some(path) => config::save_filters(path, &self.saved_filters),
+            None => Ok(()),
+        }
+    }
+
+    pub fn toggle_short_ids(&mut self) {
+        self.short_ids = !self.short_ids;
+    }
+
+    /// `O`: show rule IDs in numeric order or as they fired
+    pub fn toggle_sort_rule_ids(&mut self) {
+        self.sort_rule_ids = !self.sort_rule_ids;
+        self.status_message = Some(if self.sort_rule_ids {
+            "Rule IDs in numeric order".to_string()
+        } else {
+            "Rule IDs in the order they fired".to_string()
+        });
+    }
+
+    /// Rule IDs as the table, exports and parts layout show them: numeric
+    /// with `sort_rule_ids`, else first seen first. The stored order (and
+    /// so the info bar's first rule) never changes.
+    pub fn display_rule_ids<'a>(&self, ids: &'a [String]) -> Vec<&'a str> {
+        if self.sort_rule_ids {
+            sorted_rule_ids(ids)
+        } else {
+            ids.iter().map(String::as_str).collect()
+        }
+    }
+
+    pub fn toggle_dense(&mut self) {
+        self.dense = !self.dense;
+    }
+
+    /// Area of the table's data rows (excluding borders and the header
+    /// row), derived from the last rendered table area
+    pub fn table_body(&self) -> Option<ratatui::layout::Rect> {
+        let area = self.table_area?;
+        // Bordered: top border + header, bottom border. Dense: header only
+        let (top, chrome) = if self.dense { (1, 1) } else { (2, 3) };
+        Some(ratatui::layout::Rect::new(
+            area.x,
+            area.y + top.min(area.height),
+            area.width,
+            area.height.saturating_sub(chrome),
+        ))
+    }
+
+    /// Start resizing a column when the header separator to its right is
+    /// pressed at (`x`, `y`), give or take a cell. A double-click on it
+    /// hands the column back to automatic sizing instead. Returns whether
+    /// the press was on a separator.
+    pub fn start_column_drag(&mut self, x: u16, y: u16) -> bool {
+        self.start_column_drag_at(x, y, Instant::now())
+    }
+
+    /// `start_column_drag` with an explicit press time
+    pub fn start_column_drag_at(&mut self, x: u16, y: u16, now: Instant) -> bool {
+        let Some(area) = self.table_area else {
+            return false;
+        };
+        // Bordered: the header row is below the top border
+        if y != area.y + u16::from(!self.dense) {
+            return false;
+        }
+        let Some(&(column, _, width)) = self.column_edges
+            .iter()
+            .filter(|(_, edge, _)| edge.abs_diff(x) <= 1)
+            .min_by_key(|(_, edge, _)| edge.abs_diff(x))
+        else {
+            return false;
+        };
+
+        let window = Duration::from_millis(self.double_click_ms);
+        if self.last_edge_click.is_some_and(|(last, at)| last == column && now.duration_since(at) < window) {
+            self.column_widths.remove(&column);
+            self.last_edge_click = None;
+            self.column_drag = None;
+        } else {
+            self.last_edge_click = Some((column, now));
+            self.column_drag = Some((column, x, width));
+        }
+        true
+    }
+
+    /// Follow a drag started by `start_column_drag` to column `x`
+    pub fn drag_column(&mut self, x: u16) {
+        let Some((column, from, width)) = self.column_drag else {
+            return;
+        };
+        let max = self.table_area.map_or(u16::MAX, |area| area.width);
+        let width = (i32::from(width) + i32::from(x) - i32::from(from))
+            .clamp(i32::from(MIN_COLUMN_WIDTH), i32::from(max.max(MIN_COLUMN_WIDTH)));
+        self.column_widths.insert(column, width as u16);
+    }
+
+    pub fn end_column_drag(&mut self) {
+        self.column_drag = None;
+    }
+
+    /// Hand the mouse to the terminal (native drag-select and copy) or take
+    /// it back for row clicks
+    pub fn toggle_mouse_passthrough(&mut self) {
+        self.mouse_passthrough = !self.mouse_passthrough;
+        self.status_message = Some(if !self.mouse_capture {
+            "Mouse capture is off (--no-mouse)".to_string()
+        } else if self.mouse_passthrough {
+            "Mouse passthrough: select text natively, m to capture clicks again".to_string()
+        } else {
+            "Mouse clicks select rows again".to_string()
+        });
+    }
+
+    /// Whether `run_app` should capture the mouse right now
+    pub fn wants_mouse(&self) -> bool {
+        self.mouse_capture && !self.mouse_passthrough && self.current_view == AppView::TableView
+    }
+
+    pub fn toggle_row_tint(&mut self) {
+        self.row_tint = !self.row_tint;
+    }
+
+    /// Roll the currently filtered groups up into one row per domain,
+    /// most hits first
+    pub fn group_by_domain(&self) -> Vec<DomainSummary> {
+        let mut by_domain: HashMap<&str, Vec<&AuditGroup>> = HashMap::new();
+        for group in self.filtered_transactions() {
+            by_domain.entry(group.domain.as_str()).or_default().push(group);
+        }
+
+        let mut summaries: Vec<DomainSummary> = by_domain
+            .into_iter()
+            .map(|(domain, groups)| {
+                let distinct_ips = groups.iter()
+                    .map(|g| g.client_ip.as_str())
+                    .collect::<HashSet<_>>()
+                    .len();
+
+                let mut rule_counts: HashMap<&str, usize> = HashMap::new();
+                for rule_id in groups.iter().flat_map(|g| &g.primary_rule_ids) {
+                    *rule_counts.entry(rule_id.as_str()).or_default() += 1;
+                }
+                // Highest count wins, ties broken by lowest rule ID for stable output
+                let top_rule = rule_counts
+                    .into_iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                    .map(|(id, _)| id.to_string());
+
+                DomainSummary {
+                    domain: domain.to_string(),
+                    hits: groups.len(),
+                    distinct_ips,
+                    top_rule,
+                }
+            })
+            .collect();
+
+        summaries.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.domain.cmp(&b.domain)));
+        summaries
+    }
+
+    /// Peak request rate of every client IP across all loaded transactions
+    pub fn client_ip_rates(&self) -> HashMap<String, f64> {
+        ip_rates(&self.audit_groups)
+    }
+
+    /// Whether `ip` sent requests faster than `rate_threshold` at some point
+    pub fn is_noisy_ip(&self, ip: &str) -> bool {
+        self.ip_rates.get(ip).is_some_and(|&rate| rate >= self.rate_threshold)
+    }
+
+    /// Status classes, top IPs/rules/categories/domains and ASNs of the filtered groups.
+    /// ASNs come from the geolocation cache, so only resolved IPs count.
+    pub fn compute_stats(&self) -> Stats {
+        let groups = self.filtered_transactions();
+
+        let status_classes = top_counts(groups.iter().map(|g| match g.http_status {
+            HttpStatus::Code(s) if (200..300).contains(&s) => "2xx",
+            HttpStatus::Code(s) if (300..400).contains(&s) => "3xx",
+            HttpStatus::Code(s) if (400..500).contains(&s) => "4xx",
+            HttpStatus::Code(s) if (500..600).contains(&s) => "5xx",
+            HttpStatus::Code(_) => "other",
+            HttpStatus::Missing => "no response",
+            HttpStatus::Unparsed => "unparsed",
+        }));
+
+        let mut asns = Vec::new();
+        let mut unresolved_geo = 0;
+        for group in &groups {
+            match self.ip_api_cache.cached(&group.client_ip).and_then(|geo| geo.as_info) {
+                Some(info) => asns.push(info),
+                None => unresolved_geo += 1,
+            }
+        }
+
+        // Only IPs with more than one request in a window are worth listing
+        let single = 1.0 / RATE_WINDOW.as_secs_f64();
+        let mut noisiest_ips: Vec<(String, f64)> = ip_rates(groups.iter().copied())
+            .into_iter()
+            .filter(|&(_, rate)| rate > single)
+            .collect();
+        noisiest_ips.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        noisiest_ips.truncate(STATS_TOP_N);
+
+        let first = groups.iter().map(|g| g.first_timestamp).min();
+        let last = groups.iter().map(|g| g.first_timestamp).max();
+
+        Stats {
+            total: groups.len(),
+            blocked: groups.iter().filter(|g| g.blocked).count(),
+            time_range: first.zip(last),
+            status_classes,
+            top_ips: top_counts(groups.iter().map(|g| g.client_ip.as_str())),
+            top_rules: top_counts(groups.iter().flat_map(|g| &g.primary_rule_ids).map(String::as_str)),
+            top_categories: top_counts(groups.iter().flat_map(|g| group_categories(g))),
+            top_domains: top_counts(groups.iter().map(|g| g.domain.as_str())),
+            top_asns: top_counts(asns.iter().map(String::as_str)),
+            noisiest_ips,
+            unresolved_geo,
+        }
+    }
+
+    /// Hits of one rule among the filtered groups, per domain and client IP
+    pub fn stats_for_rule(&self, rule_id: &str) -> RuleStats {
+        let groups = self.filtered_transactions();
+        let mut domain_totals: HashMap<&str, usize> = HashMap::new();
+        for group in &groups {
+            *domain_totals.entry(group.domain.as_str()).or_default() += 1;
+        }
+        let matched: Vec<&AuditGroup> = groups.into_iter().filter(|g| fired_rule(g, rule_id)).collect();
+
+        let mut by_domain: HashMap<&str, Vec<&AuditGroup>> = HashMap::new();
+        let mut by_ip: HashMap<&str, Vec<&AuditGroup>> = HashMap::new();
+        for &group in &matched {
+            by_domain.entry(group.domain.as_str()).or_default().push(group);
+            by_ip.entry(group.client_ip.as_str()).or_default().push(group);
+        }
+
+        let mut domains: Vec<RuleDomainStats> = by_domain
+            .into_iter()
+            .map(|(domain, groups)| RuleDomainStats {
+                domain: domain.to_string(),
+                hits: groups.len(),
+                blocked: groups.iter().filter(|g| g.blocked).count(),
+                distinct_ips: groups.iter().map(|g| g.client_ip.as_str()).collect::<HashSet<_>>().len(),
+                share: groups.len() as f64 / domain_totals[domain] as f64,
+            })
+            .collect();
+        domains.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.domain.cmp(&b.domain)));
+
+        let mut client_ips: Vec<RuleIpStats> = by_ip
+            .into_iter()
+            .map(|(ip, groups)| RuleIpStats {
+                client_ip: ip.to_string(),
+                hits: groups.len(),
+                domains: groups.iter().map(|g| g.domain.as_str()).collect::<HashSet<_>>().len(),
+            })
+            .collect();
+        client_ips.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.client_ip.cmp(&b.client_ip)));
+
+        RuleStats {
+            rule_id: rule_id.to_string(),
+            transactions: matched.len(),
+            blocked: matched.iter().filter(|g| g.blocked).count(),
+            domains,
+            client_ips,
+        }
+    }
+
+    pub fn show_stats_view(&mut self) {
+        self.stats = self.compute_stats();
+        self.stats_scroll = 0;
+        self.stats_selected = 0;
+        self.current_view = AppView::StatsView;
+    }
+
+    /// The stats view rows `Enter` can pivot on, as (search token, value),
+    /// in the order the view lists them
+    pub fn stats_pivots(&self) -> Vec<(&'static str, String)> {
+        let stats = &self.stats;
+        let sections: [(&'static str, &[(String, usize)]); 6] = [
+            ("status", &stats.status_classes),
+            ("ip", &stats.top_ips),
+            ("rule", &stats.top_rules),
+            ("cat", &stats.top_categories),
+            ("domain", &stats.top_domains),
+            ("asn", &stats.top_asns),
+        ];
+        sections.iter()
+            .flat_map(|&(field, rows)| rows.iter().map(move |(key, _)| (field, key.as_str())))
+            .chain(stats.noisiest_ips.iter().map(|(ip, _)| ("ip", ip.as_str())))
+            .filter_map(|(field, key)| stats_pivot_value(field, key).map(|value| (field, value)))
+            .collect()
+    }
+
+    pub fn move_stats_selection(&mut self, delta: isize) {
+        let max_index = self.stats_pivots().len().saturating_sub(1);
+        self.stats_selected = self.stats_selected.saturating_add_signed(delta).min(max_index);
+    }
+
+    /// Return to the table filtered to the selected stats row
+    pub fn drill_into_stats(&mut self) {
+        if let Some((field, value)) = self.stats_pivots().into_iter().nth(self.stats_selected) {
+            self.pivot_to(field, &value);
+        }
+    }
+
+    pub fn show_domain_view(&mut self) {
+        self.domain_summaries = self.group_by_domain();
+        self.domain_selected = 0;
+        self.domain_scroll = 0;
+        self.current_view = AppView::DomainView;
+    }
+
+    pub fn move_domain_selection(&mut self, delta: isize, visible_height: usize) {
+        let max_index = self.domain_summaries.len().saturating_sub(1);
+        self.domain_selected = self.domain_selected.saturating_add_signed(delta).min(max_index);
+        if self.domain_selected < self.domain_scroll {
+            self.domain_scroll = self.domain_selected;
+        } else if self.domain_selected >= self.domain_scroll + visible_height {
+            self.domain_scroll = self.domain_selected.saturating_sub(visible_height.saturating_sub(1));
+        }
+    }
+
+    /// Return to the table filtered to the selected domain
+    pub fn drill_into_domain(&mut self) {
+        if let Some(summary) = self.domain_summaries.get(self.domain_selected) {
+            let domain = summary.domain.clone();
+            self.pivot_to("domain", &domain);
+        }
+    }
+
+    /// Show the table filtered to exactly `field=value` (e.g. everything one
+    /// client IP did), keeping the current transaction selected when it matches
+    pub fn pivot_to(&mut self, field: &str, value: &str) {
+        let saved_base_id = self.selected_group().map(|g| g.base_id.clone());
+        let (saved_selected_index, saved_scroll_offset) = (self.selected_index, self.scroll_offset);
+        self.search_query = format!("{}={}", field, value);
+        self.search_cursor = self.search_query.len();
+        self.apply_search();
+        self.restore_selection(saved_base_id, saved_selected_index, saved_scroll_offset);
+        self.current_view = AppView::TableView;
+    }
+
+    /// `pivot_to` on the selected transaction's client IP, domain or first
+    /// rule ID (`field` is the search token)
+    pub fn pivot_on_selected(&mut self, field: &str) {
+        let Some(group) = self.selected_group() else {
+            return;
+        };
+        let value = match field {
+            "ip" => Some(group.client_ip.clone()),
+            "domain" => Some(group.domain.clone()),
+            "rule" => group.primary_rule_ids.first().cloned(),
+            _ => None,
+        };
+        match value {
+            Some(value) => self.pivot_to(field, &value),
+            None => self.status_message = Some(format!("No {} to pivot on", field)),
+        }
+    }
+
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    pub fn handle_click(&mut self, row: usize, visible_height: usize) -> bool {
+        self.handle_click_at(row, visible_height, Instant::now())
+    }
+
+    /// `handle_click` with an explicit click time. Returns true on the second
+    /// click of a double-click: same row, within `double_click_ms`.
+    pub fn handle_click_at(&mut self, row: usize, visible_height: usize, now: Instant) -> bool {
+        let window = Duration::from_millis(self.double_click_ms);
+        let should_open_detail = self.last_click_row == Some(row)
+            && self.last_click_time.is_some_and(|last| now.duration_since(last) < window);
 
         // Update selection
         self.selected_index = row;
@@ -260,10 +2485,77 @@ impl App {
             self.scroll_offset = self.selected_index.saturating_sub(visible_height - 1);
         }
 
-        // Update click tracking
-        self.last_click_time = Some(now);
-        self.last_click_row = Some(row);
+        // A click elsewhere starts a new window; a completed double-click
+        // leaves none open, so a third click doesn't count as another
+        if should_open_detail {
+            self.last_click_time = None;
+            self.last_click_row = None;
+        } else {
+            self.last_click_time = Some(now);
+            self.last_click_row = Some(row);
+        }
 
         should_open_detail
     }
 }
+
+/// Whether ESC (or q) was pressed, without blocking
+fn skip_requested() -> bool {
+    while event::poll(Duration::ZERO).unwrap_or(false) {
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// A 4xx or 5xx response, the target of `n`/`p` in the table
+pub fn is_error_status(group: &AuditGroup) -> bool {
+    group.http_status.code().is_some_and(|status| status >= 400)
+}
+
+/// Parse the logs without a terminal or loading screen (follow mode and
+/// headless output)
+pub fn read_groups(log_paths: &[impl AsRef<str>], concurrent: bool, parser: &AuditLogParser) -> Result<Vec<AuditGroup>> {
+    parser.parse_files(&log_files(log_paths, concurrent, parser)?)
+}
+
+/// `read_groups`, handing each transaction to `on_group` as soon as it is
+/// complete instead of collecting them (see `AuditLogParser::stream_groups`)
+pub fn stream_groups<F>(log_paths: &[impl AsRef<str>], concurrent: bool, parser: &AuditLogParser, on_group: F) -> Result<()>
+where
+    F: FnMut(AuditGroup) -> Result<()>,
+{
+    parser.stream_groups(&log_files(log_paths, concurrent, parser)?, on_group)
+}
+
+/// The files to read: the logs themselves, or the part files below
+/// concurrent-mode index files and directories
+fn log_files(log_paths: &[impl AsRef<str>], concurrent: bool, parser: &AuditLogParser) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for log_path in log_paths {
+        let log_path = log_path.as_ref();
+        if concurrent {
+            files.extend(parser.concurrent_part_files(Path::new(log_path))?);
+        } else {
+            files.push(PathBuf::from(log_path));
+        }
+    }
+    Ok(files)
+}
+
+/// Parse the logs as serial files or concurrent-mode logs, with the loading screen
+fn load_groups(
+    log_paths: &[String],
+    concurrent: bool,
+    parser: &AuditLogParser,
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+) -> Result<Vec<AuditGroup>> {
+    if concurrent {
+        parser.parse_concurrent(log_paths, terminal)
+    } else {
+        parser.parse_log_files(log_paths, terminal)
+    }
+}
@@ -0,0 +1,17 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::io::{self, Write};
+
+/// Build the OSC 52 escape sequence that asks the terminal to put `text` on
+/// the system clipboard. Works over SSH since the terminal does the copying.
+pub fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+/// Copy `text` to the clipboard via OSC 52 on stdout
+pub fn copy(text: &str) -> Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -1,11 +1,68 @@
 use ratatui::style::Color;
 
+/// Which palette `ColorScheme::detect` picks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    Colors256,
+    Colors16,
+    Light, // Dark base colors for light terminal backgrounds
+}
+
+/// Decide the palette for `ColorScheme::detect`, with the reason for
+/// `--diagnostics`: `--light` first, then the background in `COLORFGBG`,
+/// then `color_support`
+pub fn palette(light: bool, colorfgbg: Option<&str>, term: Option<&str>, colorterm: Option<&str>) -> (ColorSupport, String) {
+    if light {
+        return (ColorSupport::Light, "--light".to_string());
+    }
+    // "fg;bg" or "fg;default;bg" with ANSI color numbers: 7 (white) and
+    // 9-15 (bright colors) are light, 0-6 and 8 (dark gray) are not
+    if let Some(colorfgbg) = colorfgbg {
+        let background = colorfgbg.rsplit(';').next().and_then(|bg| bg.trim().parse::<u8>().ok());
+        if matches!(background, Some(7 | 9..=15)) {
+            return (ColorSupport::Light, format!("COLORFGBG={}", colorfgbg));
+        }
+    }
+    color_support(term, colorterm)
+}
+
+/// Decide the palette from `TERM`/`COLORTERM`, with the reason for `--diagnostics`
+pub fn color_support(term: Option<&str>, colorterm: Option<&str>) -> (ColorSupport, String) {
+    // Check COLORTERM environment variable for truecolor/256color support
+    if let Some(colorterm) = colorterm {
+        if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+            return (ColorSupport::Colors256, format!("COLORTERM={}", colorterm));
+        }
+    }
+
+    // Check TERM environment variable
+    if let Some(term) = term {
+        if term.contains("256color") {
+            return (ColorSupport::Colors256, format!("TERM={}", term));
+        } else if term.contains("16color") || term.contains("color") {
+            return (ColorSupport::Colors16, format!("TERM={}", term));
+        }
+    }
+
+    // Default to 16-color scheme for better compatibility
+    (ColorSupport::Colors16, "default, neither TERM nor COLORTERM advertises more".to_string())
+}
+
+/// One line of the in-app color legend (`?`)
+pub struct LegendItem {
+    pub sample: &'static str, // Drawn in `color`
+    pub color: Color,
+    pub background: bool, // `color` is a row tint, drawn behind the sample
+    pub meaning: &'static str,
+}
+
 /// Color scheme that adapts to terminal capabilities
 pub struct ColorScheme {
     // UI Chrome
     pub title: Color,
     pub help_text: Color,
     pub search_highlight: Color,
+    pub no_matches: Color, // Search bar match count when it is zero
 
     // Table headers
     pub header: Color,
@@ -24,6 +81,10 @@ pub struct ColorScheme {
     pub status_5xx: Color,  // Server error (500-599)
     pub status_unknown: Color,
 
+    // Whole-row background tints (table view, opt-in)
+    pub row_tint_4xx: Color,
+    pub row_tint_5xx: Color,
+
     // Selection/highlight
     pub selected_bg: Color,
     pub selected_fg: Color,
@@ -44,34 +105,72 @@ impl ColorScheme {
     /// Get color for HTTP status code based on its value
     pub fn status_color(&self, status: Option<u16>) -> Color {
         match status {
-            Some(code) if code >= 200 && code < 300 => self.status_2xx,
-            Some(code) if code >= 300 && code < 400 => self.status_3xx,
-            Some(code) if code >= 400 && code < 500 => self.status_4xx,
-            Some(code) if code >= 500 && code < 600 => self.status_5xx,
+            Some(code) if (200..300).contains(&code) => self.status_2xx,
+            Some(code) if (300..400).contains(&code) => self.status_3xx,
+            Some(code) if (400..500).contains(&code) => self.status_4xx,
+            Some(code) if (500..600).contains(&code) => self.status_5xx,
             _ => self.status_unknown,
         }
     }
 
-    /// Detect terminal color support and return appropriate scheme
-    pub fn detect() -> Self {
-        // Check COLORTERM environment variable for truecolor/256color support
-        if let Ok(colorterm) = std::env::var("COLORTERM") {
-            if colorterm.contains("truecolor") || colorterm.contains("24bit") {
-                return Self::colors_256();
-            }
+    /// Background tint for a whole table row, only for error status classes
+    pub fn row_tint(&self, status: Option<u16>) -> Option<Color> {
+        match status {
+            Some(code) if (400..500).contains(&code) => Some(self.row_tint_4xx),
+            Some(code) if (500..600).contains(&code) => Some(self.row_tint_5xx),
+            _ => None,
         }
+    }
 
-        // Check TERM environment variable
-        if let Ok(term) = std::env::var("TERM") {
-            if term.contains("256color") {
-                return Self::colors_256();
-            } else if term.contains("16color") || term.contains("color") {
-                return Self::colors_16();
-            }
-        }
+    /// What the colors mean, grouped under headings, in this scheme's colors
+    pub fn legend(&self) -> Vec<(&'static str, Vec<LegendItem>)> {
+        let item = |sample, color, meaning| LegendItem { sample, color, background: false, meaning };
+        let tint = |sample, color, meaning| LegendItem { sample, color, background: true, meaning };
+        vec![
+            ("Status", vec![
+                item("200", self.status_2xx, "2xx success"),
+                item("301", self.status_3xx, "3xx redirect"),
+                item("403", self.status_4xx, "4xx client error"),
+                item("503", self.status_5xx, "5xx server error"),
+                item("—", self.status_unknown, "no response logged (no F section)"),
+                item("?", self.status_unknown, "response logged, status line not understood"),
+                item("403 ✖", self.status_4xx, "blocked, not only detected"),
+            ]),
+            ("Table", vec![
+                item("1a2b3c4d", self.audit_id, "audit ID (~ cut off, ×N collapsed, ⚠ shared)"),
+                item("2025-11-17", self.timestamp, "timestamp"),
+                item("example.com", self.domain, "domain (Host header), country"),
+                item("192.0.2.1 ▲", self.client_ip, "client IP, flags (▲ high request rate)"),
+                item("942100", self.rule_id, "rule IDs"),
+                item("✔", self.header, "marked row (v, then Space)"),
+                tint("  4xx row  ", self.row_tint_4xx, "row tint for 4xx (t)"),
+                tint("  5xx row  ", self.row_tint_5xx, "row tint for 5xx (t)"),
+            ]),
+            ("Detail view", vec![
+                item("GET /", self.http_method, "request line"),
+                item("example.com", self.host_header, "Host header value, JSON strings"),
+                item("curl/8.4.0", self.user_agent, "User-Agent header value"),
+                item("ModSecurity", self.modsec_message, "ModSecurity/OWASP messages"),
+                item("[id \"942100\"]", self.rule_id_detail, "rule message lines, rule hits"),
+                item("--1a2b-H--", self.boundary, "section boundaries"),
+                item("Content-Type:", self.header_name, "header names"),
+                item("Rule Hits", self.label, "headings and labels"),
+                item("UNION SELECT", self.search_highlight, "matched data, search"),
+            ]),
+        ]
+    }
 
-        // Default to 16-color scheme for better compatibility
-        Self::colors_16()
+    /// Detect terminal color support and background and return the
+    /// appropriate scheme; `light` (`--light`) wins over detection
+    pub fn detect(light: bool) -> Self {
+        let colorfgbg = std::env::var("COLORFGBG").ok();
+        let term = std::env::var("TERM").ok();
+        let colorterm = std::env::var("COLORTERM").ok();
+        match palette(light, colorfgbg.as_deref(), term.as_deref(), colorterm.as_deref()).0 {
+            ColorSupport::Colors256 => Self::colors_256(),
+            ColorSupport::Colors16 => Self::colors_16(),
+            ColorSupport::Light => Self::colors_light(),
+        }
     }
 
     /// 16-color scheme using bright variants (colors 8-15)
@@ -82,6 +181,7 @@ impl ColorScheme {
             title: Color::LightCyan,
             help_text: Color::DarkGray,
             search_highlight: Color::LightYellow,
+            no_matches: Color::Red,
 
             // Table headers
             header: Color::LightYellow,
@@ -100,6 +200,10 @@ impl ColorScheme {
             status_5xx: Color::Magenta,
             status_unknown: Color::DarkGray,
 
+            // Row tints - only the base colors are available here
+            row_tint_4xx: Color::Red,
+            row_tint_5xx: Color::Magenta,
+
             // Selection/highlight
             selected_bg: Color::White,
             selected_fg: Color::Black,
@@ -117,6 +221,54 @@ impl ColorScheme {
         }
     }
 
+    /// Scheme for light (white) backgrounds: the dark base colors, which
+    /// every terminal has, and black where the dark schemes use gray
+    fn colors_light() -> Self {
+        Self {
+            // UI Chrome
+            title: Color::Blue,
+            help_text: Color::Black,
+            search_highlight: Color::Magenta,
+            no_matches: Color::Red,
+
+            // Table headers
+            header: Color::Blue,
+
+            // Table row content
+            audit_id: Color::Green,
+            timestamp: Color::Blue,
+            domain: Color::Magenta,
+            client_ip: Color::Red,
+            rule_id: Color::Black,
+
+            // HTTP status colors
+            status_2xx: Color::Green,
+            status_3xx: Color::Cyan,
+            status_4xx: Color::Red,
+            status_5xx: Color::Magenta,
+            status_unknown: Color::Black,
+
+            // Row tints - pale, so the dark cell colors stay readable
+            row_tint_4xx: Color::LightRed,
+            row_tint_5xx: Color::LightMagenta,
+
+            // Selection/highlight
+            selected_bg: Color::Blue,
+            selected_fg: Color::White,
+
+            // Detail view
+            label: Color::Blue,
+            http_method: Color::Green,
+            http_status: Color::Blue,
+            host_header: Color::Magenta,
+            user_agent: Color::Green,
+            modsec_message: Color::Red,
+            rule_id_detail: Color::Magenta,
+            boundary: Color::Black,
+            header_name: Color::Blue,
+        }
+    }
+
     /// 256-color scheme with more nuanced colors
     fn colors_256() -> Self {
         Self {
@@ -124,6 +276,7 @@ impl ColorScheme {
             title: Color::Indexed(117),        // Light cyan blue
             help_text: Color::Indexed(240),    // Dark gray
             search_highlight: Color::Indexed(226), // Bright yellow
+            no_matches: Color::Indexed(196),       // Red
 
             // Table headers
             header: Color::Indexed(214),       // Orange-yellow
@@ -142,6 +295,10 @@ impl ColorScheme {
             status_5xx: Color::Indexed(170),   // Purple/magenta (server error)
             status_unknown: Color::Indexed(240), // Dark gray
 
+            // Row tints - very dark so cell colors stay readable
+            row_tint_4xx: Color::Indexed(52),  // Dark red
+            row_tint_5xx: Color::Indexed(53),  // Dark purple
+
             // Selection/highlight
             selected_bg: Color::Indexed(237),  // Dark gray background
             selected_fg: Color::Indexed(231),  // Almost white foreground
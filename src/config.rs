@@ -0,0 +1,165 @@
+use crate::app::{parse_columns, parse_duration, parse_poll_interval};
+use crate::ipapi::{parse_geo_fields, GeoProviderKind};
+use crate::parser::{parse_a_section_format, parse_trusted_ip_headers};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Defaults read from `~/.config/waflens/config.toml`. Every key is optional
+/// and mirrors a command-line flag; flags given on the command line win.
+///
+/// ```toml
+/// ip-api = false
+/// dense = true
+/// min-status = 400
+/// ip-url = "https://www.abuseipdb.com/check/{ip}"
+/// columns = "timestamp,domain,client-ip,status,rule-ids"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub concurrent: Option<bool>,
+    pub max_entries: Option<usize>,
+    pub split_on_ip: Option<bool>,
+    pub report_stray: Option<bool>,
+    pub trust_xff: Option<bool>,
+    pub trusted_ip_headers: Option<String>, // Comma-separated, tried in order
+    pub a_section_format: Option<String>,
+    pub min_status: Option<u16>,
+    pub max_status: Option<u16>,
+    pub since: Option<String>,
+    pub ip_api: Option<bool>,
+    pub geo_prefetch: Option<bool>,
+    pub geo_provider: Option<GeoProviderKind>,
+    pub geo_token: Option<String>,
+    pub geo_fields: Option<String>,
+    pub geo_full: Option<bool>,
+    pub dense: Option<bool>,
+    pub country: Option<bool>,
+    pub short_ids: Option<bool>,
+    pub sort_rule_ids: Option<bool>,
+    pub rate_threshold: Option<f64>,
+    pub columns: Option<String>,
+    pub ip_url: Option<String>,
+    pub filter: Option<String>,
+    pub double_click_ms: Option<u64>,
+    pub no_mouse: Option<bool>,
+    pub follow: Option<bool>,
+    pub poll_interval: Option<String>,
+    pub light: Option<bool>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/waflens/config.toml`, falling back to `~/.config`
+    /// (`%APPDATA%` on Windows)
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+        Some(base.join("waflens").join("config.toml"))
+    }
+
+    /// Read the config file; a missing file means no defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Cannot read config '{}'", path.display())),
+        };
+        Self::parse(&text).with_context(|| format!("Invalid config '{}'", path.display()))
+    }
+
+    /// Parse and validate config text (same limits as the flags)
+    pub fn parse(text: &str) -> Result<Self> {
+        let config: Self = toml::from_str(text)?;
+        for (key, status) in [("min-status", config.min_status), ("max-status", config.max_status)] {
+            if status.is_some_and(|s| !(100..=599).contains(&s)) {
+                bail!("{} must be between 100 and 599", key);
+            }
+        }
+        if let Some(since) = &config.since {
+            parse_duration(since).context("since")?;
+        }
+        if let Some(columns) = &config.columns {
+            parse_columns(columns).context("columns")?;
+        }
+        if let Some(headers) = &config.trusted_ip_headers {
+            parse_trusted_ip_headers(headers).context("trusted-ip-headers")?;
+        }
+        if let Some(format) = &config.a_section_format {
+            parse_a_section_format(format).context("a-section-format")?;
+        }
+        if let Some(fields) = &config.geo_fields {
+            parse_geo_fields(fields).context("geo-fields")?;
+        }
+        if let Some(interval) = &config.poll_interval {
+            parse_poll_interval(interval).context("poll-interval")?;
+        }
+        if config.rate_threshold.is_some_and(|rate| !(rate > 0.0 && rate.is_finite())) {
+            bail!("rate-threshold must be greater than zero");
+        }
+        if config.max_entries == Some(0) {
+            bail!("max-entries must be at least 1");
+        }
+        if config.double_click_ms == Some(0) {
+            bail!("double-click-ms must be at least 1");
+        }
+        Ok(config)
+    }
+}
+
+/// `filters.toml`, next to the config file: named search queries saved from
+/// the filter picker (`f`)
+///
+/// ```toml
+/// [[filter]]
+/// name = "sqli blocks"
+/// query = "rule:942 blocked:true"
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FiltersFile {
+    #[serde(default)]
+    filter: Vec<SavedFilterEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SavedFilterEntry {
+    name: String,
+    query: String,
+}
+
+/// Where saved filters live: `filters.toml` beside `Config::default_path()`
+pub fn filters_path() -> Option<PathBuf> {
+    Config::default_path().map(|path| path.with_file_name("filters.toml"))
+}
+
+/// Read saved filters as (name, query) pairs; a missing file means none
+pub fn load_filters(path: &Path) -> Result<Vec<(String, String)>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Cannot read saved filters '{}'", path.display())),
+    };
+    let file: FiltersFile = toml::from_str(&text)
+        .with_context(|| format!("Invalid saved filters '{}'", path.display()))?;
+    Ok(file.filter.into_iter().map(|entry| (entry.name, entry.query)).collect())
+}
+
+/// Write saved filters, creating the config directory if needed
+pub fn save_filters(path: &Path, filters: &[(String, String)]) -> Result<()> {
+    let file = FiltersFile {
+        filter: filters.iter()
+            .map(|(name, query)| SavedFilterEntry { name: name.clone(), query: query.clone() })
+            .collect(),
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Cannot create '{}'", dir.display()))?;
+    }
+    fs::write(path, toml::to_string(&file)?)
+        .with_context(|| format!("Cannot write saved filters '{}'", path.display()))
+}
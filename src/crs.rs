@@ -0,0 +1,165 @@
+//! Short descriptions for OWASP Core Rule Set rule IDs.
+//!
+//! Covers the commonly seen CRS rules individually, then falls back to the
+//! category implied by the rule's thousand-range (e.g. 942xxx is SQL
+//! injection). Unknown IDs return `None` and are shown as plain numbers.
+//! The attack ranges also have a short category name (`sqli`, `xss`) for
+//! the Category column, the `cat:` search token and the statistics.
+
+/// Individually known CRS rules
+const RULES: &[(&str, &str)] = &[
+    ("911100", "Method is not allowed by policy"),
+    ("913100", "Found User-Agent associated with security scanner"),
+    ("913101", "Found User-Agent associated with scripting/generic HTTP client"),
+    ("913102", "Found User-Agent associated with web crawler/bot"),
+    ("913110", "Found request header associated with security scanner"),
+    ("913120", "Found request filename/argument associated with security scanner"),
+    ("920100", "Invalid HTTP Request Line"),
+    ("920170", "GET or HEAD Request with Body Content"),
+    ("920180", "POST without Content-Length or Transfer-Encoding headers"),
+    ("920220", "URL Encoding Abuse Attack Attempt"),
+    ("920230", "Multiple URL Encoding Detected"),
+    ("920270", "Invalid character in request (null character)"),
+    ("920280", "Request Missing a Host Header"),
+    ("920290", "Empty Host Header"),
+    ("920300", "Request Missing an Accept Header"),
+    ("920310", "Request Has an Empty Accept Header"),
+    ("920320", "Missing User Agent Header"),
+    ("920330", "Empty User Agent Header"),
+    ("920340", "Request Containing Content, but Missing Content-Type header"),
+    ("920350", "Host header is a numeric IP address"),
+    ("920420", "Request content type is not allowed by policy"),
+    ("920430", "HTTP protocol version is not allowed by policy"),
+    ("920440", "URL file extension is restricted by policy"),
+    ("920450", "HTTP header is restricted by policy"),
+    ("921110", "HTTP Request Smuggling Attack"),
+    ("921120", "HTTP Response Splitting Attack"),
+    ("921130", "HTTP Response Splitting Attack"),
+    ("921150", "HTTP Header Injection Attack via payload (CR/LF detected)"),
+    ("930100", "Path Traversal Attack (/../)"),
+    ("930110", "Path Traversal Attack (/../)"),
+    ("930120", "OS File Access Attempt"),
+    ("930130", "Restricted File Access Attempt"),
+    ("931100", "Possible Remote File Inclusion (RFI) Attack: URL Parameter using IP Address"),
+    ("931110", "Possible Remote File Inclusion (RFI) Attack: Common RFI Vulnerable Parameter Name"),
+    ("931120", "Possible Remote File Inclusion (RFI) Attack: URL Payload Used w/Trailing Question Mark"),
+    ("932100", "Remote Command Execution: Unix Command Injection"),
+    ("932105", "Remote Command Execution: Unix Command Injection"),
+    ("932110", "Remote Command Execution: Windows Command Injection"),
+    ("932130", "Remote Command Execution: Unix Shell Expression Found"),
+    ("932150", "Remote Command Execution: Direct Unix Command Execution"),
+    ("932160", "Remote Command Execution: Unix Shell Code Found"),
+    ("933100", "PHP Injection Attack: PHP Open Tag Found"),
+    ("933110", "PHP Injection Attack: PHP Script File Upload Found"),
+    ("933120", "PHP Injection Attack: Configuration Directive Found"),
+    ("933130", "PHP Injection Attack: Variables Found"),
+    ("933150", "PHP Injection Attack: High-Risk PHP Function Name Found"),
+    ("933160", "PHP Injection Attack: High-Risk PHP Function Call Found"),
+    ("933210", "PHP Injection Attack: Variable Function Call Found"),
+    ("934100", "Node.js Injection Attack"),
+    ("941100", "XSS Attack Detected via libinjection"),
+    ("941110", "XSS Filter - Category 1: Script Tag Vector"),
+    ("941120", "XSS Filter - Category 2: Event Handler Vector"),
+    ("941130", "XSS Filter - Category 3: Attribute Vector"),
+    ("941140", "XSS Filter - Category 4: Javascript URI Vector"),
+    ("941160", "NoScript XSS InjectionChecker: HTML Injection"),
+    ("941180", "Node-Validator Deny List Keywords"),
+    ("942100", "SQL Injection Attack Detected via libinjection"),
+    ("942110", "SQL Injection Attack: Common Injection Testing Detected"),
+    ("942130", "SQL Injection Attack: SQL Tautology Detected"),
+    ("942150", "SQL Injection Attack: SQL function name detected"),
+    ("942160", "Detects blind sqli tests using sleep() or benchmark()"),
+    ("942190", "Detects MSSQL code execution and information gathering attempts"),
+    ("942200", "Detects MySQL comment-/space-obfuscated injections and backtick termination"),
+    ("942260", "Detects basic SQL authentication bypass attempts 2/3"),
+    ("942270", "Looking for basic sql injection. Common attack string for mysql, oracle and others"),
+    ("942340", "Detects basic SQL authentication bypass attempts 3/3"),
+    ("942360", "Detects concatenated basic SQL injection and SQLLFI attempts"),
+    ("942430", "Restricted SQL Character Anomaly Detection (args)"),
+    ("942440", "SQL Comment Sequence Detected"),
+    ("943100", "Possible Session Fixation Attack: Setting Cookie Values in HTML"),
+    ("944100", "Remote Command Execution: Suspicious Java class detected"),
+    ("949110", "Inbound Anomaly Score Exceeded"),
+    ("950100", "The Application Returned a 500-Level Status Code"),
+    ("951110", "Microsoft Access SQL Information Leakage"),
+    ("952100", "Java Source Code Leakage"),
+    ("953100", "PHP Information Leakage"),
+    ("954100", "Disclosure of IIS install location"),
+    ("959100", "Outbound Anomaly Score Exceeded"),
+    ("980130", "Inbound Anomaly Score Exceeded (correlation)"),
+    ("980140", "Outbound Anomaly Score Exceeded (correlation)"),
+];
+
+/// CRS categories by rule-ID thousand-range (the first three digits), with
+/// the short name `rule_category` gives the attack categories
+const RANGES: &[(&str, Option<&str>, &str)] = &[
+    ("901", None, "CRS initialization"),
+    ("905", None, "Common exceptions"),
+    ("910", Some("reputation"), "IP reputation"),
+    ("911", Some("method"), "Method enforcement"),
+    ("912", Some("dos"), "DoS protection"),
+    ("913", Some("scanner"), "Scanner detection"),
+    ("920", Some("protocol"), "Protocol enforcement"),
+    ("921", Some("protocol-attack"), "Protocol attack"),
+    ("922", Some("multipart"), "Multipart attack"),
+    ("930", Some("lfi"), "Local file inclusion"),
+    ("931", Some("rfi"), "Remote file inclusion"),
+    ("932", Some("rce"), "Remote code execution"),
+    ("933", Some("php"), "PHP injection"),
+    ("934", Some("generic"), "Generic/Node.js injection"),
+    ("941", Some("xss"), "XSS"),
+    ("942", Some("sqli"), "SQL injection"),
+    ("943", Some("session-fixation"), "Session fixation"),
+    ("944", Some("java"), "Java injection"),
+    ("949", None, "Inbound blocking evaluation"),
+    ("950", Some("leakage"), "Data leakage"),
+    ("951", Some("leakage-sql"), "SQL data leakage"),
+    ("952", Some("leakage-java"), "Java data leakage"),
+    ("953", Some("leakage-php"), "PHP data leakage"),
+    ("954", Some("leakage-iis"), "IIS data leakage"),
+    ("955", Some("webshell"), "Web shell detection"),
+    ("959", None, "Outbound blocking evaluation"),
+    ("980", None, "Correlation"),
+];
+
+/// Short description for a CRS rule ID: the rule's own description when
+/// known, otherwise its category range. `None` for non-CRS IDs.
+pub fn rule_description(id: &str) -> Option<&'static str> {
+    if let Some((_, desc)) = RULES.iter().find(|(rule, _)| *rule == id) {
+        return Some(desc);
+    }
+    range_of(id).map(|(_, _, desc)| *desc)
+}
+
+/// Short attack category of a CRS rule ID (`942100` is `sqli`, `941100`
+/// `xss`), as used by the `cat:` search token. `None` for non-CRS IDs and
+/// for the anomaly scoring and correlation rules, which fire along with
+/// whatever category tipped the score.
+pub fn rule_category(id: &str) -> Option<&'static str> {
+    range_of(id).and_then(|(_, category, _)| *category)
+}
+
+/// The description of a category `rule_category` returns
+pub fn category_description(category: &str) -> Option<&'static str> {
+    RANGES.iter()
+        .find(|(_, name, _)| *name == Some(category))
+        .map(|(_, _, desc)| *desc)
+}
+
+/// The thousand-range a CRS rule ID falls in
+fn range_of(id: &str) -> Option<&'static (&'static str, Option<&'static str>, &'static str)> {
+    // CRS IDs are six digits in the 9xxxxx range
+    if id.len() != 6 || !id.starts_with('9') || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    RANGES.iter().find(|(prefix, _, _)| id.starts_with(prefix))
+}
+
+/// `942100 (SQL Injection Attack Detected via libinjection)`, or just the
+/// ID when it has no known description
+pub fn describe(id: &str) -> String {
+    match rule_description(id) {
+        Some(desc) => format!("{} ({})", id, desc),
+        None => id.to_string(),
+    }
+}
@@ -0,0 +1,86 @@
+use crate::app::AppOptions;
+use crate::colors::{palette, ColorSupport};
+use crate::ipapi::{http_client, GeoProviderKind, DEFAULT_FIELDS};
+use crate::parser::AuditLogParser;
+use anyhow::Result;
+use clap::ValueEnum;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+/// Address looked up to check that the geolocation provider answers
+const PROBE_IP: &str = "8.8.8.8";
+
+/// `--diagnostics`: build info, the color palette and why it was chosen, the
+/// config file, whether the geolocation provider answers, and the parser's
+/// patterns. `config_error` is the reason an existing config was rejected.
+pub fn write_report(
+    out: &mut impl Write,
+    config_path: Option<&Path>,
+    config_error: Option<&str>,
+    options: &AppOptions,
+) -> Result<()> {
+    let profile = if cfg!(debug_assertions) { "debug" } else { "release" };
+    writeln!(
+        out,
+        "waflens {} ({} {}, {} build)",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        profile
+    )?;
+
+    let colorfgbg = std::env::var("COLORFGBG").ok();
+    let term = std::env::var("TERM").ok();
+    let colorterm = std::env::var("COLORTERM").ok();
+    let (support, reason) = palette(options.light, colorfgbg.as_deref(), term.as_deref(), colorterm.as_deref());
+    let scheme = match support {
+        ColorSupport::Colors256 => "256 colors",
+        ColorSupport::Colors16 => "16 colors",
+        ColorSupport::Light => "light background",
+    };
+    writeln!(out, "Colors:      {} ({})", scheme, reason)?;
+    writeln!(
+        out,
+        "             TERM={} COLORTERM={} COLORFGBG={}",
+        term.as_deref().unwrap_or("(unset)"),
+        colorterm.as_deref().unwrap_or("(unset)"),
+        colorfgbg.as_deref().unwrap_or("(unset)")
+    )?;
+
+    let config = match (config_path, config_error) {
+        (None, _) => "no config directory (HOME/XDG_CONFIG_HOME unset)".to_string(),
+        (Some(path), Some(error)) => format!("{} (invalid: {})", path.display(), error),
+        (Some(path), None) if path.is_file() => format!("{} (loaded)", path.display()),
+        (Some(path), None) => format!("{} (not found, using defaults)", path.display()),
+    };
+    writeln!(out, "Config:      {}", config)?;
+
+    let provider_name = options.geo_provider
+        .to_possible_value()
+        .map_or_else(String::new, |value| value.get_name().to_string());
+    let fields = options.geo_fields.as_deref().unwrap_or(DEFAULT_FIELDS);
+    let geo = if options.ip_api_enabled {
+        let started = Instant::now();
+        match options.geo_provider.provider(options.geo_token.clone()).lookup(&http_client(), PROBE_IP, fields) {
+            Ok(answer) if !answer.is_failure() => format!("reachable ({} ms)", started.elapsed().as_millis()),
+            Ok(answer) => format!(
+                "reachable, but the lookup failed: {}",
+                answer.message.as_deref().unwrap_or("no reason given")
+            ),
+            Err(e) => format!("unreachable: {}", e.root_cause()),
+        }
+    } else {
+        "not checked, lookups are disabled (--no-ip-api)".to_string()
+    };
+    writeln!(out, "Geolocation: {}, {}", provider_name, geo)?;
+    if options.geo_provider == GeoProviderKind::IpApi {
+        writeln!(out, "Geo fields:  {}", fields)?;
+    }
+
+    writeln!(out, "Parser patterns:")?;
+    for (name, pattern) in AuditLogParser::new().patterns() {
+        writeln!(out, "  {:<18}{}", name, pattern)?;
+    }
+    Ok(())
+}
@@ -1,77 +1,413 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::net::IpAddr;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr};
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IpApiResponse {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub query: Option<String>,
     pub status: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub continent: Option<String>,
     #[serde(rename = "continentCode")]
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub continent_code: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub country: Option<String>,
     #[serde(rename = "countryCode")]
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub country_code: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub region: Option<String>,
     #[serde(rename = "regionName")]
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub region_name: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub city: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub district: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub zip: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub lat: Option<f64>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub lon: Option<f64>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub offset: Option<i32>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub currency: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub isp: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub org: Option<String>,
     #[serde(rename = "as")]
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub as_info: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub asname: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mobile: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub proxy: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hosting: Option<bool>,
 }
 
+impl IpApiResponse {
+    /// A `status: "fail"` answer, as ip-api gives for addresses it can't locate
+    pub fn failed(query: &str, message: &str) -> Self {
+        Self {
+            query: Some(query.to_string()),
+            status: "fail".to_string(),
+            message: Some(message.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.status != "success"
+    }
+
+    /// Compact threat flags: `P` proxy, `H` hosting, `M` mobile (`-` if none)
+    pub fn flags(&self) -> String {
+        let flags: String = [(self.proxy, 'P'), (self.hosting, 'H'), (self.mobile, 'M')]
+            .iter()
+            .filter(|(set, _)| *set == Some(true))
+            .map(|(_, c)| *c)
+            .collect();
+        if flags.is_empty() {
+            "-".to_string()
+        } else {
+            flags
+        }
+    }
+}
+
+/// Every field ip-api can return (`--geo-full`)
+pub const FULL_FIELDS: &str = "query,status,message,continent,continentCode,country,countryCode,region,regionName,city,district,zip,lat,lon,timezone,offset,currency,isp,org,as,asname,mobile,proxy,hosting";
+
+/// Fields requested by default: what the table, search and stats use plus a
+/// short location/ISP summary for the detail view
+pub const DEFAULT_FIELDS: &str = "query,status,message,country,countryCode,regionName,city,isp,org,as,mobile,proxy,hosting";
+
+/// Fields every lookup needs to be understood (and cached) at all
+const REQUIRED_FIELDS: [&str; 3] = ["query", "status", "message"];
+
+/// Parse a comma-separated ip-api field list (`geo-fields`), adding the
+/// required `query,status,message` if missing
+pub fn parse_geo_fields(text: &str) -> Result<String> {
+    let known: Vec<&str> = FULL_FIELDS.split(',').collect();
+    let mut fields: Vec<&str> = REQUIRED_FIELDS.to_vec();
+    for field in text.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+        if !known.contains(&field) {
+            anyhow::bail!("unknown ip-api field '{}' (expected some of {})", field, FULL_FIELDS);
+        }
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    Ok(fields.join(","))
+}
+
+/// ip-api accepts at most 100 queries per batch request
+const BATCH_SIZE: usize = 100;
+
+/// How long a lookup waits for a connection before the network counts as
+/// unreachable (see `is_offline`)
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Longest a whole request may take, connection included
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sent with every provider request, so providers can tell who is asking
+pub const USER_AGENT: &str = concat!("waflens/", env!("CARGO_PKG_VERSION"));
+
+/// How long a failed lookup is remembered before it may be retried;
+/// successful ones are kept for the whole session
+pub const FAILED_LOOKUP_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Private, loopback, link-local, documentation and other reserved
+/// addresses. No provider can locate them, so they are answered locally.
+pub fn is_private_or_reserved(ip: &str) -> bool {
+    fn v4(ip: Ipv4Addr) -> bool {
+        let [a, b, ..] = ip.octets();
+        ip.is_private()
+            || ip.is_loopback()
+            || ip.is_link_local()
+            || ip.is_unspecified()
+            || ip.is_broadcast()
+            || ip.is_documentation()
+            || ip.is_multicast()
+            || (a == 100 && (64..128).contains(&b)) // Shared address space (CGNAT)
+            || a >= 240
+    }
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => v4(ip),
+        Ok(IpAddr::V6(ip)) => match ip.to_ipv4_mapped() {
+            Some(mapped) => v4(mapped),
+            None => {
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || ip.segments()[0] == 0x2001 && ip.segments()[1] == 0x0db8 // Documentation
+            }
+        },
+        Err(_) => false,
+    }
+}
+
+/// The local answer for `is_private_or_reserved` addresses, worded like ip-api's
+fn reserved_response(ip: &str) -> IpApiResponse {
+    let private = match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_private(),
+        Ok(IpAddr::V6(ip)) => ip.is_unique_local(),
+        Err(_) => false,
+    };
+    IpApiResponse::failed(ip, if private { "private range" } else { "reserved range" })
+}
+
+/// A geolocation backend. Every provider answers with an `IpApiResponse`,
+/// so the cache, the table columns and the detail view don't care which one
+/// is in use.
+pub trait GeoProvider: Send + Sync {
+    /// Look up one address (already reduced to its cache key) through the
+    /// cache's shared `client`. `fields` is an ip-api field list; providers
+    /// that always answer in full ignore it.
+    fn lookup(&self, client: &Client, ip: &str, fields: &str) -> Result<IpApiResponse>;
+
+    /// Look up several addresses, answering in request order. Providers
+    /// without a batch endpoint ask one address at a time.
+    fn lookup_batch(&self, client: &Client, ips: &[String], fields: &str) -> Result<Vec<IpApiResponse>> {
+        ips.iter().map(|ip| self.lookup(client, ip, fields)).collect()
+    }
+
+    /// How many addresses one `lookup_batch` call should carry
+    fn batch_size(&self) -> usize {
+        1
+    }
+}
+
+/// Which provider to use (`--geo-provider`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeoProviderKind {
+    /// ip-api.com: free, no key, batch endpoint for `--geo-prefetch`
+    #[default]
+    IpApi,
+    /// ipinfo.io: needs `--geo-token` beyond its anonymous quota
+    #[value(name = "ipinfo")]
+    #[serde(rename = "ipinfo")]
+    Ipinfo,
+}
+
+impl GeoProviderKind {
+    pub fn provider(self, token: Option<String>) -> Box<dyn GeoProvider> {
+        match self {
+            Self::IpApi => Box::new(IpApi),
+            Self::Ipinfo => Box::new(Ipinfo { token }),
+        }
+    }
+}
+
+/// ip-api.com, the default provider
+pub struct IpApi;
+
+impl GeoProvider for IpApi {
+    fn lookup(&self, client: &Client, ip: &str, fields: &str) -> Result<IpApiResponse> {
+        let url = format!("http://ip-api.com/json/{}?fields={}", ip, fields);
+        Ok(client.get(&url).send()?.json()?)
+    }
+
+    fn lookup_batch(&self, client: &Client, ips: &[String], fields: &str) -> Result<Vec<IpApiResponse>> {
+        let url = format!("http://ip-api.com/batch?fields={}", fields);
+        Ok(client.post(&url).json(ips).send()?.json()?)
+    }
+
+    fn batch_size(&self) -> usize {
+        BATCH_SIZE
+    }
+}
+
+/// ipinfo.io, with an optional access token
+pub struct Ipinfo {
+    pub token: Option<String>,
+}
+
+/// The parts of an ipinfo.io answer that map onto `IpApiResponse`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct IpinfoResponse {
+    ip: Option<String>,
+    city: Option<String>,
+    region: Option<String>,
+    country: Option<String>,
+    loc: Option<String>, // "lat,lon"
+    org: Option<String>, // "AS15169 Google LLC"
+    postal: Option<String>,
+    timezone: Option<String>,
+    bogon: Option<bool>,
+    privacy: Option<IpinfoPrivacy>,
+    error: Option<IpinfoError>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct IpinfoPrivacy {
+    vpn: bool,
+    proxy: bool,
+    tor: bool,
+    hosting: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct IpinfoError {
+    title: Option<String>,
+    message: Option<String>,
+}
+
+impl Ipinfo {
+    /// Map an ipinfo.io JSON answer onto the ip-api shape. Errors and bogon
+    /// (private/reserved) addresses become `status: "fail"` like ip-api's.
+    pub fn parse(text: &str) -> Result<IpApiResponse> {
+        let info: IpinfoResponse = serde_json::from_str(text)?;
+        let mut response = IpApiResponse { status: "success".to_string(), ..IpApiResponse::default() };
+        response.query = info.ip;
+        if let Some(error) = info.error {
+            response.status = "fail".to_string();
+            response.message = error.message.or(error.title);
+            return Ok(response);
+        }
+        if info.bogon == Some(true) {
+            response.status = "fail".to_string();
+            response.message = Some("reserved range".to_string());
+            return Ok(response);
+        }
+        if let Some((lat, lon)) = info.loc.as_deref().and_then(|loc| loc.split_once(',')) {
+            response.lat = lat.trim().parse().ok();
+            response.lon = lon.trim().parse().ok();
+        }
+        // ipinfo only has the country code; ip-api's `as` is the same "AS<n> <name>" string
+        response.isp = info.org.as_deref()
+            .and_then(|org| org.split_once(' '))
+            .filter(|(asn, _)| asn.starts_with("AS"))
+            .map(|(_, name)| name.to_string());
+        response.as_info = info.org.clone().filter(|org| org.starts_with("AS"));
+        response.org = info.org;
+        response.country_code = info.country;
+        response.region_name = info.region;
+        response.city = info.city;
+        response.zip = info.postal;
+        response.timezone = info.timezone;
+        if let Some(privacy) = info.privacy {
+            response.proxy = Some(privacy.vpn || privacy.proxy || privacy.tor);
+            response.hosting = Some(privacy.hosting);
+        }
+        Ok(response)
+    }
+}
+
+impl GeoProvider for Ipinfo {
+    fn lookup(&self, client: &Client, ip: &str, _fields: &str) -> Result<IpApiResponse> {
+        let mut request = client.get(format!("https://ipinfo.io/{}/json", ip));
+        if let Some(token) = &self.token {
+            // Encoded, whatever characters the token holds
+            request = request.query(&[("token", token)]);
+        }
+        let response = request.send()?;
+        let status = response.status();
+        let text = response.text()?;
+        Self::parse(&text).with_context(|| format!("ipinfo.io answered {}", status))
+    }
+}
+
+/// A cached answer and when it was stored (failures expire)
+struct CachedLookup {
+    response: IpApiResponse,
+    stored: Instant,
+}
+
+impl CachedLookup {
+    fn new(response: IpApiResponse) -> Self {
+        Self { response, stored: Instant::now() }
+    }
+
+    fn is_fresh(&self) -> bool {
+        !self.response.is_failure() || self.stored.elapsed() < FAILED_LOOKUP_TTL
+    }
+}
+
+/// Whether a lookup failed because the provider could not be reached at
+/// all (no route, DNS failure, connection refused or timed out), as opposed
+/// to an answer that could not be used
+pub fn is_offline(error: &anyhow::Error) -> bool {
+    error.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout())
+}
+
+/// The HTTP client for provider requests, with `CONNECT_TIMEOUT`,
+/// `REQUEST_TIMEOUT` and `USER_AGENT` applied. Built once per cache so
+/// lookups reuse its connections.
+pub fn http_client() -> Client {
+    Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("TLS backend failed to initialize")
+}
+
 pub struct IpApiCache {
-    cache: Mutex<HashMap<String, String>>,
+    cache: Mutex<HashMap<String, CachedLookup>>,
+    provider: Box<dyn GeoProvider>,
+    client: Client, // Shared by every request, so a dead network fails within CONNECT_TIMEOUT
+    fields: String, // ip-api fields requested by every lookup, `DEFAULT_FIELDS` unless set
+}
+
+impl Default for IpApiCache {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IpApiCache {
     pub fn new() -> Self {
+        Self::with_provider(Box::new(IpApi))
+    }
+
+    pub fn with_provider(provider: Box<dyn GeoProvider>) -> Self {
         Self {
             cache: Mutex::new(HashMap::new()),
+            provider,
+            client: http_client(),
+            fields: DEFAULT_FIELDS.to_string(),
         }
     }
 
+    /// Request these ip-api fields (see `parse_geo_fields`) instead of `DEFAULT_FIELDS`
+    pub fn with_fields(mut self, fields: &str) -> Self {
+        self.fields = fields.to_string();
+        self
+    }
+
+    /// The ip-api fields every lookup requests
+    pub fn fields(&self) -> &str {
+        &self.fields
+    }
+
     /// Get the /24 subnet for an IP address
-    fn get_subnet_24(ip: &str) -> Option<String> {
+    pub fn get_subnet_24(ip: &str) -> Option<String> {
         // Try to parse as IP address
         if let Ok(addr) = ip.parse::<IpAddr>() {
             match addr {
@@ -89,38 +425,96 @@ impl IpApiCache {
         }
     }
 
-    /// Fetch IP information from ip-api.com, using /24 subnet caching
+    /// Fetch IP information from the geolocation provider, using /24 subnet
+    /// caching. Failed lookups are cached too, for `FAILED_LOOKUP_TTL`;
+    /// private and reserved addresses never reach the network.
     pub fn get_ip_info(&self, ip: &str) -> Result<String> {
-        // Get the /24 subnet to use as cache key
+        // Get the /24 subnet to use as cache key; the API rejects non-addresses
         let cache_key = Self::get_subnet_24(ip)
-            .unwrap_or_else(|| ip.to_string());
+            .ok_or_else(|| anyhow::anyhow!("not a valid IP address: {}", ip))?;
 
-        // Check cache first
-        {
-            let cache = self.cache.lock().unwrap();
-            if let Some(cached) = cache.get(&cache_key) {
-                return Ok(cached.clone());
+        // Check cache first, otherwise fetch from API
+        let api_response = match self.cached(ip) {
+            Some(cached) => cached,
+            None => {
+                let api_response = if is_private_or_reserved(ip) {
+                    reserved_response(ip)
+                } else {
+                    self.provider.lookup(&self.client, &cache_key, &self.fields)?
+                };
+
+                // Cache the result
+                let mut cache = self.cache.lock().unwrap();
+                cache.insert(cache_key, CachedLookup::new(api_response.clone()));
+                api_response
             }
-        }
+        };
 
-        // Not in cache - fetch from API
-        let url = format!(
-            "http://ip-api.com/json/{}?fields=query,status,message,continent,continentCode,country,countryCode,region,regionName,city,district,zip,lat,lon,timezone,offset,currency,isp,org,as,asname,mobile,proxy,hosting",
-            cache_key
-        );
+        // Pretty-print the JSON response
+        Ok(serde_json::to_string_pretty(&api_response)?)
+    }
 
-        let response = reqwest::blocking::get(&url)?;
-        let api_response: IpApiResponse = response.json()?;
+    /// Cached response for an IP's subnet, without any network request.
+    /// Expired failures count as not cached.
+    pub fn cached(&self, ip: &str) -> Option<IpApiResponse> {
+        let cache_key = Self::get_subnet_24(ip).unwrap_or_else(|| ip.to_string());
+        self.cache.lock().unwrap().get(&cache_key)
+            .filter(|entry| entry.is_fresh())
+            .map(|entry| entry.response.clone())
+    }
 
-        // Pretty-print the JSON response
-        let pretty_json = serde_json::to_string_pretty(&api_response)?;
+    /// Store a response for an IP's subnet (as if it had been fetched)
+    pub fn insert(&self, ip: &str, response: IpApiResponse) {
+        let cache_key = Self::get_subnet_24(ip).unwrap_or_else(|| ip.to_string());
+        self.cache.lock().unwrap().insert(cache_key, CachedLookup::new(response));
+    }
 
-        // Cache the result
+    /// Resolve every not-yet-cached subnet of `ips` through the provider's
+    /// batch lookup (100 per request for ip-api, one at a time for providers
+    /// without a batch endpoint). `on_batch` is called with (resolved, total)
+    /// subnets before each request and after the last; returning false stops
+    /// before the next request. Private and reserved subnets are answered
+    /// locally and not counted. Returns the number of subnets resolved.
+    pub fn prefetch<'a, I, F>(&self, ips: I, mut on_batch: F) -> Result<usize>
+    where
+        I: IntoIterator<Item = &'a str>,
+        F: FnMut(usize, usize) -> bool,
+    {
+        let mut pending: Vec<String> = Vec::new();
         {
             let mut cache = self.cache.lock().unwrap();
-            cache.insert(cache_key, pretty_json.clone());
+            let mut seen = HashSet::new();
+            for ip in ips {
+                if let Some(key) = Self::get_subnet_24(ip) {
+                    if cache.get(&key).is_some_and(|entry| entry.is_fresh()) || !seen.insert(key.clone()) {
+                        continue;
+                    }
+                    if is_private_or_reserved(ip) {
+                        cache.insert(key, CachedLookup::new(reserved_response(ip)));
+                    } else {
+                        pending.push(key);
+                    }
+                }
+            }
+        }
+
+        let total = pending.len();
+        let mut resolved = 0;
+        for batch in pending.chunks(self.provider.batch_size().max(1)) {
+            if !on_batch(resolved, total) {
+                return Ok(resolved);
+            }
+            let responses = self.provider.lookup_batch(&self.client, batch, &self.fields)?;
+
+            // Responses come back in request order
+            let mut cache = self.cache.lock().unwrap();
+            for (key, response) in batch.iter().zip(responses) {
+                cache.insert(key.clone(), CachedLookup::new(response));
+            }
+            resolved += batch.len();
         }
+        on_batch(resolved, total);
 
-        Ok(pretty_json)
+        Ok(resolved)
     }
 }
@@ -0,0 +1,19 @@
+//! Waflens - a terminal lens into ModSecurity audit logs.
+//!
+//! The binary in `main.rs` drives the TUI; the modules are exposed here so
+//! the parsing and search logic can be used (and tested) without a terminal.
+
+pub mod app;
+pub mod clipboard;
+pub mod colors;
+pub mod config;
+pub mod crs;
+pub mod diagnostics;
+pub mod ipapi;
+pub mod loading;
+pub mod output;
+pub mod parser;
+pub mod rdap;
+pub mod rdns;
+pub mod ui;
+pub mod watch;
@@ -5,15 +5,42 @@ use ratatui::{
     widgets::{Block, Borders, Gauge, Paragraph},
     Frame,
 };
+use std::time::Duration;
+
+/// Shortest time between two progress redraws while a step is running.
+/// Parsing reports progress far more often than a terminal can show it.
+pub const REDRAW_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Progress `fraction` of the way through the band from `start` to `end`,
+/// kept inside the band when the fraction overshoots (a file still growing)
+pub fn band_progress(start: f64, end: f64, fraction: f64) -> f64 {
+    start + fraction.clamp(0.0, 1.0) * (end - start)
+}
 
 pub struct LoadingScreen;
 
+impl Default for LoadingScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LoadingScreen {
     pub fn new() -> Self {
         Self
     }
 
     pub fn draw(&self, f: &mut Frame, step: usize, step_name: &str, progress: f64, message: &str) {
+        self.draw_progress(f, &format!("Step {}/5: {}", step, step_name), progress, message);
+    }
+
+    /// Progress for work after parsing (not one of the numbered steps)
+    pub fn draw_task(&self, f: &mut Frame, task_name: &str, progress: f64, message: &str) {
+        self.draw_progress(f, task_name, progress, message);
+    }
+
+    fn draw_progress(&self, f: &mut Frame, step_text: &str, progress: f64, message: &str) {
+        let progress = progress.clamp(0.0, 1.0);
             // Center the dialog - 50% width, centered horizontally and vertically
             let area = f.area();
             let vertical_center = Layout::default()
@@ -54,7 +81,6 @@ impl LoadingScreen {
             f.render_widget(title, chunks[0]);
 
             // Step indicator
-            let step_text = format!("Step {}/5: {}", step, step_name);
             let step_para = Paragraph::new(step_text)
                 .style(Style::default().fg(Color::Yellow))
                 .alignment(Alignment::Center)
@@ -77,7 +103,8 @@ impl LoadingScreen {
             f.render_widget(msg, chunks[3]);
     }
 
-    pub fn draw_summary(&self, f: &mut Frame, total_entries: usize, total_groups: usize, file_size_mb: f64) {
+    /// `groups_seen` is more than `total_groups` when `--max-entries` dropped older groups
+    pub fn draw_summary(&self, f: &mut Frame, total_entries: usize, total_groups: usize, groups_seen: usize, file_size_mb: f64) {
             // Center the dialog - 50% width, centered
             let area = f.area();
             let vertical_center = Layout::default()
@@ -103,7 +130,7 @@ impl LoadingScreen {
                 .margin(2)
                 .constraints([
                     Constraint::Length(3),   // Title
-                    Constraint::Length(7),   // Summary box
+                    Constraint::Length(8),   // Summary box
                     Constraint::Min(0),      // Spacer
                 ])
                 .split(horizontal_center[1]);
@@ -116,7 +143,7 @@ impl LoadingScreen {
             f.render_widget(title, chunks[0]);
 
             // Summary
-            let summary_lines = vec![
+            let mut summary_lines = vec![
                 Line::from(""),
                 Line::from(vec![
                     Span::raw("  📁 File size: "),
@@ -130,8 +157,17 @@ impl LoadingScreen {
                     Span::raw("  🔗 Audit groups: "),
                     Span::styled(format!("{}", total_groups), Style::default().fg(Color::Green)),
                 ]),
-                Line::from(""),
             ];
+            if groups_seen > total_groups {
+                summary_lines.push(Line::from(vec![
+                    Span::raw("  ✂️  Kept most recent: "),
+                    Span::styled(
+                        format!("{} of {} groups", total_groups, groups_seen),
+                        Style::default().fg(Color::Magenta),
+                    ),
+                ]));
+            }
+            summary_lines.push(Line::from(""));
 
             let summary = Paragraph::new(summary_lines)
                 .block(Block::default().borders(Borders::ALL).title(" Summary "))
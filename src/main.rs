@@ -1,15 +1,15 @@
-mod app;
-mod colors;
-mod ipapi;
-mod loading;
-mod parser;
-mod ui;
-
 use anyhow::Result;
-use app::{App, AppView};
+use waflens::app::{fired_rule, parse_columns, parse_duration, parse_poll_interval, parse_rate_threshold, read_groups, stray_bytes_note, stream_groups, App, AppOptions, AppView, Column, DETAIL_HSCROLL_STEP};
+use waflens::config::{self, Config};
+use waflens::diagnostics;
+use waflens::ipapi::{parse_geo_fields, GeoProviderKind, FULL_FIELDS};
+use waflens::output::{self, OutputFormat};
+use waflens::parser::{self, parse_a_section_format, parse_trusted_ip_headers, DEFAULT_TRUSTED_IP_HEADERS};
+use waflens::ui;
+use waflens::watch::{WatchDir, DEFAULT_PATTERN};
 use clap::Parser as ClapParser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseEvent, MouseEventKind},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -18,33 +18,328 @@ use ratatui::{
     Terminal,
 };
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 #[derive(ClapParser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Path to ModSecurity audit log file
-    file: String,
+    /// Path to ModSecurity audit log file (or concurrent index/directory with --concurrent).
+    /// Several can be given, e.g. a rotated log and the current one, and are read as one
+    #[arg(required_unless_present_any = ["diagnostics", "watch_dir"], value_name = "FILE")]
+    files: Vec<String>,
+
+    /// Follow the newest log in this directory whose name matches --pattern,
+    /// switching to the new file when the log is rotated (implies --follow)
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["files", "concurrent", "format"])]
+    watch_dir: Option<PathBuf>,
+
+    /// With --watch-dir: the log file names, e.g. "modsec_audit.log*" [default: *]
+    #[arg(long, value_name = "GLOB", requires = "watch_dir")]
+    pattern: Option<String>,
+
+    /// Read a concurrent-mode log: FILE is the index file or the storage directory
+    #[arg(long)]
+    concurrent: bool,
+
+    /// Keep only the N most recent transactions while parsing (bounds memory on huge logs)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_entries: Option<u64>,
+
+    /// When one audit ID holds transactions from different client IPs
+    /// (a collision, marked ⚠), show one row per IP instead
+    #[arg(long)]
+    split_on_ip: bool,
+
+    /// Report how many bytes of the log are outside any audit entry
+    /// (comments, other output interleaved into the file), which are skipped
+    #[arg(long)]
+    report_stray: bool,
+
+    /// Behind a proxy or CDN: take the client IP from the request headers
+    /// (CF-Connecting-IP, True-Client-IP, X-Real-IP, X-Client-IP, then the
+    /// leftmost X-Forwarded-For address) instead of the connecting address
+    #[arg(long)]
+    trust_xff: bool,
+
+    /// With --trust-xff: the headers to try, in order, e.g.
+    /// "CF-Connecting-IP,X-Forwarded-For"; the first valid address wins
+    #[arg(long, value_name = "LIST", value_parser = parse_trusted_ip_headers, requires = "trust_xff")]
+    trusted_ip_headers: Option<std::vec::Vec<String>>,
+
+    /// The A line fields after the timestamp, for logs that order them
+    /// differently, e.g. "id server-ip server-port client-ip client-port".
+    /// The client IP is read from the client-ip field
+    /// [default: id client-ip client-port server-ip server-port]
+    #[arg(long, value_name = "LAYOUT", value_parser = parse_a_section_format)]
+    a_section_format: Option<usize>,
+
+    /// Only show entries with an HTTP status of at least this value
+    #[arg(long, value_parser = clap::value_parser!(u16).range(100..=599))]
+    min_status: Option<u16>,
+
+    /// Only show entries with an HTTP status of at most this value
+    #[arg(long, value_parser = clap::value_parser!(u16).range(100..=599))]
+    max_status: Option<u16>,
+
+    /// Only show entries from the last DURATION, e.g. 30m, 1h, 2d
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    since: Option<std::time::Duration>,
+
+    /// Enable IP API lookups (fetches geo/ISP data from ip-api.com) [default: true].
+    /// `--ip-api` alone means true; a value needs the `=` (`--ip-api=false`)
+    #[arg(long = "ip-api", value_name = "BOOL", action = clap::ArgAction::Set, num_args = 0..=1, require_equals = true, default_missing_value = "true", overrides_with = "no_ip_api")]
+    ip_api: Option<bool>,
+
+    /// Disable IP API lookups, same as `--ip-api=false`; the later of the two wins
+    #[arg(long, overrides_with = "ip_api")]
+    no_ip_api: bool,
+
+    /// Resolve geolocation for all client IPs at startup (ip-api batch endpoint)
+    #[arg(long)]
+    geo_prefetch: bool,
+
+    /// Geolocation provider [default: ip-api]
+    #[arg(long, value_enum, value_name = "PROVIDER")]
+    geo_provider: Option<GeoProviderKind>,
+
+    /// Access token for the geolocation provider (ipinfo)
+    #[arg(long, value_name = "TOKEN")]
+    geo_token: Option<String>,
+
+    /// ip-api fields to request, e.g. "country,countryCode,as,lat,lon"
+    /// [default: country,countryCode,regionName,city,isp,org,as,mobile,proxy,hosting]
+    #[arg(long, value_name = "LIST", value_parser = parse_geo_fields)]
+    geo_fields: Option<String>,
+
+    /// Request every field ip-api offers (timezone, coordinates, currency, ...)
+    #[arg(long, conflicts_with = "geo_fields")]
+    geo_full: bool,
+
+    /// Draw the table without borders to fit more rows (toggle with 'b')
+    #[arg(long)]
+    dense: bool,
+
+    /// Show a Country column from cached geolocation (toggle with 'g')
+    #[arg(long)]
+    country: bool,
+
+    /// Shorten audit IDs in the table to their first 8 characters, longer
+    /// where needed to keep them unique (toggle with 'a')
+    #[arg(long)]
+    short_ids: bool,
+
+    /// Show rule IDs in ascending numeric order instead of the order they
+    /// fired (toggle with 'O')
+    #[arg(long)]
+    sort_rule_ids: bool,
+
+    /// Mark client IPs that reached this many requests per second within
+    /// a 10 s window [default: 2]
+    #[arg(long, value_name = "RPS", value_parser = parse_rate_threshold)]
+    rate_threshold: Option<f64>,
+
+    /// Table columns to show, in order, e.g. "domain,client-ip,status,rule-ids"
+    /// [default: audit-id,timestamp,domain,client-ip,country,status,action,flags,rule-ids; also: category]
+    #[arg(long, value_name = "LIST", value_parser = parse_columns)]
+    // Spelled out so clap takes the whole list from one value instead of one column per value
+    columns: Option<std::vec::Vec<Column>>,
+
+    /// URL opened by 'o' for the selected client IP ({ip} is replaced),
+    /// e.g. "https://www.abuseipdb.com/check/{ip}"
+    #[arg(long, value_name = "TEMPLATE")]
+    ip_url: Option<String>,
+
+    /// Milliseconds between two clicks on a row that count as a double-click
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..))]
+    double_click_ms: Option<u64>,
+
+    /// Don't capture the mouse (keeps the terminal's native text selection)
+    #[arg(long)]
+    no_mouse: bool,
+
+    /// Search query to apply at startup, same syntax as '/' (e.g. "status:403 domain:api.")
+    #[arg(long, value_name = "QUERY")]
+    filter: Option<String>,
+
+    /// Follow the log: reload it whenever it changes (toggle with F)
+    #[arg(long)]
+    follow: bool,
+
+    /// How often follow mode checks the log for changes, e.g. 500ms, 2s [default: 1s]
+    #[arg(long, value_name = "DURATION", value_parser = parse_poll_interval)]
+    poll_interval: Option<std::time::Duration>,
+
+    /// Start with the color legend shown (toggle with '?')
+    #[arg(long)]
+    legend: bool,
 
-    /// Enable IP API lookups (fetches geo/ISP data from ip-api.com)
-    #[arg(long = "ip-api", default_value_t = true, action = clap::ArgAction::Set)]
-    ip_api: bool,
+    /// Use the color scheme for light terminal backgrounds (detected from
+    /// COLORFGBG when set)
+    #[arg(long)]
+    light: bool,
+
+    /// Show the debug overlay (timings, group counts, table state); toggle with F12
+    #[arg(long)]
+    debug: bool,
+
+    /// Print the matching transactions in this format instead of opening the TUI
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// With --format: exit with status 1 when any transaction matched (0 when none)
+    #[arg(long, requires = "format")]
+    exit_code: bool,
+
+    /// With --format: only print transactions that fired this rule, followed
+    /// by a breakdown of its hits per domain and client IP
+    #[arg(long, value_name = "ID", requires = "format")]
+    rule: Option<String>,
+
+    /// On exit, write a JSON summary of the filtered transactions to FILE:
+    /// totals, blocked vs. detection-only, time range, status classes and
+    /// the top IPs, rules and domains
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+
+    /// Print build info, color detection, the config path, geolocation
+    /// reachability and the parser's patterns, then exit
+    #[arg(long)]
+    diagnostics: bool,
+}
+
+/// Combine command-line flags with the config file defaults; flags win
+fn app_options(args: &Args, config: Config) -> AppOptions {
+    AppOptions {
+        ip_api_enabled: args.ip_api.or(args.no_ip_api.then_some(false)).or(config.ip_api).unwrap_or(true),
+        concurrent: args.concurrent || config.concurrent.unwrap_or(false),
+        max_entries: args.max_entries.map(|n| n as usize).or(config.max_entries),
+        split_on_ip: args.split_on_ip || config.split_on_ip.unwrap_or(false),
+        report_stray: args.report_stray || config.report_stray.unwrap_or(false),
+        trusted_ip_headers: if args.trust_xff || config.trust_xff.unwrap_or(false) {
+            args.trusted_ip_headers.clone()
+                .or_else(|| config.trusted_ip_headers.as_deref().and_then(|headers| parse_trusted_ip_headers(headers).ok()))
+                .unwrap_or_else(|| DEFAULT_TRUSTED_IP_HEADERS.iter().map(|h| h.to_string()).collect())
+        } else {
+            Vec::new()
+        },
+        a_section_ip_field: args.a_section_format
+            .or_else(|| config.a_section_format.as_deref().and_then(|format| parse_a_section_format(format).ok())),
+        min_status: args.min_status.or(config.min_status),
+        max_status: args.max_status.or(config.max_status),
+        // Validated when the config was loaded
+        since: args.since.or_else(|| config.since.as_deref().and_then(|since| parse_duration(since).ok())),
+        geo_prefetch: args.geo_prefetch || config.geo_prefetch.unwrap_or(false),
+        geo_provider: args.geo_provider.or(config.geo_provider).unwrap_or_default(),
+        geo_token: args.geo_token.clone().or(config.geo_token),
+        // --geo-full wins over a field list from the config
+        geo_fields: if args.geo_full || (args.geo_fields.is_none() && config.geo_full.unwrap_or(false)) {
+            Some(FULL_FIELDS.to_string())
+        } else {
+            args.geo_fields.clone().or_else(|| config.geo_fields.as_deref().and_then(|fields| parse_geo_fields(fields).ok()))
+        },
+        dense: args.dense || config.dense.unwrap_or(false),
+        show_country: args.country || config.country.unwrap_or(false),
+        short_ids: args.short_ids || config.short_ids.unwrap_or(false),
+        sort_rule_ids: args.sort_rule_ids || config.sort_rule_ids.unwrap_or(false),
+        rate_threshold: args.rate_threshold.or(config.rate_threshold),
+        columns: args.columns.clone().or_else(|| config.columns.as_deref().and_then(|columns| parse_columns(columns).ok())),
+        ip_url_template: args.ip_url.clone().or(config.ip_url),
+        filter: args.filter.clone().or(config.filter),
+        double_click_ms: args.double_click_ms.or(config.double_click_ms),
+        no_mouse: args.no_mouse || config.no_mouse.unwrap_or(false),
+        follow: args.follow || args.watch_dir.is_some() || config.follow.unwrap_or(false),
+        watch: args.watch_dir.as_ref()
+            .map(|dir| WatchDir::new(dir, args.pattern.as_deref().unwrap_or(DEFAULT_PATTERN))),
+        debug: args.debug,
+        legend: args.legend,
+        light: args.light || config.light.unwrap_or(false),
+        // Saved filters have their own file, read in main
+        saved_filters: Vec::new(),
+        filters_path: None,
+        poll_interval: args.poll_interval
+            .or_else(|| config.poll_interval.as_deref().and_then(|interval| parse_poll_interval(interval).ok())),
+    }
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Check if file exists and is readable before launching UI
-    if !std::path::Path::new(&args.file).exists() {
-        eprintln!("Error: File '{}' does not exist", args.file);
-        std::process::exit(1);
+    let config_path = Config::default_path();
+    let config = match &config_path {
+        Some(path) => Config::load(path),
+        None => Ok(Config::default()),
+    };
+
+    if args.diagnostics {
+        // Report a broken config instead of refusing to start
+        let config_error = config.as_ref().err()
+            .map(|e| e.chain().skip(1).map(|cause| cause.to_string()).collect::<Vec<_>>().join(": "));
+        let options = app_options(&args, config.unwrap_or_default());
+        diagnostics::write_report(&mut io::stdout().lock(), config_path.as_deref(), config_error.as_deref(), &options)?;
+        return Ok(());
     }
 
-    if let Err(e) = std::fs::File::open(&args.file) {
-        eprintln!("Error: Cannot read file '{}': {}", args.file, e);
+    let config = config.unwrap_or_else(|e| {
+        eprintln!("Error: {:#}", e);
         std::process::exit(1);
+    });
+    let mut options = app_options(&args, config);
+    options.filters_path = config::filters_path();
+    if let Some(path) = &options.filters_path {
+        options.saved_filters = config::load_filters(path).unwrap_or_else(|e| {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        });
+    }
+
+    if let (Some(min), Some(max)) = (options.min_status, options.max_status) {
+        if min > max {
+            eprintln!("Error: --min-status ({}) is greater than --max-status ({})", min, max);
+            std::process::exit(1);
+        }
+    }
+
+    // --watch-dir starts on the newest matching log
+    let files = match &options.watch {
+        Some(watch) => match watch.newest() {
+            Ok(Some(newest)) => vec![newest.to_string_lossy().into_owned()],
+            Ok(None) => {
+                eprintln!("Error: no file in '{}' matches '{}'", watch.dir.display(), watch.pattern);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                std::process::exit(1);
+            }
+        },
+        None => args.files.clone(),
+    };
+
+    // Check if the files exist and are readable before launching UI
+    for file in &files {
+        if let Err(e) = parser::check_log_path(Path::new(file), options.concurrent) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(format) = args.format {
+        match print_headless(&files, &options, format, args.rule.as_deref(), args.report.as_deref()) {
+            Ok(matched) if args.exit_code && matched > 0 => std::process::exit(1),
+            Ok(_) => return Ok(()),
+            // The reader went away (`| head`); nothing left to report to
+            Err(e) if is_broken_pipe(&e) => return Ok(()),
+            Err(e) => {
+                // 1 is reserved for --exit-code findings
+                eprintln!("Error: {:#}", e);
+                std::process::exit(2);
+            }
+        }
     }
 
     // Setup terminal
+    ui::init_colors(options.light);
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -52,12 +347,10 @@ fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app (this will show the loading screen)
-    let mut app = App::new(&args.file, &mut terminal, args.ip_api)?;
-
-    // Main loop
-    let res = run_app(&mut terminal, &mut app);
+    let res = App::new(&files, &mut terminal, &options)
+        .and_then(|mut app| run_app(&mut terminal, &mut app).map(|_| app));
 
-    // Restore terminal
+    // Restore terminal (also when loading failed, so the error is readable)
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -65,22 +358,82 @@ fn main() -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
-    if let Err(err) = res {
-        eprintln!("Error: {:?}", err);
+    let report = res.and_then(|app| match &args.report {
+        Some(path) => app.write_report(path),
+        None => Ok(()),
+    });
+    if let Err(err) = report {
+        eprintln!("Error: {:#}", err);
+        std::process::exit(1);
     }
 
     Ok(())
 }
 
+/// Whether writing to stdout failed because the pipe was closed
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        let kind = cause.downcast_ref::<io::Error>().map(io::Error::kind)
+            .or_else(|| cause.downcast_ref::<serde_json::Error>().and_then(serde_json::Error::io_error_kind));
+        kind == Some(io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Headless mode: parse, apply the startup filters and print the matching
+/// transactions to stdout, with a per-rule summary for `--rule`, then write
+/// the `--report`. Returns how many matched. NDJSON without `--rule` or
+/// `--report` streams: each transaction is printed as soon as it is
+/// complete, in log order. `--report-stray` goes to stderr.
+fn print_headless(files: &[String], options: &AppOptions, format: OutputFormat, rule: Option<&str>, report: Option<&Path>) -> Result<usize> {
+    let parser = options.log_parser();
+    let report_stray = || {
+        if options.report_stray {
+            eprintln!("{}", stray_bytes_note(parser.stray_bytes()));
+        }
+    };
+    if format == OutputFormat::Ndjson && rule.is_none() && report.is_none() {
+        // An empty app still applies the startup filters to each group
+        let filters = App::from_groups(Vec::new(), files, options);
+        let mut out = io::stdout().lock();
+        let mut matched = 0;
+        stream_groups(files, options.concurrent, &parser, |group| {
+            if filters.matches_search(&group) {
+                matched += 1;
+                output::write_ndjson(&group, &mut out)?;
+            }
+            Ok(())
+        })?;
+        report_stray();
+        return Ok(matched);
+    }
+    let groups = read_groups(files, options.concurrent, &parser)?;
+    report_stray();
+    let app = App::from_groups(groups, files, options);
+    let mut matched = app.visible_groups();
+    let mut out = io::stdout().lock();
+    match rule {
+        Some(rule_id) => {
+            matched.retain(|group| fired_rule(group, rule_id));
+            output::write_rule_report(format, &matched, &app.stats_for_rule(rule_id), &mut out)?;
+        }
+        None => output::write_groups(format, &matched, &mut out)?,
+    }
+    if let Some(path) = report {
+        app.write_report(path)?;
+    }
+    Ok(matched.len())
+}
+
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
 ) -> Result<()> {
     let mut mouse_enabled = false;
+    let mut next_poll = Instant::now() + app.poll_interval;
 
     loop {
-        // Enable mouse in table view, disable in detail view
-        let should_enable_mouse = matches!(app.current_view, AppView::TableView);
+        // Enable mouse in table view (unless --no-mouse or passthrough), disable in detail view
+        let should_enable_mouse = app.wants_mouse();
         if should_enable_mouse != mouse_enabled {
             if should_enable_mouse {
                 execute!(io::stdout(), EnableMouseCapture)?;
@@ -90,18 +443,52 @@ fn run_app(
             mouse_enabled = should_enable_mouse;
         }
 
+        let draw_started = Instant::now();
         terminal.draw(|f| ui::draw(f, app))?;
+        app.debug_stats.record_frame(draw_started.elapsed());
+
+        // The lookup blocks, so it waits until its placeholder is on screen
+        if app.ip_info_pending {
+            app.load_pending_ip_info();
+            continue;
+        }
+
+        // Follow mode waits for input only until the next log check, so
+        // keys are handled immediately whatever the interval
+        if app.follow && !event::poll(next_poll.saturating_duration_since(Instant::now()))? {
+            if let Err(e) = app.poll_log() {
+                app.status_message = Some(format!("Follow: {:#}", e));
+            }
+            next_poll = Instant::now() + app.poll_interval;
+            continue;
+        }
 
         match event::read()? {
             Event::Key(key) => {
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
+                // Notes only last until the next key press
+                app.status_message = None;
+
+                // The debug overlay toggles from every view, even while typing a search
+                if key.code == KeyCode::F(12) {
+                    app.toggle_debug();
+                    continue;
+                }
+                // Any key closes the legend; `?` opens it unless it is being typed
+                let typing = app.search_mode || app.filter_picker.is_some();
+                if app.show_legend || (key.code == KeyCode::Char('?') && !typing) {
+                    app.toggle_legend();
+                    continue;
+                }
 
                 match app.current_view {
                     AppView::TableView => {
-                        if app.search_mode {
-                            handle_search_input(app, key.code);
+                        if app.filter_picker.is_some() {
+                            handle_filter_picker_input(app, key.code);
+                        } else if app.search_mode {
+                            handle_search_input(app, key);
                         } else {
                             let needs_redraw = handle_table_input(app, terminal, key.code);
                             if needs_redraw {
@@ -113,14 +500,19 @@ fn run_app(
                     AppView::DetailView => {
                         handle_detail_input(app, key.code);
                     }
+                    AppView::DomainView => {
+                        handle_domain_input(app, key.code);
+                    }
+                    AppView::StatsView => {
+                        handle_stats_input(app, key.code);
+                    }
                 }
             }
-            Event::Mouse(mouse) => {
-                // Only handle mouse events in table view for row selection
-                if matches!(app.current_view, AppView::TableView) && !app.search_mode {
-                    handle_mouse_input(app, mouse);
-                }
+            // Only handle mouse events in table view for row selection
+            Event::Mouse(mouse) if app.wants_mouse() && !app.search_mode && app.filter_picker.is_none() => {
+                handle_mouse_input(app, mouse);
             }
+            // The next draw measures the new size and refits the window
             _ => {}
         }
 
@@ -138,15 +530,15 @@ fn run_app(
 }
 
 fn handle_table_input(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, key: KeyCode) -> bool {
-    const VISIBLE_HEIGHT: usize = 20;
+    let visible_height = app.visible_height;
     let mut needs_redraw = false;
 
     match key {
         KeyCode::Char('q') => app.quit(),
         KeyCode::Up | KeyCode::Char('k') => app.move_selection_up(),
-        KeyCode::Down | KeyCode::Char('j') => app.move_selection_down(VISIBLE_HEIGHT),
-        KeyCode::PageUp => app.page_up(VISIBLE_HEIGHT),
-        KeyCode::PageDown => app.page_down(VISIBLE_HEIGHT),
+        KeyCode::Down | KeyCode::Char('j') => app.move_selection_down(visible_height),
+        KeyCode::PageUp => app.page_up(visible_height),
+        KeyCode::PageDown => app.page_down(visible_height),
         KeyCode::Home => {
             app.selected_index = 0;
             app.scroll_offset = 0;
@@ -154,14 +546,43 @@ fn handle_table_input(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io
         KeyCode::End => {
             let last = app.filtered_groups.len().saturating_sub(1);
             app.selected_index = last;
-            app.scroll_offset = last.saturating_sub(VISIBLE_HEIGHT - 1);
+            app.scroll_offset = last.saturating_sub(visible_height - 1);
+        }
+        // Collapsed rows with several transactions expand instead
+        KeyCode::Enter if app.selected_similar().is_some_and(|s| s.count() > 1) => {
+            app.expand_similar();
         }
         KeyCode::Enter => app.show_detail_view(),
+        KeyCode::Char('n') => app.jump_to_error(true),
+        KeyCode::Char('p') => app.jump_to_error(false),
         KeyCode::Char('/') => app.enter_search_mode(),
+        KeyCode::Char('f') => app.open_filter_picker(),
+        KeyCode::Char('d') => app.show_domain_view(),
+        KeyCode::Char('s') => app.show_stats_view(),
+        KeyCode::Char('t') => app.toggle_row_tint(),
+        KeyCode::Char('c') => app.toggle_collapse(),
+        KeyCode::Char('z') => app.toggle_fuzzy(),
+        KeyCode::Char('b') => app.toggle_dense(),
+        KeyCode::Char('m') => app.toggle_mouse_passthrough(),
+        KeyCode::Char('g') => app.toggle_country(),
+        KeyCode::Char('a') => app.toggle_short_ids(),
+        KeyCode::Char('O') => app.toggle_sort_rule_ids(),
+        KeyCode::Char('H') => app.toggle_hosting_only(),
+        KeyCode::Char('P') => app.toggle_proxy_only(),
+        KeyCode::Char('o') => open_ip(app),
+        KeyCode::Char('M') => app.copy_markdown(),
+        KeyCode::Char('v') => app.toggle_select_mode(),
+        KeyCode::Char(' ') if app.select_mode => app.toggle_mark(),
+        KeyCode::Char('I') => app.toggle_ip_api(),
+        KeyCode::Char('F') => app.toggle_follow(),
         KeyCode::Char('r') | KeyCode::F(5) => {
             let _ = app.refresh(terminal);
             needs_redraw = true;
         }
+        KeyCode::Esc if app.select_mode => app.clear_marks(),
+        KeyCode::Esc if app.expanded_similar.is_some() => {
+            app.close_similar();
+        }
         KeyCode::Esc => app.clear_search(),
         _ => {}
     }
@@ -169,10 +590,82 @@ fn handle_table_input(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io
     needs_redraw
 }
 
-fn handle_search_input(app: &mut App, key: KeyCode) {
+fn open_ip(app: &mut App) {
+    if let Err(e) = app.open_ip_in_browser() {
+        app.status_message = Some(format!("Cannot open browser: {}", e));
+    }
+}
+
+/// Keys while the saved-filter picker is open. While a name is being typed
+/// for the current query, keys edit the name instead.
+fn handle_filter_picker_input(app: &mut App, key: KeyCode) {
+    let Some(picker) = app.filter_picker.clone() else {
+        return;
+    };
+
+    if let Some(mut name) = picker.naming {
+        match key {
+            KeyCode::Char(c) => name.push(c),
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            KeyCode::Enter => {
+                app.status_message = Some(match app.save_filter(&name) {
+                    Ok(()) => format!("Saved filter '{}'", name.trim()),
+                    Err(e) => format!("Cannot save filter: {:#}", e),
+                });
+                if let Some(picker) = &mut app.filter_picker {
+                    picker.naming = None;
+                }
+                return;
+            }
+            KeyCode::Esc => {
+                if let Some(picker) = &mut app.filter_picker {
+                    picker.naming = None;
+                }
+                return;
+            }
+            _ => {}
+        }
+        if let Some(picker) = &mut app.filter_picker {
+            picker.naming = Some(name);
+        }
+        return;
+    }
+
     match key {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('f') => app.close_filter_picker(),
+        KeyCode::Up | KeyCode::Char('k') => app.move_filter_selection(false),
+        KeyCode::Down | KeyCode::Char('j') => app.move_filter_selection(true),
+        KeyCode::Enter => app.apply_saved_filter(picker.selected),
+        KeyCode::Char('s') => {
+            if let Some(picker) = &mut app.filter_picker {
+                picker.naming = Some(String::new());
+            }
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            if let Err(e) = app.delete_saved_filter(picker.selected) {
+                app.status_message = Some(format!("Cannot delete filter: {:#}", e));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_search_input(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        // Readline-style editing
+        KeyCode::Char('u') if ctrl => app.clear_search(),
+        KeyCode::Char('w') if ctrl => app.delete_search_word(),
+        KeyCode::Char('a') if ctrl => app.search_cursor_home(),
+        KeyCode::Char('e') if ctrl => app.search_cursor_end(),
         KeyCode::Char(c) => app.add_search_char(c),
         KeyCode::Backspace => app.remove_search_char(),
+        KeyCode::Left => app.move_search_cursor(false),
+        KeyCode::Right => app.move_search_cursor(true),
+        KeyCode::Home => app.search_cursor_home(),
+        KeyCode::End => app.search_cursor_end(),
         KeyCode::Enter => app.exit_search_mode(),
         KeyCode::Esc => {
             app.exit_search_mode();
@@ -184,51 +677,91 @@ fn handle_search_input(app: &mut App, key: KeyCode) {
 
 fn handle_detail_input(app: &mut App, key: KeyCode) {
     const PAGE_SIZE: usize = 10;
-    const VISIBLE_HEIGHT: usize = 20;
 
     match key {
         KeyCode::Char('q') | KeyCode::Esc => app.show_table_view(),
+        KeyCode::Up | KeyCode::Char('k') if app.detail_parts => app.move_detail_part(false),
+        KeyCode::Down | KeyCode::Char('j') if app.detail_parts => app.move_detail_part(true),
+        KeyCode::Enter if app.detail_parts => app.toggle_detail_part_open(),
         KeyCode::Up | KeyCode::Char('k') => app.scroll_detail_up(),
         KeyCode::Down | KeyCode::Char('j') => app.scroll_detail_down(),
         KeyCode::PageUp => app.page_detail_up(PAGE_SIZE),
         KeyCode::PageDown => app.page_detail_down(PAGE_SIZE),
         KeyCode::Home => app.scroll_detail_home(),
         KeyCode::End => app.scroll_detail_end(),
-        KeyCode::Left | KeyCode::Char('h') => {
-            app.move_selection_up();
-            app.detail_scroll = 0; // Reset scroll to top when switching entries
-            // Fetch new IP info for the new entry
-            if app.ip_api_enabled {
-                if let Some(group) = app.selected_group() {
-                    app.current_ip_info = app.ip_api_cache.get_ip_info(&group.client_ip).ok();
-                }
-            }
-        },
-        KeyCode::Right | KeyCode::Char('l') => {
-            app.move_selection_down(VISIBLE_HEIGHT);
-            app.detail_scroll = 0; // Reset scroll to top when switching entries
-            // Fetch new IP info for the new entry
-            if app.ip_api_enabled {
-                if let Some(group) = app.selected_group() {
-                    app.current_ip_info = app.ip_api_cache.get_ip_info(&group.client_ip).ok();
-                }
-            }
-        },
+        KeyCode::Char('c') => app.copy_selected_chain(),
+        KeyCode::Char('C') => app.copy_selected_curl(),
+        KeyCode::Char('f') => app.toggle_detail_sections(),
+        KeyCode::Char('r') => app.toggle_detail_raw(),
+        KeyCode::Char('t') => app.toggle_detail_header_table(),
+        KeyCode::Char('x') => app.toggle_detail_data_expanded(),
+        KeyCode::Char('H') => app.toggle_detail_hexdump(),
+        KeyCode::Char('w') => app.toggle_detail_timeline(),
+        KeyCode::Char('W') => app.toggle_detail_wrap(),
+        KeyCode::Char('<') => app.scroll_detail_sideways(-DETAIL_HSCROLL_STEP),
+        KeyCode::Char('>') => app.scroll_detail_sideways(DETAIL_HSCROLL_STEP),
+        KeyCode::Char('i') => app.pivot_on_selected("ip"),
+        KeyCode::Char('d') => app.pivot_on_selected("domain"),
+        KeyCode::Char('R') => app.pivot_on_selected("rule"),
+        KeyCode::Char('p') => app.toggle_detail_parts(),
+        KeyCode::Char('o') => open_ip(app),
+        KeyCode::Char('a') => app.lookup_abuse_contact(),
+        KeyCode::Char('I') => app.toggle_ip_api(),
+        KeyCode::Left | KeyCode::Char('h') => app.step_detail_entry(false),
+        KeyCode::Right | KeyCode::Char('l') => app.step_detail_entry(true),
+        _ => {}
+    }
+}
+
+fn handle_domain_input(app: &mut App, key: KeyCode) {
+    let visible_height = app.visible_height;
+
+    match key {
+        KeyCode::Char('q') | KeyCode::Char('d') | KeyCode::Esc => app.show_table_view(),
+        KeyCode::Up | KeyCode::Char('k') => app.move_domain_selection(-1, visible_height),
+        KeyCode::Down | KeyCode::Char('j') => app.move_domain_selection(1, visible_height),
+        KeyCode::PageUp => app.move_domain_selection(-(visible_height as isize), visible_height),
+        KeyCode::PageDown => app.move_domain_selection(visible_height as isize, visible_height),
+        KeyCode::Home => app.move_domain_selection(isize::MIN, visible_height),
+        KeyCode::End => app.move_domain_selection(isize::MAX, visible_height),
+        KeyCode::Enter => app.drill_into_domain(),
+        _ => {}
+    }
+}
+
+fn handle_stats_input(app: &mut App, key: KeyCode) {
+    const PAGE_SIZE: isize = 10;
+
+    match key {
+        KeyCode::Char('q') | KeyCode::Char('s') | KeyCode::Esc => app.show_table_view(),
+        KeyCode::Up | KeyCode::Char('k') => app.move_stats_selection(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_stats_selection(1),
+        KeyCode::PageUp => app.move_stats_selection(-PAGE_SIZE),
+        KeyCode::PageDown => app.move_stats_selection(PAGE_SIZE),
+        KeyCode::Home => app.move_stats_selection(isize::MIN),
+        KeyCode::End => app.move_stats_selection(isize::MAX),
+        KeyCode::Enter => app.drill_into_stats(),
         _ => {}
     }
 }
 
 fn handle_mouse_input(app: &mut App, mouse: MouseEvent) {
+    match mouse.kind {
+        // Pressing a header separator resizes its column instead of selecting
+        MouseEventKind::Down(_) if app.start_column_drag(mouse.column, mouse.row) => return,
+        MouseEventKind::Drag(_) => return app.drag_column(mouse.column),
+        MouseEventKind::Up(_) => return app.end_column_drag(),
+        _ => {}
+    }
     if let MouseEventKind::Down(_) = mouse.kind {
-        // Use the stored table area to properly calculate which row was clicked
-        if let Some(table_area) = app.table_area {
-            // Table has borders (1 top, 1 bottom) and a header row (1)
-            // So content starts at table_area.y + 2 (top border + header)
-            let content_start = table_area.y + 2;
-            let content_height = table_area.height.saturating_sub(3) as usize; // Subtract top border, header, bottom border
+        // Use the stored table area to properly calculate which row was clicked.
+        // The body excludes borders and the header row (dense mode: header only)
+        if let Some(body) = app.table_body() {
+            let content_start = body.y;
+            let content_height = body.height as usize;
 
             // Check if click is within the table content area
-            if mouse.row >= content_start && mouse.row < table_area.y + table_area.height - 1 {
+            if mouse.row >= content_start && mouse.row < body.y + body.height {
                 let clicked_row = (mouse.row - content_start) as usize;
                 let actual_index = app.scroll_offset + clicked_row;
 
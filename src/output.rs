@@ -0,0 +1,350 @@
+use crate::app::{RuleStats, Stats, STATS_TOP_N};
+use crate::parser::AuditGroup;
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Headless output formats (`--format`): print the filtered transactions
+/// instead of opening the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns, like the table view
+    Table,
+    /// One JSON array of transactions
+    Json,
+    /// A GitHub-flavored Markdown table, for issues and wikis
+    Markdown,
+    /// One JSON object per line, printed as the log is read (for jq and pipes)
+    Ndjson,
+}
+
+/// A transaction as printed by the headless output: the `AuditGroup` fields,
+/// without the raw text of its parts
+#[derive(Debug, Serialize)]
+pub struct GroupRecord<'a> {
+    pub audit_id: &'a str,
+    pub timestamp: String,
+    pub domain: &'a str,
+    pub client_ip: &'a str,
+    pub http_status: Option<u16>,
+    pub blocked: bool,
+    pub incomplete: bool,
+    pub collided: bool,
+    pub engine_mode: Option<&'a str>,
+    pub action: Option<&'a str>,
+    pub rule_ids: &'a [String],
+    pub request_uri: Option<&'a str>,
+    pub file_path: Option<&'a str>,
+    pub request_size: Option<u64>,
+    pub response_size: Option<u64>,
+    pub parts: usize,
+}
+
+impl<'a> From<&'a AuditGroup> for GroupRecord<'a> {
+    fn from(group: &'a AuditGroup) -> Self {
+        Self {
+            audit_id: &group.base_id,
+            timestamp: group.first_timestamp.to_rfc3339(),
+            domain: &group.domain,
+            client_ip: &group.client_ip,
+            http_status: group.http_status.code(),
+            blocked: group.blocked,
+            incomplete: group.incomplete,
+            collided: group.collided,
+            engine_mode: group.engine_mode.as_deref(),
+            action: group.action.as_deref(),
+            rule_ids: &group.primary_rule_ids,
+            request_uri: group.request_uri.as_deref(),
+            file_path: group.file_path.as_deref(),
+            request_size: group.request_size,
+            response_size: group.response_size,
+            parts: group.entries.len(),
+        }
+    }
+}
+
+/// Print `groups` in `format`
+pub fn write_groups(format: OutputFormat, groups: &[&AuditGroup], out: &mut impl Write) -> Result<()> {
+    match format {
+        OutputFormat::Table => write_table(groups, out),
+        OutputFormat::Markdown => {
+            write!(out, "{}", markdown_table(&GROUP_HEADERS, &group_rows(groups)))?;
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let records: Vec<GroupRecord> = groups.iter().map(|g| GroupRecord::from(*g)).collect();
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)?;
+            Ok(())
+        }
+        OutputFormat::Ndjson => groups.iter().try_for_each(|group| write_ndjson(group, out)),
+    }
+}
+
+/// One transaction as a single line of JSON (`--format ndjson`)
+pub fn write_ndjson(group: &AuditGroup, out: &mut impl Write) -> Result<()> {
+    serde_json::to_writer(&mut *out, &GroupRecord::from(group))?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// `--rule` report: the transactions that fired the rule, then how its hits
+/// spread over domains and client IPs. JSON puts both in one object.
+pub fn write_rule_report(format: OutputFormat, groups: &[&AuditGroup], stats: &RuleStats, out: &mut impl Write) -> Result<()> {
+    match format {
+        OutputFormat::Table => {
+            write_table(groups, out)?;
+            writeln!(out)?;
+            write_rule_summary(stats, false, out)
+        }
+        OutputFormat::Markdown => {
+            write!(out, "{}", markdown_table(&GROUP_HEADERS, &group_rows(groups)))?;
+            writeln!(out)?;
+            write_rule_summary(stats, true, out)
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let ndjson = format == OutputFormat::Ndjson;
+            let report = RuleReport {
+                rule_id: &stats.rule_id,
+                transactions: stats.transactions,
+                blocked: stats.blocked,
+                domains: stats.domains.iter().map(|d| RuleDomainRecord {
+                    domain: &d.domain,
+                    hits: d.hits,
+                    blocked: d.blocked,
+                    distinct_ips: d.distinct_ips,
+                    share: d.share,
+                }).collect(),
+                client_ips: stats.client_ips.iter().map(|ip| RuleIpRecord {
+                    client_ip: &ip.client_ip,
+                    hits: ip.hits,
+                    domains: ip.domains,
+                }).collect(),
+                matches: (!ndjson).then(|| groups.iter().map(|g| GroupRecord::from(*g)).collect()),
+            };
+            if ndjson {
+                groups.iter().try_for_each(|group| write_ndjson(group, out))?;
+                serde_json::to_writer(&mut *out, &report)?;
+            } else {
+                serde_json::to_writer_pretty(&mut *out, &report)?;
+            }
+            writeln!(out)?;
+            Ok(())
+        }
+    }
+}
+
+/// `--report`: `stats` (from `App::compute_stats`) as one JSON object, with
+/// the files and search filter they cover
+pub fn write_stats_report(stats: &Stats, files: &[String], filter: &str, out: &mut impl Write) -> Result<()> {
+    fn counts(rows: &[(String, usize)]) -> Vec<CountRecord<'_>> {
+        rows.iter().map(|(value, count)| CountRecord { value, count: *count }).collect()
+    }
+    let report = StatsReport {
+        generated: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        files,
+        filter: (!filter.is_empty()).then_some(filter),
+        total: stats.total,
+        blocked: stats.blocked,
+        detection_only: stats.total - stats.blocked,
+        first_seen: stats.time_range.map(|(first, _)| first.to_rfc3339()),
+        last_seen: stats.time_range.map(|(_, last)| last.to_rfc3339()),
+        status_classes: stats.status_classes.iter().map(|(class, count)| (class.as_str(), *count)).collect(),
+        top_ips: counts(&stats.top_ips),
+        top_rules: counts(&stats.top_rules),
+        top_domains: counts(&stats.top_domains),
+    };
+    serde_json::to_writer_pretty(&mut *out, &report)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// `write_stats_report`'s JSON
+#[derive(Debug, Serialize)]
+struct StatsReport<'a> {
+    generated: String,
+    files: &'a [String],
+    filter: Option<&'a str>,
+    total: usize,
+    blocked: usize,
+    detection_only: usize,
+    first_seen: Option<String>,
+    last_seen: Option<String>,
+    status_classes: BTreeMap<&'a str, usize>,
+    top_ips: Vec<CountRecord<'a>>,
+    top_rules: Vec<CountRecord<'a>>,
+    top_domains: Vec<CountRecord<'a>>,
+}
+
+/// One entry of a "top" list
+#[derive(Debug, Serialize)]
+struct CountRecord<'a> {
+    value: &'a str,
+    count: usize,
+}
+
+/// `write_rule_report` in JSON
+#[derive(Debug, Serialize)]
+struct RuleReport<'a> {
+    rule_id: &'a str,
+    transactions: usize,
+    blocked: usize,
+    domains: Vec<RuleDomainRecord<'a>>,
+    client_ips: Vec<RuleIpRecord<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matches: Option<Vec<GroupRecord<'a>>>, // None for NDJSON, which prints them first
+}
+
+#[derive(Debug, Serialize)]
+struct RuleDomainRecord<'a> {
+    domain: &'a str,
+    hits: usize,
+    blocked: usize,
+    distinct_ips: usize,
+    share: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleIpRecord<'a> {
+    client_ip: &'a str,
+    hits: usize,
+    domains: usize,
+}
+
+fn write_rule_summary(stats: &RuleStats, markdown: bool, out: &mut impl Write) -> Result<()> {
+    let rule = if markdown { format!("**Rule {}**", stats.rule_id) } else { format!("Rule {}", stats.rule_id) };
+    writeln!(
+        out,
+        "{}: {} ({} blocked) on {} from {}",
+        rule,
+        plural(stats.transactions, "transaction"),
+        stats.blocked,
+        plural(stats.domains.len(), "domain"),
+        plural(stats.client_ips.len(), "client IP"),
+    )?;
+    if stats.transactions == 0 {
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = stats.domains.iter()
+        .map(|d| vec![
+            d.domain.clone(),
+            d.hits.to_string(),
+            d.blocked.to_string(),
+            d.distinct_ips.to_string(),
+            format!("{:.0}%", d.share * 100.0),
+        ])
+        .collect();
+    write_summary_table(&["Domain", "Hits", "Blocked", "IPs", "Of domain"], &rows, markdown, out)?;
+    // A rule that fires on most of a domain's traffic from many IPs is
+    // likely tripped by normal requests rather than an attack
+    write_note(out, markdown, "(Of domain: share of the domain's transactions that fired the rule)")?;
+
+    let rows: Vec<Vec<String>> = stats.client_ips.iter()
+        .take(STATS_TOP_N)
+        .map(|ip| vec![ip.client_ip.clone(), ip.hits.to_string(), ip.domains.to_string()])
+        .collect();
+    write_summary_table(&["Client IP", "Hits", "Domains"], &rows, markdown, out)?;
+    if stats.client_ips.len() > STATS_TOP_N {
+        let more = format!("... and {} more", plural(stats.client_ips.len() - STATS_TOP_N, "client IP"));
+        write_note(out, markdown, &more)?;
+    }
+    Ok(())
+}
+
+/// A blank line, then the table as Markdown or aligned columns
+fn write_summary_table(headers: &[&str], rows: &[Vec<String>], markdown: bool, out: &mut impl Write) -> Result<()> {
+    writeln!(out)?;
+    if markdown {
+        write!(out, "{}", markdown_table(headers, rows))?;
+        Ok(())
+    } else {
+        write_columns(headers, rows, out)
+    }
+}
+
+/// A line under a table; Markdown needs a blank line first, or the text
+/// would become another row
+fn write_note(out: &mut impl Write, markdown: bool, note: &str) -> Result<()> {
+    if markdown {
+        writeln!(out)?;
+    }
+    writeln!(out, "{}", note)?;
+    Ok(())
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+const GROUP_HEADERS: [&str; 6] = ["Audit ID", "Timestamp", "Domain", "Client IP", "Status", "Rule IDs"];
+
+/// The cells of `GROUP_HEADERS` for every group
+fn group_rows(groups: &[&AuditGroup]) -> Vec<Vec<String>> {
+    groups
+        .iter()
+        .map(|group| {
+            let mut status = group.http_status.label();
+            if group.blocked {
+                status.push_str(" ✖");
+            }
+            vec![
+                group.base_id.clone(),
+                group.first_timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                group.domain.clone(),
+                group.client_ip.clone(),
+                status,
+                group.primary_rule_ids.join(", "),
+            ]
+        })
+        .collect()
+}
+
+fn write_table(groups: &[&AuditGroup], out: &mut impl Write) -> Result<()> {
+    let rows = group_rows(groups);
+    write_columns(&GROUP_HEADERS, &rows, out)?;
+    writeln!(out, "{}", plural(rows.len(), "transaction"))?;
+    Ok(())
+}
+
+/// A GitHub-flavored Markdown table. `|` inside a value is escaped and line
+/// breaks become spaces, so a cell can never end early or split the row.
+pub fn markdown_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut table = line(headers.iter().map(|h| markdown_cell(h)).collect());
+    table.push_str(&line(headers.iter().map(|_| "---".to_string()).collect()));
+    for row in rows {
+        table.push_str(&line(row.iter().map(|cell| markdown_cell(cell)).collect()));
+    }
+    table
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Left-aligned columns, two spaces apart, each as wide as its widest cell
+fn write_columns(headers: &[&str], rows: &[Vec<String>], out: &mut impl Write) -> Result<()> {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut write_row = |cells: &[&str]| -> Result<()> {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell))
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())?;
+        Ok(())
+    };
+    write_row(headers)?;
+    for row in rows {
+        write_row(&row.iter().map(String::as_str).collect::<Vec<_>>())?;
+    }
+    Ok(())
+}
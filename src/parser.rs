@@ -1,12 +1,166 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use regex::Regex;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io::{Read, Seek, SeekFrom};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use std::io;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use crate::loading::LoadingScreen;
+use crate::loading::{band_progress, LoadingScreen, REDRAW_INTERVAL};
+
+/// Client IP shown when the A section holds no parseable address
+pub const UNKNOWN_IP: &str = "unknown";
+
+/// Request headers `--trust-xff` reads the real client address from, first
+/// present wins: the single-address headers CDNs set, then the leftmost
+/// `X-Forwarded-For` hop
+pub const DEFAULT_TRUSTED_IP_HEADERS: &[&str] =
+    &["CF-Connecting-IP", "True-Client-IP", "X-Real-IP", "X-Client-IP", "X-Forwarded-For"];
+
+/// The A line fields after the timestamp as ModSecurity 2.x and 3.x log
+/// them; `--a-section-format` describes other layouts the same way
+pub const DEFAULT_A_SECTION_FORMAT: &str = "id client-ip client-port server-ip server-port";
+
+/// `--a-section-format`: the fields of the A line after the `[timestamp]`,
+/// separated by spaces. Only `client-ip` is looked for, the other names just
+/// hold their place. Returns its position (0 = first field after the
+/// timestamp).
+pub fn parse_a_section_format(text: &str) -> Result<usize> {
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    // Allow the timestamp to be spelled out too
+    let fields = match fields.first() {
+        Some(first) if first.eq_ignore_ascii_case("timestamp") => &fields[1..],
+        _ => &fields[..],
+    };
+    let mut positions = fields.iter().enumerate()
+        .filter(|(_, field)| field.eq_ignore_ascii_case("client-ip"))
+        .map(|(position, _)| position);
+    match (positions.next(), positions.next()) {
+        (Some(position), None) => Ok(position),
+        (None, _) => anyhow::bail!("no client-ip field in '{}' (e.g. \"{}\")", text, DEFAULT_A_SECTION_FORMAT),
+        (Some(_), Some(_)) => anyhow::bail!("client-ip is listed twice in '{}'", text),
+    }
+}
+
+/// `--trusted-ip-headers`: comma-separated header names, tried in order
+pub fn parse_trusted_ip_headers(text: &str) -> Result<Vec<String>> {
+    let mut headers: Vec<String> = Vec::new();
+    for name in text.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("'{}' is not a header name", name);
+        }
+        if !headers.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+            headers.push(name.to_string());
+        }
+    }
+    if headers.is_empty() {
+        anyhow::bail!("at least one header is needed");
+    }
+    Ok(headers)
+}
+
+/// Shortest audit ID prefix shown with `--short-ids`
+pub const SHORT_ID_LEN: usize = 8;
+
+/// Order in which ModSecurity writes the sections of a transaction
+const SECTION_ORDER: &str = "ABICJDFEGHKZ";
+
+/// Position of an entry's first section in `SECTION_ORDER` (unknown last)
+fn section_rank(entry: &AuditEntry) -> usize {
+    entry.raw_content
+        .lines()
+        .next()
+        .and_then(|line| line.trim_end().strip_suffix("--"))
+        .and_then(|line| line.chars().last())
+        .and_then(|letter| SECTION_ORDER.find(letter))
+        .unwrap_or(SECTION_ORDER.len())
+}
+
+/// How far apart the A sections under one audit ID may be before the group
+/// counts as a collision
+pub const COLLISION_WINDOW: Duration = Duration::from_secs(60);
+
+/// Whether the entries under one audit ID look like different transactions:
+/// several parts open with an A section (only the first part of a
+/// transaction does) and those disagree on the client IP or lie more than
+/// `COLLISION_WINDOW` apart. The same part read twice (a file given twice)
+/// agrees on both, so it is not a collision.
+pub fn is_collision(entries: &[AuditEntry]) -> bool {
+    let heads: Vec<&AuditEntry> = entries.iter().filter(|e| section_rank(e) == 0).collect();
+    let (Some(first), Some(last)) = (
+        heads.iter().map(|e| e.timestamp).min(),
+        heads.iter().map(|e| e.timestamp).max(),
+    ) else {
+        return false;
+    };
+    let apart = (last - first).to_std().unwrap_or_default();
+    heads.iter().any(|e| e.client_ip != heads[0].client_ip) || apart > COLLISION_WINDOW
+}
+
+/// `--split-on-ip`: one group per client IP of a collided audit ID. Parts
+/// without an A section go with the A part before them in the log (the
+/// first one if none came before). Split groups get `@<ip>` appended to
+/// their `base_id` so they stay apart, and keep `collided` set.
+pub fn split_by_client_ip(entries: Vec<AuditEntry>) -> Vec<AuditGroup> {
+    if !is_collision(&entries) {
+        return vec![AuditGroup::from_entries(entries)];
+    }
+    let mut by_ip: Vec<(String, Vec<AuditEntry>)> = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut orphans = Vec::new();
+    for entry in entries {
+        if section_rank(&entry) == 0 {
+            let i = match by_ip.iter().position(|(ip, _)| *ip == entry.client_ip) {
+                Some(i) => i,
+                None => {
+                    by_ip.push((entry.client_ip.clone(), Vec::new()));
+                    by_ip.len() - 1
+                }
+            };
+            current = Some(i);
+            by_ip[i].1.push(entry);
+        } else {
+            match current {
+                Some(i) => by_ip[i].1.push(entry),
+                None => orphans.push(entry),
+            }
+        }
+    }
+    by_ip[0].1.extend(orphans);
+
+    let split = by_ip.len() > 1;
+    by_ip.into_iter()
+        .map(|(ip, entries)| {
+            let mut group = AuditGroup::from_entries(entries);
+            if split {
+                group.base_id = format!("{}@{}", group.base_id, ip);
+            }
+            group.collided = true;
+            group
+        })
+        .collect()
+}
+
+/// Accept an address token as logged (`203.0.113.7`, `2001:db8::1`) or with
+/// brackets/port (`[2001:db8::1]`, `203.0.113.7:51000`). Valid addresses keep
+/// their original spelling; anything else is rejected.
+fn normalize_ip(token: &str) -> Option<String> {
+    let token = token.trim_matches(|c: char| c == ',' || c == ';');
+    if token.parse::<IpAddr>().is_ok() {
+        return Some(token.to_string());
+    }
+    let unbracketed = token.trim_start_matches('[').trim_end_matches(']');
+    if unbracketed.parse::<IpAddr>().is_ok() {
+        return Some(unbracketed.to_string());
+    }
+    token.parse::<SocketAddr>().ok().map(|addr| addr.ip().to_string())
+}
 
 #[derive(Debug, Clone)]
 pub struct AuditEntry {
@@ -15,9 +169,50 @@ pub struct AuditEntry {
     pub domain: String,
     pub rule_ids: Vec<String>,
     pub client_ip: String,
-    pub http_status: Option<u16>,
+    pub peer_ip: Option<String>, // Section A address when a trusted header gave `client_ip`
+    pub http_status: HttpStatus,
     pub raw_content: String,
     pub file_path: Option<String>,
+    pub request_uri: Option<String>, // Target of the request line in section B
+    pub request_size: Option<u64>,  // Request body bytes (Content-Length, else section C length)
+    pub response_size: Option<u64>, // Response body bytes (Content-Length, else section E length)
+    pub incomplete: bool, // The log ended before this entry's `--id-Z--` boundary
+    pub intercepted: bool, // ModSecurity took a disruptive action (H: `Action: Intercepted`)
+    pub action: Option<String>, // What ModSecurity did, see `AuditEntry::action`
+    pub engine_mode: Option<String>, // H: `Engine-Mode: "ENABLED"` / `"DETECTION_ONLY"`, absent in older logs
+    pub matched_data: Vec<String>, // Every `[data "..."]` of the rule messages, unescaped
+    pub raw_bytes: Option<Vec<u8>>, // Undecoded `raw_content`, kept only when it had invalid UTF-8
+}
+
+/// The response status of an entry or group, from its F section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpStatus {
+    /// No F section: the response was never logged (aborted request, a part
+    /// still to come, or audit parts configured without F)
+    #[default]
+    Missing,
+    /// An F section whose status line `http_status_re` could not read
+    Unparsed,
+    Code(u16),
+}
+
+impl HttpStatus {
+    pub fn code(self) -> Option<u16> {
+        match self {
+            HttpStatus::Code(code) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Table text: the code, `—` without an F section, `?` when it could
+    /// not be read
+    pub fn label(self) -> String {
+        match self {
+            HttpStatus::Code(code) => code.to_string(),
+            HttpStatus::Missing => "—".to_string(),
+            HttpStatus::Unparsed => "?".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -27,21 +222,51 @@ pub struct AuditGroup {
     pub first_timestamp: DateTime<Utc>,
     pub domain: String,
     pub client_ip: String,
-    pub http_status: Option<u16>,
+    pub http_status: HttpStatus,
     pub primary_rule_ids: Vec<String>,
     pub file_path: Option<String>,
+    pub request_uri: Option<String>,
+    pub request_size: Option<u64>,
+    pub response_size: Option<u64>,
+    pub incomplete: bool, // Some part was cut off (log still being written)
+    pub blocked: bool, // Stopped by ModSecurity rather than only logged, see `is_blocked`
+    pub engine_mode: Option<String>, // First engine mode found in the chain's parts
+    pub action: Option<String>, // An intercepting part's action, else the first known one
+    pub collided: bool, // Looks like several transactions sharing one audit ID, see `is_collision`
 }
 
 impl AuditGroup {
-    pub fn from_entries(entries: Vec<AuditEntry>) -> Self {
+    pub fn from_entries(mut entries: Vec<AuditEntry>) -> Self {
+        // Parts can arrive out of order when a transaction was split across
+        // files (logrotate) and the files were given newest first. Stable, so
+        // parts starting with the same section keep their log order.
+        entries.sort_by_key(section_rank);
         let base_id = entries[0].audit_id.clone();
         let first_timestamp = entries.iter().map(|e| e.timestamp).min().unwrap();
         let domain = entries[0].domain.clone();
-        let client_ip = entries[0].client_ip.clone();
+        // Continuation parts have no A section; take the first known address
+        let client_ip = entries.iter()
+            .map(|e| &e.client_ip)
+            .find(|ip| *ip != UNKNOWN_IP)
+            .unwrap_or(&entries[0].client_ip)
+            .clone();
 
         let mut rule_ids = Vec::new();
         let mut file_path = None;
-        let mut http_status = None;
+        let mut request_uri = None;
+        let mut http_status = HttpStatus::Missing;
+        let mut request_size = None;
+        let mut response_size = None;
+        // Only the last part can be cut off; an earlier cut (the end of a
+        // rotated file) is completed by the parts after it
+        let incomplete = entries.last().is_some_and(|e| e.incomplete);
+        let intercepted = entries.iter().any(|e| e.intercepted);
+        let engine_mode = entries.iter().find_map(|e| e.engine_mode.clone());
+        let action = entries.iter().filter_map(|e| e.action.as_ref())
+            .find(|action| is_intercept(action))
+            .or_else(|| entries.iter().find_map(|e| e.action.as_ref()))
+            .cloned();
+        let collided = is_collision(&entries);
 
         for entry in &entries {
             for rule_id in &entry.rule_ids {
@@ -53,12 +278,23 @@ impl AuditGroup {
             if file_path.is_none() && entry.file_path.is_some() {
                 file_path = entry.file_path.clone();
             }
-            // Get the first non-None HTTP status
-            if http_status.is_none() && entry.http_status.is_some() {
-                http_status = entry.http_status;
+            // Get the first status code; failing that, note an F section
+            // that could not be read
+            http_status = match (http_status, entry.http_status) {
+                (HttpStatus::Code(code), _) | (_, HttpStatus::Code(code)) => HttpStatus::Code(code),
+                (HttpStatus::Unparsed, _) | (_, HttpStatus::Unparsed) => HttpStatus::Unparsed,
+                _ => HttpStatus::Missing,
+            };
+            if request_uri.is_none() {
+                request_uri = entry.request_uri.clone();
             }
+            // Sizes live in whichever part holds the B/C and E/F sections
+            request_size = request_size.or(entry.request_size);
+            response_size = response_size.or(entry.response_size);
         }
 
+        let blocked = is_blocked(http_status.code(), intercepted, &rule_ids);
+
         Self {
             base_id,
             entries,
@@ -68,25 +304,538 @@ impl AuditGroup {
             http_status,
             primary_rule_ids: rule_ids,
             file_path,
+            request_uri,
+            request_size,
+            response_size,
+            incomplete,
+            blocked,
+            engine_mode,
+            action,
+            collided,
+        }
+    }
+
+    /// Every rule ID fired across the group's entries with its hit count,
+    /// most hits first (ties keep first-seen order)
+    pub fn rule_id_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for rule_id in self.entries.iter().flat_map(|e| &e.rule_ids) {
+            match counts.iter_mut().find(|(id, _)| id == rule_id) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((rule_id.clone(), 1)),
+            }
+        }
+        // Stable sort keeps first-seen order among equal counts
+        counts.sort_by_key(|(_, count)| Reverse(*count));
+        counts
+    }
+
+    /// Time of each entry relative to the chain's first timestamp, for the
+    /// detail view's timeline. `None` for continuation parts, which have no
+    /// A section and so no timestamp of their own.
+    pub fn entry_offsets(&self) -> Vec<Option<Duration>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (section_rank(e) == 0)
+                    .then(|| (e.timestamp - self.first_timestamp).to_std().unwrap_or_default())
+            })
+            .collect()
+    }
+
+    /// How long ModSecurity took over the transaction, from the
+    /// `Stopwatch: <start µs> <duration µs>` line of section H (ModSecurity
+    /// 2; `Stopwatch2` carries the same figures)
+    pub fn processing_time(&self) -> Option<Duration> {
+        self.entries
+            .iter()
+            .flat_map(|e| e.raw_content.lines())
+            .find_map(|line| {
+                let (name, value) = split_header(line.trim_end())?;
+                if name != "Stopwatch" && name != "Stopwatch2" {
+                    return None;
+                }
+                let micros = value.split(|c: char| c.is_whitespace() || c == ';').nth(1)?;
+                micros.parse().ok().map(Duration::from_micros)
+            })
+    }
+
+    /// The raw text of every entry in the chain, as it appears in the log
+    pub fn raw_chain(&self) -> String {
+        let mut chain = String::new();
+        for entry in &self.entries {
+            chain.push_str(&entry.raw_content);
+            if !chain.ends_with('\n') {
+                chain.push('\n');
+            }
+        }
+        chain
+    }
+
+    /// An approximate `curl` command repeating the logged request, to
+    /// reproduce it against a test system: method and target from section
+    /// B's request line, the Host header (with https when the server port in
+    /// section A is 443), the other headers and the body from section C. The
+    /// target is percent-escaped where a URL can't carry the character, and
+    /// `-g --path-as-is` keep curl from globbing or normalizing the payload.
+    /// A leading `#` line notes a body that was cut short or not logged.
+    /// `None` without a request line.
+    pub fn to_curl(&self) -> Option<String> {
+        let request = self.section_text('B')?;
+        let mut request_line = request.lines().next()?.split_whitespace();
+        let (method, target) = (request_line.next()?, request_line.next()?);
+        let headers = parse_headers(&request);
+
+        let header = |name: &str| headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.trim());
+        let url = if target.starts_with("http://") || target.starts_with("https://") {
+            escape_url(target)
+        } else {
+            // `[date] id client-ip client-port server-ip server-port`
+            let server_port = self.section_text('A')
+                .and_then(|a| a.lines().next().and_then(|line| line.split_once(']')).map(|(_, rest)| rest.to_string()))
+                .and_then(|rest| rest.split_whitespace().nth(4).map(str::to_string));
+            let scheme = if server_port.as_deref() == Some("443") { "https" } else { "http" };
+            let host = header("Host").unwrap_or(&self.domain);
+            format!("{}://{}{}", scheme, host, escape_url(target))
+        };
+
+        let mut notes = Vec::new();
+        let body = match self.section_text('C') {
+            Some(body) => Some(body),
+            None => {
+                // Multipart bodies can be logged with the files left out
+                let body = self.section_text('I');
+                if body.is_some() {
+                    notes.push("# Body from section I: uploaded files are left out".to_string());
+                }
+                body
+            }
+        };
+        let body = body.map(|body| body.trim_end_matches('\n').to_string()).filter(|body| !body.is_empty());
+        let declared = header("Content-Length").and_then(|length| length.parse::<usize>().ok());
+        match (&body, declared) {
+            (Some(body), Some(declared)) if body.len() < declared => notes.push(format!(
+                "# Request body truncated in the log: {} of {} bytes",
+                body.len(),
+                declared
+            )),
+            (None, Some(declared)) if declared > 0 => {
+                notes.push(format!("# Request body not logged ({} bytes declared)", declared))
+            }
+            _ => {}
+        }
+
+        let mut args = vec![match method {
+            "GET" if body.is_none() => format!("curl -g --path-as-is {}", shell_quote(&url)),
+            _ => format!("curl -g --path-as-is -X {} {}", method, shell_quote(&url)),
+        }];
+        for (name, value) in &headers {
+            // curl sets both from the URL and the body
+            if name.eq_ignore_ascii_case("Host") || name.eq_ignore_ascii_case("Content-Length") {
+                continue;
+            }
+            args.push(format!("-H {}", shell_quote(&format!("{}: {}", name, value.trim_end()))));
+        }
+        if let Some(body) = body {
+            args.push(format!("--data-binary {}", shell_quote(&body)));
+        }
+        notes.push(args.join(" \\\n  "));
+        Some(notes.join("\n"))
+    }
+
+    /// Text of the first `letter` section in the chain's parts, without its
+    /// boundary line
+    fn section_text(&self, letter: char) -> Option<String> {
+        let content = self.entries.iter()
+            .map(|e| e.raw_content.as_str())
+            .find(|content| content.lines().any(|line| section_marker(line) == Some(letter)))?;
+        let mut text = String::new();
+        let mut inside = false;
+        for line in content.lines() {
+            match section_marker(line) {
+                Some(found) if inside || found == letter => {
+                    if inside {
+                        break;
+                    }
+                    inside = true;
+                }
+                _ if inside => {
+                    text.push_str(line);
+                    text.push('\n');
+                }
+                _ => {}
+            }
+        }
+        Some(text)
+    }
+
+    /// The first `len` characters of the audit ID, for the table's short-ID
+    /// mode. Search and the detail view always use the full `base_id`.
+    pub fn display_id(&self, len: usize) -> &str {
+        match self.base_id.char_indices().nth(len) {
+            Some((end, _)) => &self.base_id[..end],
+            None => &self.base_id,
+        }
+    }
+}
+
+/// Shortest prefix length (at least `SHORT_ID_LEN`) that still tells every
+/// audit ID in `groups` apart
+pub fn short_id_len(groups: &[AuditGroup]) -> usize {
+    let mut ids: Vec<&str> = groups.iter().map(|g| g.base_id.as_str()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.windows(2)
+        .map(|pair| {
+            let common = pair[0].chars().zip(pair[1].chars()).take_while(|(a, b)| a == b).count();
+            common + 1
+        })
+        .fold(SHORT_ID_LEN, usize::max)
+}
+
+/// Split a `Name: value` header line. Request and status lines or body text
+/// (empty name, or whitespace in it) are not headers.
+pub fn split_header(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.split_once(':')?;
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((name, value.trim_start()))
+}
+
+/// Percent-escape what a URL can't carry literally (spaces, quotes, control
+/// and non-ASCII bytes, ...). `%` stays, as the logged target is already
+/// encoded wherever the client encoded it.
+fn escape_url(target: &str) -> String {
+    let mut url = String::with_capacity(target.len());
+    for &byte in target.as_bytes() {
+        if byte.is_ascii_graphic() && !b"\"<>\\^`{|}".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    url
+}
+
+/// Single-quote `text` for a POSIX shell
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Section letter of a boundary line such as `--1a2b3c4d-H--`
+pub fn section_marker(line: &str) -> Option<char> {
+    let (_, letter) = line.strip_prefix("--")?.strip_suffix("--")?.rsplit_once('-')?;
+    let mut chars = letter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_uppercase() => Some(letter),
+        _ => None,
+    }
+}
+
+/// Headers of a B (request) or F (response) section: the lines after its
+/// request/status line, up to the first blank line
+pub fn parse_headers(section: &str) -> Vec<(&str, &str)> {
+    section
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+        .filter_map(split_header)
+        .collect()
+}
+
+/// Anomaly scoring rules that only fire when the inbound score reached the
+/// blocking threshold (CRS `949110` and its `980130` correlation report)
+pub const BLOCKING_RULE_IDS: &[&str] = &["949110", "980130"];
+
+/// Whether an action (`AuditEntry::action`) stopped the request
+pub fn is_intercept(action: &str) -> bool {
+    action.starts_with("Intercepted")
+}
+
+/// Short table badge for an action: `block` for an interception, `warn`
+/// when rules only warned
+pub fn action_badge(action: Option<&str>) -> &'static str {
+    match action {
+        Some(action) if is_intercept(action) => "block",
+        Some(_) => "warn",
+        None => "",
+    }
+}
+
+/// Whether a transaction was stopped rather than only logged (detection
+/// only): a disruptive action, a 403/429 answer, or a CRS blocking rule
+pub fn is_blocked(http_status: Option<u16>, intercepted: bool, rule_ids: &[String]) -> bool {
+    intercepted
+        || matches!(http_status, Some(403 | 429))
+        || rule_ids.iter().any(|id| BLOCKING_RULE_IDS.contains(&id.as_str()))
+}
+
+/// Fail when a file with text in it produced no audit entries at all, which
+/// almost always means the wrong file was opened. An empty file is fine.
+pub fn ensure_audit_entries(has_text: bool, entry_count: usize) -> Result<()> {
+    if entry_count == 0 && has_text {
+        anyhow::bail!(
+            "No ModSecurity audit entries found (no --boundary-A-- markers) — is this the right file?"
+        );
+    }
+    Ok(())
+}
+
+/// Pre-flight check before the UI starts: say plainly whether a log path is
+/// missing, not readable by this user, or a directory given by mistake. A
+/// directory is fine with `concurrent` (the storage directory).
+pub fn check_log_path(path: &Path, concurrent: bool) -> Result<()> {
+    let explain = |e: io::Error| match e.kind() {
+        io::ErrorKind::NotFound => anyhow::anyhow!("File '{}' does not exist", path.display()),
+        io::ErrorKind::PermissionDenied => anyhow::anyhow!(
+            "Permission denied reading '{}'. Audit logs are usually readable by root only: \
+             run with sudo, or get read access to the file (e.g. through its group)",
+            path.display()
+        ),
+        _ => anyhow::anyhow!("Cannot read file '{}': {}", path.display(), e),
+    };
+
+    let metadata = fs::metadata(path).map_err(explain)?;
+    if metadata.is_dir() {
+        if !concurrent {
+            anyhow::bail!(
+                "'{}' is a directory, not an audit log file (use --concurrent for a concurrent-mode storage directory)",
+                path.display()
+            );
+        }
+        fs::read_dir(path).map_err(explain)?;
+    } else {
+        fs::File::open(path).map_err(explain)?;
+    }
+    Ok(())
+}
+
+/// Share of non-text bytes (control characters, invalid UTF-8) from which a
+/// section body counts as binary
+const BINARY_THRESHOLD: f64 = 0.1;
+
+/// Whether a section body looks like binary data (an uploaded image, a
+/// compressed payload) rather than text: at least `BINARY_THRESHOLD` of its
+/// bytes are control characters or not valid UTF-8
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    if bytes.is_empty() {
+        return false;
+    }
+    let non_text: usize = bytes
+        .utf8_chunks()
+        .map(|chunk| {
+            let controls = chunk.valid().chars()
+                .filter(|&ch| ch.is_control() && !matches!(ch, '\t' | '\n' | '\r'))
+                .count();
+            controls + chunk.invalid().len()
+        })
+        .sum();
+    non_text as f64 >= bytes.len() as f64 * BINARY_THRESHOLD
+}
+
+/// Call `f` for every line of `reader` with the bytes consumed so far.
+/// Lines are decoded lossily, and both `\r\n` and bare `\r` count as line
+/// breaks, so boundary detection, field extraction and `raw_content` never
+/// see stray carriage returns. Lines that were not valid UTF-8 also come
+/// with their original bytes.
+fn for_each_line<R, F>(mut reader: R, mut f: F) -> Result<()>
+where
+    R: io::BufRead,
+    F: FnMut(&str, Option<&[u8]>, usize) -> Result<()>,
+{
+    let mut buf = Vec::new();
+    let mut bytes_read = 0;
+    loop {
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        bytes_read += n;
+        let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let text = String::from_utf8_lossy(line);
+        // Replacement only ever swaps invalid bytes, so the parts line up
+        let lossy = matches!(text, Cow::Owned(_));
+        for (part, raw) in text.split('\r').zip(line.split(|&b| b == b'\r')) {
+            f(part, lossy.then_some(raw), bytes_read)?;
+        }
+    }
+}
+
+/// Accumulates lines into entries, emitting one whenever the boundary ID
+/// changes (and the last one on `finish`)
+#[derive(Default)]
+struct EntrySplitter {
+    current_id: Option<String>,
+    closed: bool, // The current entry reached its Z boundary
+    accumulated_content: String,
+    accumulated_bytes: Option<Vec<u8>>, // Started at the entry's first invalid UTF-8 line
+    stray_bytes: usize, // Non-blank lines outside any entry, see `AuditLogParser::stray_bytes`
+}
+
+impl EntrySplitter {
+    fn append(&mut self, line: &str, raw: Option<&[u8]>) {
+        if raw.is_some() && self.accumulated_bytes.is_none() {
+            // Everything so far decoded cleanly, so its text is its bytes
+            self.accumulated_bytes = Some(self.accumulated_content.as_bytes().to_vec());
+        }
+        if let Some(bytes) = &mut self.accumulated_bytes {
+            bytes.extend_from_slice(raw.unwrap_or(line.as_bytes()));
+            bytes.push(b'\n');
+        }
+        self.accumulated_content.push_str(line);
+        self.accumulated_content.push('\n');
+    }
+
+    fn push_line(&mut self, parser: &AuditLogParser, line: &str, raw: Option<&[u8]>) -> Option<AuditEntry> {
+        let mut finished = None;
+        if let Some(caps) = parser.boundary_re.captures(line) {
+            let id = caps.get(1).unwrap().as_str().to_string();
+            let opens_transaction = &caps[2] == "A";
+
+            // If this is a different ID than current, or a new transaction
+            // reusing it (see `is_collision`), save the previous entry
+            if let Some(prev_id) = self.current_id.take() {
+                if id != prev_id || opens_transaction {
+                    let content = std::mem::take(&mut self.accumulated_content);
+                    let bytes = self.accumulated_bytes.take();
+                    if !content.trim().is_empty() {
+                        finished = parser.create_entry(prev_id, content).map(|mut entry| {
+                            entry.raw_bytes = bytes;
+                            entry
+                        });
+                    }
+                }
+            }
+
+            // Track this ID
+            self.current_id = Some(id);
+            self.closed = &caps[2] == "Z";
+            self.append(line, raw);
+        } else if self.current_id.is_some() && !self.closed {
+            // Accumulate content for current entry
+            self.append(line, raw);
+        } else if !line.trim().is_empty() {
+            // Before the first boundary or after a Z: not part of any entry.
+            // Blank lines are how ModSecurity separates entries.
+            self.stray_bytes += raw.map_or(line.len(), <[u8]>::len) + 1;
+        }
+        finished
+    }
+
+    fn finish(self, parser: &AuditLogParser) -> Option<AuditEntry> {
+        let id = self.current_id?;
+        if self.accumulated_content.trim().is_empty() {
+            return None;
+        }
+        // Only the last entry can be cut short: a log written concurrently
+        // may end before its Z boundary. Earlier parts of an interleaved
+        // chain legitimately lack one, as another part follows later.
+        parser.create_entry(id, self.accumulated_content).map(|mut entry| {
+            entry.incomplete = parser.section(&entry.raw_content, 'Z').is_none();
+            entry.raw_bytes = self.accumulated_bytes;
+            entry
+        })
+    }
+}
+
+/// Collects entries by audit ID. With a limit, only the `max_groups` groups
+/// with the newest first entry are kept; older ones are dropped as soon as
+/// the limit is exceeded, so memory stays bounded on huge logs.
+struct GroupCollector {
+    groups: HashMap<String, Vec<AuditEntry>>,
+    oldest: BinaryHeap<Reverse<(DateTime<Utc>, String)>>,
+    max_groups: Option<usize>,
+    entries_seen: usize,
+    groups_seen: usize,
+}
+
+impl GroupCollector {
+    fn new(max_groups: Option<usize>) -> Self {
+        Self {
+            groups: HashMap::new(),
+            oldest: BinaryHeap::new(),
+            max_groups,
+            entries_seen: 0,
+            groups_seen: 0,
+        }
+    }
+
+    fn push(&mut self, entry: AuditEntry) {
+        self.entries_seen += 1;
+        if let Some(group) = self.groups.get_mut(&entry.audit_id) {
+            group.push(entry);
+            return;
+        }
+
+        self.groups_seen += 1;
+        if self.max_groups.is_some() {
+            self.oldest.push(Reverse((entry.timestamp, entry.audit_id.clone())));
+        }
+        self.groups.insert(entry.audit_id.clone(), vec![entry]);
+
+        if let Some(max) = self.max_groups {
+            while self.groups.len() > max {
+                match self.oldest.pop() {
+                    Some(Reverse((_, id))) => {
+                        self.groups.remove(&id);
+                    }
+                    None => break,
+                }
+            }
         }
     }
+
+    fn entries_seen(&self) -> usize {
+        self.entries_seen
+    }
+
+    fn groups_seen(&self) -> usize {
+        self.groups_seen
+    }
+
+    fn into_map(self) -> HashMap<String, Vec<AuditEntry>> {
+        self.groups
+    }
 }
 
 pub struct AuditLogParser {
+    boundary_re: Regex,
     timestamp_re: Regex,
     rule_id_re: Regex,
+    data_re: Regex,
     host_re: Regex,
     client_ip_re: Regex,
     file_re: Regex,
     http_status_re: Regex,
+    content_length_re: Regex,
+    engine_mode_re: Regex,
+    action_re: Regex,
+    concurrent_index_re: Regex,
+    max_groups: Option<usize>,
+    split_on_ip: bool,
+    trusted_ip_headers: Vec<String>,
+    a_section_ip_field: Option<usize>,
+    stray_bytes: Cell<usize>,
+}
+
+impl Default for AuditLogParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AuditLogParser {
     pub fn new() -> Self {
         Self {
+            boundary_re: Regex::new(r"--([a-zA-Z0-9]+)-([A-Z])--").unwrap(),
             timestamp_re: Regex::new(r"\[(\d{2}/[A-Za-z]{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4})\]")
                 .unwrap(),
             rule_id_re: Regex::new(r#"\[id "(\d+)"\]"#).unwrap(),
+            // Matched payload; quotes inside are escaped as \"
+            data_re: Regex::new(r#"\[data "((?:[^"\\]|\\.)*)"\]"#).unwrap(),
             host_re: Regex::new(r"(?i)Host:\s*([^\r\n]+)").unwrap(),
             // Parse the A section line: [timestamp] audit-id source-ip source-port dest-ip dest-port
             // We'll extract this in create_entry by splitting the line
@@ -95,137 +844,533 @@ impl AuditLogParser {
             file_re: Regex::new(r#"\[file "([^"]+)"\]"#).unwrap(),
             // Extract HTTP status code from F section: HTTP/1.1 200 OK
             http_status_re: Regex::new(r"HTTP/\d\.\d\s+(\d{3})").unwrap(),
+            content_length_re: Regex::new(r"(?im)^Content-Length:\s*(\d+)").unwrap(),
+            // H trailer: Engine-Mode: "ENABLED" (or "DETECTION_ONLY")
+            engine_mode_re: Regex::new(r#"(?m)^Engine-Mode:\s*"?([A-Za-z_]+)"?"#).unwrap(),
+            // H trailer: Action: Intercepted (phase 2)
+            action_re: Regex::new(r"(?m)^Action:[ \t]*(\S[^\r\n]*?)\s*$").unwrap(),
+            // Concurrent index lines end with: /relative/path offset size md5:hash
+            concurrent_index_re: Regex::new(r"\s(/\S+)\s+\d+\s+\d+\s+\S+\s*$").unwrap(),
+            max_groups: None,
+            split_on_ip: false,
+            trusted_ip_headers: Vec::new(),
+            a_section_ip_field: None,
+            stray_bytes: Cell::new(0),
+        }
+    }
+
+    /// Every regex the parser uses, by name (`--diagnostics`)
+    pub fn patterns(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("boundary", self.boundary_re.as_str()),
+            ("timestamp", self.timestamp_re.as_str()),
+            ("rule_id", self.rule_id_re.as_str()),
+            ("data", self.data_re.as_str()),
+            ("host", self.host_re.as_str()),
+            ("client_ip", self.client_ip_re.as_str()),
+            ("file", self.file_re.as_str()),
+            ("http_status", self.http_status_re.as_str()),
+            ("content_length", self.content_length_re.as_str()),
+            ("engine_mode", self.engine_mode_re.as_str()),
+            ("action", self.action_re.as_str()),
+            ("concurrent_index", self.concurrent_index_re.as_str()),
+        ]
+    }
+
+    /// Keep only the `max_groups` most recent groups (`--max-entries`)
+    pub fn with_max_groups(mut self, max_groups: Option<usize>) -> Self {
+        self.max_groups = max_groups;
+        self
+    }
+
+    /// The `--max-entries` limit this parser keeps to
+    pub fn max_groups(&self) -> Option<usize> {
+        self.max_groups
+    }
+
+    /// Whether collided groups come out split per client IP
+    pub fn split_on_ip(&self) -> bool {
+        self.split_on_ip
+    }
+
+    /// Split collided groups (`AuditGroup::collided`) into one group per
+    /// client IP (`--split-on-ip`), see `split_by_client_ip`
+    pub fn with_split_on_ip(mut self, split_on_ip: bool) -> Self {
+        self.split_on_ip = split_on_ip;
+        self
+    }
+
+    /// Take the client IP from the first of these request headers that holds
+    /// a valid address (`--trust-xff`), e.g. `DEFAULT_TRUSTED_IP_HEADERS`.
+    /// Empty (the default) keeps the section A address.
+    pub fn with_trusted_ip_headers(mut self, headers: Vec<String>) -> Self {
+        self.trusted_ip_headers = headers;
+        self
+    }
+
+    /// Read the client IP from this field of the A line after the timestamp
+    /// (`--a-section-format`, see `parse_a_section_format`) instead of
+    /// `client_ip_re`. A field that is not an address still falls back to the
+    /// first one on the line.
+    pub fn with_a_section_ip_field(mut self, field: Option<usize>) -> Self {
+        self.a_section_ip_field = field;
+        self
+    }
+
+    /// Bytes of non-blank lines this parser found outside any entry so far:
+    /// before the first boundary of a file, or after an entry's Z boundary
+    /// and before the next boundary (comments, other logs written into the
+    /// same file). Such lines are skipped.
+    pub fn stray_bytes(&self) -> usize {
+        self.stray_bytes.get()
+    }
+
+    /// The client address from the trusted headers of section B, if any: the
+    /// headers are tried in order, and in a list (`X-Forwarded-For: client,
+    /// proxy`) the leftmost valid address counts. Malformed values are skipped.
+    fn trusted_header_ip(&self, content: &str) -> Option<String> {
+        if self.trusted_ip_headers.is_empty() {
+            return None;
         }
+        let headers = parse_headers(self.section(content, 'B')?);
+        self.trusted_ip_headers.iter().find_map(|wanted| {
+            headers.iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case(wanted))
+                .flat_map(|(_, value)| value.split(','))
+                .find_map(|token| normalize_ip(token.trim()))
+        })
     }
 
-    pub fn parse_log_file<P: AsRef<Path>>(
+    /// Parse one or more serial audit log files into a single set of groups.
+    /// Rotated files can be given together (`modsec_audit.log.1
+    /// modsec_audit.log`): a transaction cut in two by the rotation is
+    /// merged back by audit ID.
+    pub fn parse_log_files<P: AsRef<Path>>(
         &self,
-        path: P,
+        paths: &[P],
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> Result<Vec<AuditGroup>> {
         let loading = LoadingScreen::new();
 
-        // Step 1: Read file
-        terminal.draw(|f| loading.draw(f, 1, "Reading audit log file", 0.0, "Reading file from disk..."))?;
-        let bytes = fs::read(path.as_ref())
-            .context("Failed to read audit log file")?;
-        let file_size_mb = bytes.len() as f64 / 1_000_000.0;
-        let file_size_msg = format!("File size: {:.2} MB ({} bytes)", file_size_mb, bytes.len());
-        terminal.draw(|f| loading.draw(f, 1, "Reading audit log file", 0.2, &file_size_msg))?;
-
-        // Step 2: Convert to UTF-8
-        terminal.draw(|f| loading.draw(f, 2, "Converting to UTF-8 text", 0.2, "Processing file contents..."))?;
-        let content = String::from_utf8_lossy(&bytes).to_string();
-        let line_count = content.lines().count();
-        let lines_msg = format!("Lines processed: {}", line_count);
-        terminal.draw(|f| loading.draw(f, 2, "Converting to UTF-8 text", 0.4, &lines_msg))?;
-
-        // Step 3: Parse entries
-        terminal.draw(|f| loading.draw(f, 3, "Parsing audit entries", 0.4, "Extracting audit log entries..."))?;
-        let entries = self.parse_entries_with_loading(&content, terminal, &loading)?;
-        let entries_msg = format!("Entries found: {}", entries.len());
-        terminal.draw(|f| loading.draw(f, 3, "Parsing audit entries", 0.6, &entries_msg))?;
+        // Step 1: Open files
+        terminal.draw(|f| loading.draw(f, 1, "Reading audit log file", 0.0, "Opening file..."))?;
+        let mut files = Vec::new();
+        for path in paths {
+            files.push(fs::File::open(path.as_ref()).context("Failed to read audit log file")?);
+        }
+        let file_size: usize = files.iter()
+            .map(|file| file.metadata().map(|m| m.len() as usize).unwrap_or(0))
+            .sum();
+        let file_size_mb = file_size as f64 / 1_000_000.0;
+        let file_size_msg = format!("File size: {:.2} MB ({} bytes)", file_size_mb, file_size);
+        terminal.draw(|f| loading.draw(f, 1, "Reading audit log file", 0.05, &file_size_msg))?;
 
-        // Step 4: Group entries
-        terminal.draw(|f| loading.draw(f, 4, "Grouping entries by audit ID", 0.6, "Creating audit groups..."))?;
-        let mut groups: HashMap<String, Vec<AuditEntry>> = HashMap::new();
-        let total_entries = entries.len();
-        for entry in entries {
-            groups.entry(entry.audit_id.clone())
-                .or_insert_with(Vec::new)
-                .push(entry);
+        // Step 2: Stream the files, splitting them into entries and collecting
+        // them by audit ID as we go (only the newest groups with --max-entries)
+        terminal.draw(|f| loading.draw(f, 2, "Parsing audit entries", 0.05, "Scanning log file..."))?;
+        let mut collector = GroupCollector::new(self.max_groups);
+        let mut has_text = false;
+        let mut bytes_before = 0;
+        let mut last_draw = Instant::now();
+        for file in files {
+            let size = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+            let found_before = collector.entries_seen();
+            has_text |= self.stream_entries(
+                io::BufReader::new(file),
+                |entry| collector.push(entry),
+                |bytes_read, found| {
+                    if last_draw.elapsed() < REDRAW_INTERVAL {
+                        return Ok(());
+                    }
+                    last_draw = Instant::now();
+                    let bytes_read = bytes_before + bytes_read;
+                    let progress = band_progress(0.05, 0.6, bytes_read as f64 / file_size.max(1) as f64);
+                    let msg = format!(
+                        "Found {} entries so far ({:.1} / {:.1} MB)...",
+                        found_before + found,
+                        bytes_read as f64 / 1_000_000.0,
+                        file_size_mb
+                    );
+                    terminal.draw(|f| loading.draw(f, 2, "Parsing audit entries", progress, &msg))?;
+                    Ok(())
+                },
+            )
+            .context("Failed to read audit log file")?;
+            bytes_before += size;
         }
+        let total_entries = collector.entries_seen();
+        ensure_audit_entries(has_text, total_entries)
+            .with_context(|| format!("Cannot load {}", display_paths(paths)))?;
+        let entries_msg = format!("Entries found: {}", total_entries);
+        terminal.draw(|f| loading.draw(f, 2, "Parsing audit entries", 0.6, &entries_msg))?;
+
+        // Step 3: Group entries
+        terminal.draw(|f| loading.draw(f, 3, "Grouping entries by audit ID", 0.6, "Creating audit groups..."))?;
+        let groups_seen = collector.groups_seen();
+        let groups = collector.into_map();
         let group_count = groups.len();
         let groups_msg = format!("Unique audit groups: {}", group_count);
-        terminal.draw(|f| loading.draw(f, 4, "Grouping entries by audit ID", 0.8, &groups_msg))?;
+        terminal.draw(|f| loading.draw(f, 3, "Grouping entries by audit ID", 0.8, &groups_msg))?;
+
+        // Step 4: Build groups
+        terminal.draw(|f| loading.draw(f, 4, "Building audit groups", 0.8, "Summarizing entries..."))?;
+        let mut audit_groups: Vec<AuditGroup> = groups.into_values().flat_map(|entries| self.groups_for(entries)).collect();
+        terminal.draw(|f| loading.draw(f, 4, "Building audit groups", 0.9, &groups_msg))?;
 
         // Step 5: Sort
-        terminal.draw(|f| loading.draw(f, 5, "Sorting by timestamp", 0.8, "Sorting groups (most recent first)..."))?;
-        let mut audit_groups: Vec<AuditGroup> = groups
-            .into_iter()
-            .map(|(_, entries)| AuditGroup::from_entries(entries))
-            .collect();
-        audit_groups.sort_by(|a, b| b.first_timestamp.cmp(&a.first_timestamp));
+        terminal.draw(|f| loading.draw(f, 5, "Sorting by timestamp", 0.9, "Sorting groups (most recent first)..."))?;
+        audit_groups.sort_by_key(|g| Reverse(g.first_timestamp));
         terminal.draw(|f| loading.draw(f, 5, "Sorting by timestamp", 1.0, "Complete!"))?;
 
         // Show summary
-        terminal.draw(|f| loading.draw_summary(f, total_entries, group_count, file_size_mb))?;
-        std::thread::sleep(std::time::Duration::from_millis(800));
+        terminal.draw(|f| loading.draw_summary(f, total_entries, group_count, groups_seen, file_size_mb))?;
+        std::thread::sleep(Duration::from_millis(800));
 
         Ok(audit_groups)
     }
 
-    fn parse_entries_with_loading(
+    /// Parse a concurrent-mode audit log (`SecAuditLogType Concurrent`).
+    /// Each path is either the index file written to `SecAuditLog` or the
+    /// `SecAuditLogStorageDir` directory holding one file per transaction.
+    pub fn parse_concurrent<P: AsRef<Path>>(
         &self,
-        content: &str,
+        paths: &[P],
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-        loading: &LoadingScreen,
-    ) -> Result<Vec<AuditEntry>> {
-        let mut entries = Vec::new();
-        let boundary_re = Regex::new(r"--([a-zA-Z0-9]+)-([A-Z])--").unwrap();
-        let mut current_id: Option<String> = None;
-        let mut accumulated_content = String::new();
-        let mut line_num = 0;
-        let total_lines = content.lines().count() as u64;
+    ) -> Result<Vec<AuditGroup>> {
+        let loading = LoadingScreen::new();
 
-        for line in content.lines() {
-            line_num += 1;
+        // Step 1: Resolve transaction files from the index
+        terminal.draw(|f| loading.draw(f, 1, "Reading concurrent index", 0.0, "Locating transaction files..."))?;
+        let mut files = Vec::new();
+        for path in paths {
+            files.extend(self.concurrent_part_files(path.as_ref())?);
+        }
+        let files_msg = format!("Transaction files: {}", files.len());
+        terminal.draw(|f| loading.draw(f, 1, "Reading concurrent index", 0.2, &files_msg))?;
 
-            // Update progress every 1000 lines
-            if line_num % 1000 == 0 {
-                let progress = 0.4 + (line_num as f64 / total_lines as f64) * 0.2;
-                let msg = if entries.len() > 0 {
-                    format!("Found {} entries so far...", entries.len())
-                } else {
-                    "Scanning log file...".to_string()
-                };
-                terminal.draw(|f| loading.draw(f, 3, "Parsing audit entries", progress, &msg))?;
+        // Steps 2-3: Read and parse each transaction file
+        let mut collector = GroupCollector::new(self.max_groups);
+        let mut total_bytes = 0;
+        let mut last_draw = Instant::now();
+        for (i, file) in files.iter().enumerate() {
+            if last_draw.elapsed() >= REDRAW_INTERVAL {
+                last_draw = Instant::now();
+                let progress = band_progress(0.2, 0.6, i as f64 / files.len() as f64);
+                let msg = format!("File {}/{} ({} entries so far)", i + 1, files.len(), collector.entries_seen());
+                terminal.draw(|f| loading.draw(f, 3, "Parsing transaction files", progress, &msg))?;
+            }
+            let (file_entries, bytes) = self.parse_file_entries(file)?;
+            total_bytes += bytes;
+            for entry in file_entries {
+                collector.push(entry);
             }
+        }
+        let total_entries = collector.entries_seen();
+        let entries_msg = format!("Entries found: {}", total_entries);
+        terminal.draw(|f| loading.draw(f, 3, "Parsing transaction files", 0.6, &entries_msg))?;
 
-            if let Some(caps) = boundary_re.captures(line) {
-                let id = caps.get(1).unwrap().as_str().to_string();
+        // Step 4: Group entries
+        terminal.draw(|f| loading.draw(f, 4, "Grouping entries by audit ID", 0.6, "Creating audit groups..."))?;
+        let groups_seen = collector.groups_seen();
+        let groups = collector.into_map();
+        let group_count = groups.len();
+        let groups_msg = format!("Unique audit groups: {}", group_count);
+        terminal.draw(|f| loading.draw(f, 4, "Grouping entries by audit ID", 0.8, &groups_msg))?;
+
+        // Step 5: Sort
+        terminal.draw(|f| loading.draw(f, 5, "Sorting by timestamp", 0.8, "Sorting groups (most recent first)..."))?;
+        let audit_groups = self.build_groups(groups);
+        terminal.draw(|f| loading.draw(f, 5, "Sorting by timestamp", 1.0, "Complete!"))?;
+
+        let file_size_mb = total_bytes as f64 / 1_000_000.0;
+        terminal.draw(|f| loading.draw_summary(f, total_entries, group_count, groups_seen, file_size_mb))?;
+        std::thread::sleep(Duration::from_millis(800));
+
+        Ok(audit_groups)
+    }
+
+    /// Resolve the per-transaction files of a concurrent-mode log. If `path`
+    /// is an index file (or a directory containing one named `index`), the
+    /// paths it references are resolved relative to its directory; otherwise
+    /// the directory is walked recursively.
+    pub fn concurrent_part_files(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        let (index, base) = if path.is_dir() {
+            let index = path.join("index");
+            (index.is_file().then_some(index), path.to_path_buf())
+        } else {
+            let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            (Some(path.to_path_buf()), base)
+        };
 
-                // If this is a different ID than current, save the previous entry
-                if let Some(ref prev_id) = current_id {
-                    if &id != prev_id {
-                        // Save previous entry
-                        if !accumulated_content.trim().is_empty() {
-                            if let Some(entry) = self.create_entry(prev_id.clone(), accumulated_content.clone()) {
-                                entries.push(entry);
+        let files = match index {
+            Some(index) => {
+                let content = fs::read_to_string(&index)
+                    .with_context(|| format!("Failed to read concurrent index {}", index.display()))?;
+                content
+                    .lines()
+                    .filter_map(|line| self.concurrent_index_re.captures(line))
+                    .filter_map(|c| c.get(1))
+                    .map(|m| base.join(m.as_str().trim_start_matches('/')))
+                    .filter(|p| p.is_file())
+                    .collect()
+            }
+            None => {
+                let mut files = Vec::new();
+                collect_files(&base, &mut files)?;
+                files.sort();
+                files
+            }
+        };
+
+        if files.is_empty() {
+            anyhow::bail!("No transaction files found for concurrent log {}", path.display());
+        }
+        Ok(files)
+    }
+
+    /// Parse a list of files (serial logs or concurrent-mode transaction
+    /// files) without a terminal, grouping entries across all of them
+    pub fn parse_files(&self, files: &[PathBuf]) -> Result<Vec<AuditGroup>> {
+        let mut entries = Vec::new();
+        for file in files {
+            entries.extend(self.parse_file_entries(file)?.0);
+        }
+        Ok(self.group_entries(entries))
+    }
+
+    /// Parse `files` in order and hand each transaction to `on_group` as
+    /// soon as its Z boundary was read, without holding the whole log
+    /// (`--format ndjson`). Groups come out in log order; parts still open at
+    /// the end (a log being written, or a cut-off rotation) follow, oldest
+    /// first. A transaction reusing an audit ID after its Z comes out as a
+    /// group of its own rather than being flagged `collided`.
+    pub fn stream_groups<F>(&self, files: &[PathBuf], mut on_group: F) -> Result<()>
+    where
+        F: FnMut(AuditGroup) -> Result<()>,
+    {
+        let mut open: HashMap<String, Vec<AuditEntry>> = HashMap::new();
+        // The consumer's error, which stops the read at the next progress call
+        let failed = Cell::new(None);
+        let stop = Cell::new(false);
+        for file in files {
+            let read_error = || format!("Failed to read audit log file {}", file.display());
+            let reader = io::BufReader::new(fs::File::open(file).with_context(read_error)?);
+            let read = self.stream_entries(
+                reader,
+                |entry| {
+                    if stop.get() {
+                        return;
+                    }
+                    let complete = self.section(&entry.raw_content, 'Z').is_some();
+                    let id = entry.audit_id.clone();
+                    open.entry(id.clone()).or_default().push(entry);
+                    if complete {
+                        let entries = open.remove(&id).unwrap_or_default();
+                        for group in self.groups_for(entries) {
+                            if let Err(e) = on_group(group) {
+                                failed.set(Some(e));
+                                stop.set(true);
+                                return;
                             }
                         }
-                        // Reset for new entry
-                        accumulated_content.clear();
                     }
-                }
-
-                // Track this ID
-                current_id = Some(id);
-                accumulated_content.push_str(&format!("{}\n", line));
-            } else if current_id.is_some() {
-                // Accumulate content for current entry
-                accumulated_content.push_str(line);
-                accumulated_content.push('\n');
+                },
+                // Stop reading once the consumer failed (e.g. a closed pipe)
+                |_, _| if stop.get() { anyhow::bail!("stopped") } else { Ok(()) },
+            );
+            if let Some(e) = failed.take() {
+                return Err(e);
             }
+            read.with_context(read_error)?;
         }
 
-        // Save the last entry
-        if let Some(id) = current_id {
-            if !accumulated_content.trim().is_empty() {
-                if let Some(entry) = self.create_entry(id, accumulated_content) {
-                    entries.push(entry);
+        let mut rest: Vec<AuditGroup> = open.into_values().flat_map(|entries| self.groups_for(entries)).collect();
+        rest.sort_by_key(|g| g.first_timestamp);
+        rest.into_iter().try_for_each(on_group)
+    }
+
+    /// Parse all entries of a single file, returning them with the file size
+    fn parse_file_entries(&self, path: &Path) -> Result<(Vec<AuditEntry>, usize)> {
+        let read_error = || format!("Failed to read transaction file {}", path.display());
+        let file = fs::File::open(path).with_context(read_error)?;
+        let size = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+        let mut entries = Vec::new();
+        self.stream_entries(io::BufReader::new(file), |entry| entries.push(entry), |_, _| Ok(()))
+            .with_context(read_error)?;
+        Ok((entries, size))
+    }
+
+    /// Start of the last entry of a serial log that ends at `end`: the first
+    /// boundary line of the trailing run of one audit ID. Lines appended
+    /// after `end` may still belong to that entry, so an append is parsed
+    /// from here (`parse_file_from`) and merged with `merge_entries`. Only the
+    /// tail of the file is read.
+    pub fn last_entry_offset(&self, path: &Path, end: u64) -> Result<u64> {
+        let mut file = fs::File::open(path)?;
+        let mut window: u64 = 64 * 1024;
+        loop {
+            let start = end.saturating_sub(window);
+            let mut bytes = Vec::new();
+            file.seek(SeekFrom::Start(start))?;
+            (&mut file).take(end - start).read_to_end(&mut bytes)?;
+
+            // Boundary lines as (offset, audit ID); a line cut by the window is skipped
+            let mut boundaries: Vec<(u64, String)> = Vec::new();
+            let mut offset = 0;
+            for line in bytes.split_inclusive(|&b| b == b'\n') {
+                let line_start = offset;
+                offset += line.len();
+                if line_start == 0 && start > 0 {
+                    continue;
+                }
+                let text = String::from_utf8_lossy(line);
+                if let Some(caps) = self.boundary_re.captures(text.trim_end()) {
+                    boundaries.push((start + line_start as u64, caps[1].to_string()));
+                }
+            }
+
+            if let Some((_, last_id)) = boundaries.last() {
+                let run = boundaries.iter().rposition(|(_, id)| id != last_id);
+                match run {
+                    Some(other) => return Ok(boundaries[other + 1].0),
+                    None if start == 0 => return Ok(boundaries[0].0),
+                    None => {}
                 }
+            } else if start == 0 {
+                return Ok(0);
             }
+            window *= 4;
         }
+    }
 
+    /// Parse a serial log from byte `offset` (the start of a line) to its end
+    pub fn parse_file_from(&self, path: &Path, offset: u64) -> Result<Vec<AuditEntry>> {
+        let read_error = || format!("Failed to read audit log file {}", path.display());
+        let mut file = fs::File::open(path).with_context(read_error)?;
+        file.seek(SeekFrom::Start(offset)).with_context(read_error)?;
+        let mut entries = Vec::new();
+        self.stream_entries(io::BufReader::new(file), |entry| entries.push(entry), |_, _| Ok(()))
+            .with_context(read_error)?;
         Ok(entries)
     }
 
+    /// Merge freshly parsed entries into existing groups by audit ID,
+    /// rebuilding only the groups they touch. A part already present is
+    /// skipped; a part that is the completed version of one cut off earlier
+    /// replaces it. Groups stay sorted most recent first, and only the newest
+    /// `max_groups` are kept if set.
+    pub fn merge_entries(&self, groups: &mut Vec<AuditGroup>, entries: Vec<AuditEntry>) {
+        let mut index: HashMap<String, usize> = groups.iter()
+            .enumerate()
+            .map(|(i, group)| (group.base_id.clone(), i))
+            .collect();
+        let mut touched = HashSet::new();
+        for entry in entries {
+            let Some(&i) = index.get(&entry.audit_id) else {
+                index.insert(entry.audit_id.clone(), groups.len());
+                groups.push(AuditGroup::from_entries(vec![entry]));
+                continue;
+            };
+            let parts = &mut groups[i].entries;
+            if parts.iter().any(|part| part.raw_content == entry.raw_content) {
+                continue;
+            }
+            parts.retain(|part| !entry.raw_content.starts_with(&part.raw_content));
+            parts.push(entry);
+            touched.insert(i);
+        }
+        for i in touched {
+            let parts = std::mem::take(&mut groups[i].entries);
+            groups[i] = AuditGroup::from_entries(parts);
+        }
+
+        groups.sort_by_key(|g| Reverse(g.first_timestamp));
+        if let Some(max) = self.max_groups {
+            groups.truncate(max);
+        }
+    }
+
+    /// Parse audit log content that is already in memory, without any
+    /// loading screen. Returns groups sorted most recent first, exactly as
+    /// `parse_log_file` would.
+    pub fn parse_str(&self, content: &str) -> Vec<AuditGroup> {
+        let mut collector = GroupCollector::new(self.max_groups);
+        // Reading from memory cannot fail and there is no progress to report
+        let _ = self.stream_entries(content.as_bytes(), |entry| collector.push(entry), |_, _| Ok(()));
+        self.build_groups(collector.into_map())
+    }
+
+    /// Group entries sharing an audit ID and sort the groups by timestamp
+    /// (most recent first), keeping only the newest `max_groups` if set
+    pub fn group_entries(&self, entries: Vec<AuditEntry>) -> Vec<AuditGroup> {
+        let mut collector = GroupCollector::new(self.max_groups);
+        for entry in entries {
+            collector.push(entry);
+        }
+        self.build_groups(collector.into_map())
+    }
+
+    fn build_groups(&self, groups: HashMap<String, Vec<AuditEntry>>) -> Vec<AuditGroup> {
+        let mut audit_groups: Vec<AuditGroup> = groups
+            .into_values()
+            .flat_map(|entries| self.groups_for(entries))
+            .collect();
+        audit_groups.sort_by_key(|g| Reverse(g.first_timestamp));
+        audit_groups
+    }
+
+    /// The group (or, with `--split-on-ip`, groups) for the entries of one
+    /// audit ID, in log order
+    fn groups_for(&self, entries: Vec<AuditEntry>) -> Vec<AuditGroup> {
+        if self.split_on_ip {
+            split_by_client_ip(entries)
+        } else {
+            vec![AuditGroup::from_entries(entries)]
+        }
+    }
+
+    /// Read `reader` line by line, splitting it into entries on boundary
+    /// changes and handing each to `on_entry`. `on_progress` is called every
+    /// 1000 lines with (bytes read, entries found). Returns whether the input
+    /// had any non-blank text (see `ensure_audit_entries`).
+    fn stream_entries<R, E, P>(&self, reader: R, mut on_entry: E, mut on_progress: P) -> Result<bool>
+    where
+        R: io::BufRead,
+        E: FnMut(AuditEntry),
+        P: FnMut(usize, usize) -> Result<()>,
+    {
+        let mut splitter = EntrySplitter::default();
+        let mut has_text = false;
+        let mut line_num = 0;
+        let mut found = 0;
+
+        for_each_line(reader, |line, raw, bytes_read| {
+            line_num += 1;
+            has_text |= !line.trim().is_empty();
+            if let Some(entry) = splitter.push_line(self, line, raw) {
+                found += 1;
+                on_entry(entry);
+            }
+
+            // Update progress every 1000 lines
+            if line_num % 1000 == 0 {
+                on_progress(bytes_read, found)?;
+            }
+            Ok(())
+        })?;
+
+        self.stray_bytes.set(self.stray_bytes.get() + splitter.stray_bytes);
+        // Save the last entry
+        if let Some(entry) = splitter.finish(self) {
+            on_entry(entry);
+        }
+        Ok(has_text)
+    }
 
     fn create_entry(&self, audit_id: String, content: String) -> Option<AuditEntry> {
         // Parse timestamp
         let timestamp = self.parse_timestamp(&content)
-            .unwrap_or_else(|| Utc::now());
+            .unwrap_or_else(Utc::now);
 
         // Extract domain (trim to remove any \r or whitespace)
         let domain = self.host_re
@@ -234,12 +1379,30 @@ impl AuditLogParser {
             .map(|m| m.as_str().trim_end_matches('\r').trim().to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
-        // Extract client IP
-        let client_ip = self.client_ip_re
-            .captures(&content)
-            .and_then(|c| c.get(1))
-            .map(|m| m.as_str().to_string())
-            .unwrap_or_else(|| "0.0.0.0".to_string());
+        // Extract client IP, validated: an unusual A-section layout can put
+        // something else in that position, so fall back to the first token of
+        // the A line that is an address
+        let a_fields = || {
+            self.section(&content, 'A')
+                .and_then(|a| a.lines().next())
+                .map(|line| line.split_once(']').map_or(line, |(_, rest)| rest))
+                .map(str::split_whitespace)
+        };
+        let positioned = match self.a_section_ip_field {
+            Some(field) => a_fields().and_then(|mut fields| fields.nth(field)).and_then(normalize_ip),
+            None => self.client_ip_re
+                .captures(&content)
+                .and_then(|c| c.get(1))
+                .and_then(|m| normalize_ip(m.as_str())),
+        };
+        let client_ip = positioned
+            .or_else(|| a_fields().and_then(|mut fields| fields.find_map(normalize_ip)))
+            .unwrap_or_else(|| UNKNOWN_IP.to_string());
+        // Behind a proxy or CDN that address is the proxy's
+        let (client_ip, peer_ip) = match self.trusted_header_ip(&content) {
+            Some(real_ip) if real_ip != client_ip => (real_ip, Some(client_ip)),
+            _ => (client_ip, None),
+        };
 
         // Extract rule IDs
         let rule_ids: Vec<String> = self.rule_id_re
@@ -247,17 +1410,59 @@ impl AuditLogParser {
             .filter_map(|c| c.get(1).map(|m| m.as_str().to_string()))
             .collect();
 
+        // Extract matched data, keeping only the non-empty payloads
+        let matched_data: Vec<String> = self.data_re
+            .captures_iter(&content)
+            .filter_map(|c| c.get(1))
+            .map(|m| m.as_str().replace("\\\"", "\"").replace("\\\\", "\\"))
+            .filter(|data| !data.is_empty())
+            .collect();
+
         // Extract file path
         let file_path = self.file_re
             .captures(&content)
             .and_then(|c| c.get(1))
             .map(|m| m.as_str().to_string());
 
-        // Extract HTTP status code from F section
-        let http_status = self.http_status_re
+        // Extract HTTP status code from F section, telling a missing section
+        // from a status line the pattern does not cover
+        let http_status = match self.http_status_re
             .captures(&content)
             .and_then(|c| c.get(1))
-            .and_then(|m| m.as_str().parse::<u16>().ok());
+            .and_then(|m| m.as_str().parse::<u16>().ok())
+        {
+            Some(code) => HttpStatus::Code(code),
+            None if self.section(&content, 'F').is_some() => HttpStatus::Unparsed,
+            None => HttpStatus::Missing,
+        };
+
+        // Request target from the first line of section B: `GET /path HTTP/1.1`
+        let request_uri = self.section(&content, 'B')
+            .and_then(|headers| headers.lines().next())
+            .and_then(|line| line.split_whitespace().nth(1))
+            .map(|uri| uri.to_string());
+
+        // What ModSecurity did: the H trailer's Action line, else what the
+        // rule messages say (a denial, or warnings only). Only the trailer
+        // counts; the client controls the request headers and body.
+        let trailer = self.section(&content, 'H').unwrap_or_default();
+        let action = self.action_re
+            .captures(trailer)
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().to_string())
+            .or_else(|| trailer.contains("Access denied with").then(|| "Intercepted".to_string()))
+            .or_else(|| trailer.contains("Message: Warning.").then(|| "Warning".to_string()));
+        let intercepted = action.as_deref().is_some_and(is_intercept);
+
+        let engine_mode = self.engine_mode_re
+            .captures(trailer)
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().to_uppercase());
+
+        // Body sizes: declared Content-Length in the headers section, falling
+        // back to the measured body section (chunked or missing header)
+        let request_size = self.body_size(&content, 'B', 'C');
+        let response_size = self.body_size(&content, 'F', 'E');
 
         Some(AuditEntry {
             audit_id,
@@ -265,13 +1470,48 @@ impl AuditLogParser {
             domain,
             rule_ids,
             client_ip,
+            peer_ip,
             http_status,
+            matched_data,
+            raw_bytes: None,
             raw_content: content,
             file_path,
+            request_uri,
+            request_size,
+            response_size,
+            incomplete: false,
+            intercepted,
+            action,
+            engine_mode,
         })
     }
 
-    fn parse_timestamp(&self, content: &str) -> Option<DateTime<Utc>> {
+    /// Text of one section (between its `--id-X--` line and the next boundary)
+    pub fn section<'a>(&self, content: &'a str, letter: char) -> Option<&'a str> {
+        let mut boundaries = self.boundary_re.captures_iter(content).peekable();
+        while let Some(caps) = boundaries.next() {
+            if caps.get(2).is_some_and(|m| m.as_str().starts_with(letter)) {
+                let start = caps.get(0).unwrap().end();
+                let end = boundaries.peek().map_or(content.len(), |next| next.get(0).unwrap().start());
+                return Some(content[start..end].trim_start_matches('\n'));
+            }
+        }
+        None
+    }
+
+    fn body_size(&self, content: &str, headers: char, body: char) -> Option<u64> {
+        self.section(content, headers)
+            .and_then(|h| self.content_length_re.captures(h))
+            .and_then(|c| c.get(1))
+            .and_then(|m| m.as_str().parse().ok())
+            .or_else(|| {
+                self.section(content, body)
+                    .map(|b| b.trim_end_matches('\n').len() as u64)
+            })
+    }
+
+    /// Parse the `[DD/MMM/YYYY:HH:MM:SS +ZZZZ]` timestamp from section A
+    pub fn parse_timestamp(&self, content: &str) -> Option<DateTime<Utc>> {
         self.timestamp_re
             .captures(content)
             .and_then(|c| c.get(1))
@@ -282,3 +1522,24 @@ impl AuditLogParser {
             })
     }
 }
+
+/// Paths for error messages: `a.log, b.log`
+fn display_paths<P: AsRef<Path>>(paths: &[P]) -> String {
+    paths.iter()
+        .map(|path| path.as_ref().display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Recursively collect regular files below `dir`
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read directory {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! Abuse contacts for client IPs from the RIRs' RDAP service.
+//!
+//! `rdap.org` redirects each query to the registry that holds the address
+//! (ARIN, RIPE, APNIC, ...). The answer describes the whole network the
+//! address belongs to, so it is cached by that range: one request covers
+//! every client IP of a hosting provider's block.
+
+use crate::ipapi::{http_client, is_private_or_reserved};
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use serde_json::Value;
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+/// Where `RdapCache` sends its queries, with the IP appended
+pub const RDAP_URL: &str = "https://rdap.org/ip/";
+
+/// The registry's record of the network an IP belongs to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AbuseContact {
+    pub network: Option<String>, // Network name, e.g. GOGL
+    pub handle: Option<String>,  // Registry handle, e.g. NET-8-8-8-0-2
+    pub range: Option<(IpAddr, IpAddr)>,
+    pub emails: Vec<String>, // Of the entities with the "abuse" role
+}
+
+impl AbuseContact {
+    /// Parse an RDAP IP network response. Abuse entities are often nested
+    /// in the registrant's, so every level is searched.
+    pub fn parse(text: &str) -> Result<Self> {
+        let json: Value = serde_json::from_str(text).context("RDAP response is not JSON")?;
+        let field = |name: &str| json.get(name).and_then(Value::as_str).map(str::to_string);
+        let address = |name: &str| json.get(name).and_then(Value::as_str).and_then(|a| a.parse().ok());
+        let mut emails = Vec::new();
+        abuse_emails(&json, &mut emails);
+        Ok(Self {
+            network: field("name"),
+            handle: field("handle"),
+            range: address("startAddress").zip(address("endAddress")),
+            emails,
+        })
+    }
+
+    /// "8.8.8.0 - 8.8.8.255", or empty without a range
+    pub fn range_text(&self) -> String {
+        self.range.map(|(start, end)| format!("{} - {}", start, end)).unwrap_or_default()
+    }
+}
+
+/// Collect the vCard emails of `entities` with the abuse role, recursively
+fn abuse_emails(object: &Value, emails: &mut Vec<String>) {
+    for entity in object.get("entities").and_then(Value::as_array).into_iter().flatten() {
+        let is_abuse = entity.get("roles").and_then(Value::as_array)
+            .is_some_and(|roles| roles.iter().any(|r| r.as_str() == Some("abuse")));
+        if is_abuse {
+            // ["vcard", [["email", {}, "text", "abuse@example.net"], ...]]
+            let properties = entity.pointer("/vcardArray/1").and_then(Value::as_array);
+            for property in properties.into_iter().flatten() {
+                if property.get(0).and_then(Value::as_str) == Some("email") {
+                    if let Some(email) = property.get(3).and_then(Value::as_str) {
+                        if !emails.iter().any(|e| e.eq_ignore_ascii_case(email)) {
+                            emails.push(email.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        abuse_emails(entity, emails);
+    }
+}
+
+pub struct RdapCache {
+    // Answers with the range they cover; (ip, ip) when the registry gave none
+    networks: Mutex<Vec<(IpAddr, IpAddr, AbuseContact)>>,
+    client: Client,
+    base_url: String,
+}
+
+impl Default for RdapCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RdapCache {
+    pub fn new() -> Self {
+        Self {
+            networks: Mutex::new(Vec::new()),
+            client: http_client(),
+            base_url: RDAP_URL.to_string(),
+        }
+    }
+
+    /// Query this URL (the IP is appended) instead of `RDAP_URL`
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.to_string();
+        self
+    }
+
+    /// Cached record of a network containing `ip`, without any request
+    pub fn cached(&self, ip: &str) -> Option<AbuseContact> {
+        let addr: IpAddr = ip.parse().ok()?;
+        self.networks.lock().unwrap().iter()
+            .find(|(start, end, _)| start.is_ipv4() == addr.is_ipv4() && (*start..=*end).contains(&addr))
+            .map(|(_, _, contact)| contact.clone())
+    }
+
+    /// The record of the network `ip` belongs to, from the cache or the
+    /// registry. Private and reserved addresses have none.
+    pub fn lookup(&self, ip: &str) -> Result<AbuseContact> {
+        let addr: IpAddr = ip.parse().map_err(|_| anyhow::anyhow!("not a valid IP address: {}", ip))?;
+        if is_private_or_reserved(ip) {
+            anyhow::bail!("{} is a private or reserved address", ip);
+        }
+        if let Some(cached) = self.cached(ip) {
+            return Ok(cached);
+        }
+
+        let text = self.client.get(format!("{}{}", self.base_url, ip))
+            .header("Accept", "application/rdap+json")
+            .send()?
+            .error_for_status()?
+            .text()?;
+        let contact = AbuseContact::parse(&text)?;
+        let (start, end) = contact.range
+            .filter(|(start, end)| (*start..=*end).contains(&addr))
+            .unwrap_or((addr, addr));
+        self.networks.lock().unwrap().push((start, end, contact.clone()));
+        Ok(contact)
+    }
+}
@@ -0,0 +1,64 @@
+//! Reverse DNS (PTR) lookups for client IPs, cached per IP.
+//!
+//! The system resolver has no timeout control, so each lookup runs on its own
+//! thread. Callers wait briefly for the answer; slower lookups keep running in
+//! the background and land in the cache for the next visit.
+
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long `lookup` blocks before giving up on a slow resolver
+const LOOKUP_TIMEOUT: Duration = Duration::from_millis(1500);
+
+pub struct ReverseDnsCache {
+    // None = resolved, but no PTR record (NXDOMAIN, resolver error)
+    cache: Arc<Mutex<HashMap<String, Option<String>>>>,
+    in_flight: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Default for ReverseDnsCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReverseDnsCache {
+    pub fn new() -> Self {
+        Self {
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// PTR name for `ip`, or `None` when there is none, the IP is invalid,
+    /// or the resolver did not answer within the timeout
+    pub fn lookup(&self, ip: &str) -> Option<String> {
+        if let Some(cached) = self.cache.lock().unwrap().get(ip) {
+            return cached.clone();
+        }
+        let addr: IpAddr = ip.parse().ok()?;
+
+        // A slow lookup for this IP is still running; don't start another
+        if !self.in_flight.lock().unwrap().insert(ip.to_string()) {
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let cache = Arc::clone(&self.cache);
+        let in_flight = Arc::clone(&self.in_flight);
+        let key = ip.to_string();
+        thread::spawn(move || {
+            let name = dns_lookup::lookup_addr(&addr).ok();
+            cache.lock().unwrap().insert(key.clone(), name.clone());
+            in_flight.lock().unwrap().remove(&key);
+            // The receiver is gone if the caller already timed out
+            let _ = tx.send(name);
+        });
+
+        rx.recv_timeout(LOOKUP_TIMEOUT).ok().flatten()
+    }
+}
@@ -1,83 +1,368 @@
-use crate::app::{App, AppView};
+use crate::app::{format_duration, group_categories, stats_pivot_value, App, AppView, Column, RATE_WINDOW};
 use crate::colors::ColorScheme;
+use crate::crs;
+use crate::parser::{action_badge, looks_binary, parse_headers, section_marker, split_header, AuditEntry, AuditGroup};
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Modifier, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, Wrap},
     Frame,
 };
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Smallest terminal the views are drawn in: the table's three bars plus a
+/// table with one row need 13 lines, and the bars' text is unreadable
+/// below 40 columns
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 13;
 
 // Detect color scheme once at startup
 static COLOR_SCHEME: OnceLock<ColorScheme> = OnceLock::new();
 
+/// Pick the color scheme before the first draw; `light` is `--light`.
+/// Without this, the first draw detects it from the environment alone.
+pub fn init_colors(light: bool) {
+    let _ = COLOR_SCHEME.set(ColorScheme::detect(light));
+}
+
 fn colors() -> &'static ColorScheme {
-    COLOR_SCHEME.get_or_init(|| ColorScheme::detect())
+    COLOR_SCHEME.get_or_init(|| ColorScheme::detect(false))
+}
+
+/// Vertical scrollbar along the right edge of `area`, for `total` rows of
+/// which `viewport` are visible starting at `offset`. Nothing is drawn when
+/// everything fits.
+fn draw_scrollbar(f: &mut Frame, area: Rect, total: usize, viewport: usize, offset: usize) {
+    if total <= viewport || area.height == 0 {
+        return;
+    }
+    // One position per possible offset, so the thumb reaches the bottom
+    // exactly when the last row is on screen
+    let positions = total - viewport + 1;
+    let mut state = ScrollbarState::new(positions)
+        .position(offset.min(positions - 1))
+        .viewport_content_length(viewport);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .style(Style::default().fg(colors().boundary));
+    f.render_stateful_widget(scrollbar, area, &mut state);
+}
+
+/// Minimum width that keeps a column readable, and the preferred width it
+/// grows to when there is room
+fn column_width_range(column: Column) -> (usize, usize) {
+    match column {
+        Column::AuditId => (12, 24),
+        Column::Timestamp => (16, 19),
+        Column::Domain => (15, 40),
+        Column::ClientIp => (15, 39), // IPv4: 15 chars, IPv6: up to 39 chars
+        Column::Country => (7, 7),    // "Country" header, values are 2-letter codes
+        Column::Status => (6, 6),     // "Status" header or "429 ✖"
+        Column::Action => (6, 6),     // "Action" header or "block"/"warn"
+        Column::Flags => (5, 5),      // "Flags" header or "PHM"
+        Column::RuleIds => (10, 20),
+        Column::Category => (8, 16), // "Category" header or "sqli, xss"
+    }
 }
 
 /// Calculate dynamic column widths based on available terminal width
-/// Ensures all columns are visible even on narrow terminals
-fn calculate_column_widths(table_width: u16) -> [Constraint; 6] {
-    // Account for borders (2 chars) and column separators (5 chars for 6 columns)
-    let available_width = table_width.saturating_sub(7) as usize;
-
-    // Minimum widths to keep columns readable
-    const MIN_AUDIT_ID: usize = 12;
-    const MIN_TIMESTAMP: usize = 16;
-    const MIN_DOMAIN: usize = 15;
-    const MIN_CLIENT_IP: usize = 15;  // IPv4: 15 chars, IPv6: up to 39 chars
-    const MIN_STATUS: usize = 6;      // "Status" header or "429"
-    const MIN_RULE_IDS: usize = 10;
-
-    let total_min = MIN_AUDIT_ID + MIN_TIMESTAMP + MIN_DOMAIN + MIN_CLIENT_IP + MIN_STATUS + MIN_RULE_IDS;
-
-    // If terminal is very narrow, use minimum widths with proportional allocation
-    if available_width <= total_min {
-        return [
-            Constraint::Length(MIN_AUDIT_ID as u16),
-            Constraint::Length(MIN_TIMESTAMP as u16),
-            Constraint::Length(MIN_DOMAIN as u16),
-            Constraint::Length(MIN_CLIENT_IP as u16),
-            Constraint::Length(MIN_STATUS as u16),
-            Constraint::Min(MIN_RULE_IDS as u16),
-        ];
-    }
-
-    // We have extra space - distribute intelligently
-    let extra_space = available_width - total_min;
-
-    // Preferred maximum widths when space allows
-    const PREF_AUDIT_ID: usize = 24;
-    const PREF_TIMESTAMP: usize = 19;
-    const PREF_DOMAIN: usize = 40;
-    const PREF_CLIENT_IP: usize = 39;  // Full IPv6 width
-    const PREF_STATUS: usize = 6;      // Status codes are always 3 digits
-    const PREF_RULE_IDS: usize = 20;
-
-    // Calculate how much we can grow each column
-    let audit_id_growth = (PREF_AUDIT_ID - MIN_AUDIT_ID).min(extra_space / 6);
-    let timestamp_growth = (PREF_TIMESTAMP - MIN_TIMESTAMP).min(extra_space / 6);
-    let domain_growth = (PREF_DOMAIN - MIN_DOMAIN).min(extra_space / 6);
-    let client_ip_growth = (PREF_CLIENT_IP - MIN_CLIENT_IP).min(extra_space / 6);
-    let status_growth = (PREF_STATUS - MIN_STATUS).min(extra_space / 6);
-    let rule_ids_growth = (PREF_RULE_IDS - MIN_RULE_IDS).min(extra_space / 6);
-
-    [
-        Constraint::Length((MIN_AUDIT_ID + audit_id_growth) as u16),
-        Constraint::Length((MIN_TIMESTAMP + timestamp_growth) as u16),
-        Constraint::Length((MIN_DOMAIN + domain_growth) as u16),
-        Constraint::Length((MIN_CLIENT_IP + client_ip_growth) as u16),
-        Constraint::Length((MIN_STATUS + status_growth) as u16),
-        Constraint::Min((MIN_RULE_IDS + rule_ids_growth) as u16),
-    ]
+/// Ensures all columns are visible even on narrow terminals. Columns in
+/// `overrides` (dragged by the user) keep their width.
+fn calculate_column_widths(
+    table_width: u16,
+    dense: bool,
+    columns: &[Column],
+    overrides: &HashMap<Column, u16>,
+) -> Vec<Constraint> {
+    // Account for borders (2 chars, none when dense) and one separator between columns
+    let borders = if dense { 0 } else { 2 };
+    let separators = columns.len().saturating_sub(1) as u16;
+    let available_width = table_width.saturating_sub(borders + separators) as usize;
+
+    let fixed: usize = columns.iter().filter_map(|c| overrides.get(c)).map(|&w| w as usize).sum();
+    let auto: Vec<Column> = columns.iter().copied().filter(|c| !overrides.contains_key(c)).collect();
+    let total_min: usize = auto.iter().map(|&c| column_width_range(c).0).sum();
+    // If terminal is very narrow, use minimum widths; otherwise each column
+    // grows towards its preferred width by an even share of the extra space
+    let share = available_width.saturating_sub(fixed + total_min) / auto.len().saturating_sub(1).max(1);
+
+    // The last column takes whatever is left over
+    let last = columns.len().saturating_sub(1);
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, &column)| {
+            let (min, pref) = column_width_range(column);
+            let width = match overrides.get(&column) {
+                // Never wider than leaves the other columns their minimum
+                Some(&width) => width.min(available_width.saturating_sub(fixed + total_min - width as usize) as u16),
+                None => (min + (pref - min).min(share)) as u16,
+            };
+            if i == last { Constraint::Min(width) } else { Constraint::Length(width) }
+        })
+        .collect()
+}
+
+/// Resolve column constraints into the actual cell widths the table will
+/// use (inside the borders, with ratatui's default 1-char column spacing)
+fn resolve_column_widths(constraints: &[Constraint], table_width: u16, dense: bool) -> Vec<usize> {
+    let borders = if dense { 0 } else { 2 };
+    let inner = Rect::new(0, 0, table_width.saturating_sub(borders), 1);
+    Layout::horizontal(constraints.iter().copied())
+        .spacing(1)
+        .split(inner)
+        .iter()
+        .map(|r| r.width as usize)
+        .collect()
+}
+
+/// Short description of an HTTP status class
+fn status_class_label(code: u16) -> Option<&'static str> {
+    match code {
+        100..=199 => Some("informational"),
+        200..=299 => Some("success"),
+        300..=399 => Some("redirect"),
+        400..=499 => Some("client error"),
+        500..=599 => Some("server error"),
+        _ => None,
+    }
+}
+
+/// Human-readable byte count (`512 B`, `1.5 KB`, `2.0 MB`)
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// Shorten `text` to `width` columns, marking hidden content with `…`
+fn ellipsize(text: &str, width: usize) -> String {
+    if width == 0 || text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut shortened: String = text.chars().take(width - 1).collect();
+    shortened.push('…');
+    shortened
 }
 
 pub fn draw(f: &mut Frame, app: &mut App) {
+    // Squeezed layouts garble rather than help; say what is needed instead
+    let area = f.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        // Nothing on screen to click
+        app.table_area = None;
+        draw_too_small(f);
+        return;
+    }
     match app.current_view {
         AppView::TableView => draw_table_view(f, app),
         AppView::DetailView => draw_detail_view(f, app),
+        AppView::DomainView => draw_domain_view(f, app),
+        AppView::StatsView => draw_stats_view(f, app),
+    }
+    if app.current_view == AppView::TableView && app.filter_picker.is_some() {
+        draw_filter_picker(f, app);
+    }
+    if app.show_legend {
+        draw_legend(f);
+    }
+    if app.debug {
+        draw_debug_overlay(f, app);
+    }
+}
+
+/// In place of every view while the terminal is below `MIN_WIDTH` x
+/// `MIN_HEIGHT`. The app keeps its state, so growing the window brings the
+/// view back as it was.
+fn draw_too_small(f: &mut Frame) {
+    let area = f.area();
+    let text = format!(
+        "Terminal too small ({}x{}, need ≥ {}x{})",
+        area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+    );
+    // Start about half the text's height above the middle; word wrapping
+    // may need more lines than that, so the area runs to the bottom
+    let lines = (Line::from(text.as_str()).width() as u16).div_ceil(area.width.max(1));
+    let top = area.height.saturating_sub(lines) / 2;
+    let message = Paragraph::new(text)
+        .style(Style::default().fg(colors().title).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(message, Rect { y: area.y + top, height: area.height - top, ..area });
+}
+
+/// Color legend (`?`): a sample in each color with what it stands for,
+/// taken from the active scheme so it matches what is on screen
+fn draw_legend(f: &mut Frame) {
+    let c = colors();
+    let legend = c.legend();
+    let sample_width = legend.iter()
+        .flat_map(|(_, items)| items)
+        .map(|item| Line::from(item.sample).width())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (heading, items) in &legend {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::styled(format!(" {}", heading), Style::default().fg(c.label).add_modifier(Modifier::BOLD)));
+        for item in items {
+            let style = if item.background {
+                Style::default().bg(item.color)
+            } else {
+                Style::default().fg(item.color).add_modifier(Modifier::BOLD)
+            };
+            let padding = " ".repeat(sample_width - Line::from(item.sample).width());
+            lines.push(Line::from(vec![
+                Span::raw("   "),
+                Span::styled(item.sample, style),
+                Span::raw(format!("{}  {} ", padding, item.meaning)),
+            ]));
+        }
     }
+    lines.push(Line::from(""));
+    lines.push(Line::styled(" Any key closes this legend", Style::default().fg(c.help_text)));
+
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 2;
+    let rect = centered_rect(f.area(), width, lines.len() as u16 + 2);
+    let legend_box = Paragraph::new(Text::from(lines))
+        .block(Block::default().borders(Borders::ALL).title(" Colors ").style(Style::default().fg(c.title)));
+    f.render_widget(Clear, rect);
+    f.render_widget(legend_box, rect);
+}
+
+/// A `width` x `height` rectangle centered in `area`, clamped to fit
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Saved-filter picker (`f`): one line per filter, name then query, with
+/// the name prompt at the bottom while saving
+fn draw_filter_picker(f: &mut Frame, app: &App) {
+    let c = colors();
+    let Some(picker) = &app.filter_picker else {
+        return;
+    };
+    let name_width = app.saved_filters.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    let mut lines: Vec<Line> = app.saved_filters
+        .iter()
+        .enumerate()
+        .map(|(i, (name, query))| {
+            let style = if i == picker.selected && picker.naming.is_none() {
+                Style::default().bg(c.selected_bg).fg(c.selected_fg).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(format!(" {:<width$}  ", name, width = name_width), style.fg(c.label)),
+                Span::styled(format!("{} ", query), style.fg(c.search_highlight)),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::styled(" No saved filters yet", Style::default().fg(c.help_text)));
+    }
+    lines.push(Line::from(""));
+    lines.push(match &picker.naming {
+        Some(name) => Line::from(vec![
+            Span::styled(" Name: ", Style::default().fg(c.label)),
+            Span::styled(name.as_str(), Style::default().fg(c.search_highlight)),
+        ]),
+        None => Line::styled(
+            " Enter: Apply | s: Save current search | d: Delete | ESC: Close ",
+            Style::default().fg(c.help_text),
+        ),
+    });
+
+    let area = f.area();
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0).max(40) as u16 + 2;
+    let rect = centered_rect(area, width, lines.len() as u16 + 2);
+    let picker_box = Paragraph::new(Text::from(lines))
+        .block(Block::default().borders(Borders::ALL).title(" Saved filters ").style(Style::default().fg(c.title)));
+    f.render_widget(Clear, rect);
+    f.render_widget(picker_box, rect);
+    if let Some(name) = &picker.naming {
+        f.set_cursor_position((
+            (rect.x + 1 + 7 + Line::from(name.as_str()).width() as u16).min(rect.right().saturating_sub(2)),
+            rect.bottom().saturating_sub(2),
+        ));
+    }
+}
+
+/// Small box in the top-right corner with timings and table state, for
+/// performance reports (`--debug`, F12)
+fn draw_debug_overlay(f: &mut Frame, app: &App) {
+    let c = colors();
+    let stats = &app.debug_stats;
+    let rows = [
+        ("load", format!("{:.1} ms", stats.load_time.as_secs_f64() * 1000.0)),
+        ("draw", format!("{:.2} ms", stats.draw_time.as_secs_f64() * 1000.0)),
+        ("fps", stats.fps().to_string()),
+        ("groups", app.audit_groups.len().to_string()),
+        ("filtered", app.filtered_groups.len().to_string()),
+        ("selected", app.selected_index.to_string()),
+        ("scroll", app.scroll_offset.to_string()),
+        ("rows", app.visible_height.to_string()),
+        ("memory", format_bytes(stats.memory as u64)),
+    ];
+    let lines: Vec<String> = rows.iter().map(|(label, value)| format!("{:<9}{:>11}", label, value)).collect();
+    let area = f.area();
+    let width = (lines.iter().map(|l| l.len()).max().unwrap_or(0) + 2) as u16;
+    let height = lines.len() as u16 + 2;
+    let rect = Rect {
+        x: area.right().saturating_sub(width + 1),
+        y: area.y + 1,
+        width: width.min(area.width),
+        height: height.min(area.height),
+    };
+    let overlay = Paragraph::new(Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>()))
+        .style(Style::default().fg(c.label))
+        .block(Block::default().borders(Borders::ALL).title(" debug (F12) "));
+    f.render_widget(Clear, rect);
+    f.render_widget(overlay, rect);
+}
+
+/// `text` with every case-insensitive occurrence of a needle in `highlight`.
+/// Needles are lowercase; ASCII case folding keeps the byte offsets valid.
+fn highlight_matches(text: String, needles: &[&str], highlight: Style) -> Line<'static> {
+    if needles.is_empty() || text.is_empty() {
+        return Line::from(text);
+    }
+    let folded = text.to_ascii_lowercase();
+    let mut marked = vec![false; text.len()];
+    for needle in needles {
+        for (start, _) in folded.match_indices(needle) {
+            marked[start..start + needle.len()].fill(true);
+        }
+    }
+    // Runs of marked and unmarked characters, each one span
+    let mut spans = Vec::new();
+    let mut run_start = 0;
+    let ends = text.char_indices().skip(1).map(|(i, _)| i).chain(std::iter::once(text.len()));
+    for end in ends {
+        if end == text.len() || marked[end] != marked[run_start] {
+            let run = text[run_start..end].to_string();
+            spans.push(if marked[run_start] { Span::styled(run, highlight) } else { Span::raw(run) });
+            run_start = end;
+        }
+    }
+    Line::from(spans)
 }
 
 fn draw_table_view(f: &mut Frame, app: &mut App) {
@@ -94,93 +379,283 @@ fn draw_table_view(f: &mut Frame, app: &mut App) {
 
     // Store table area for mouse click handling
     app.table_area = Some(chunks[1]);
+    // ...and the row count the input handlers page by
+    let available_height = app.table_body().map_or(0, |body| body.height as usize);
+    app.visible_height = available_height.max(1);
+    // Fit the window to the rows that really fit, so the selected row is
+    // always among the ones rendered below (whatever the terminal height,
+    // a resize, a dense toggle or the last view's height)
+    app.clamp_scroll();
 
     // Title bar
     let c = colors();
-    let title = Paragraph::new("ModSecurity Audit Log Examiner")
+    let mut title_text = "ModSecurity Audit Log Examiner".to_string();
+    if app.dense {
+        // Dense tables have no block title, so show the count up here
+        title_text.push_str(&format!(" | {} entries", app.filtered_groups.len()));
+    }
+    title_text.push_str(&format!(" | updated {}", app.last_refresh.format("%H:%M:%S")));
+    if app.follow {
+        title_text.push_str(&format!(" | live (every {})", format_duration(app.poll_interval)));
+    }
+    if let Some(log) = app.watch.as_ref().and(app.log_paths.first()) {
+        let name = Path::new(log).file_name().map_or(log.into(), |name| name.to_string_lossy());
+        title_text.push_str(&format!(" | watching {}", name));
+    }
+    if !app.ip_api_enabled {
+        title_text.push_str(" | IP lookups off");
+    }
+    if app.mouse_passthrough && app.mouse_capture {
+        title_text.push_str(" | mouse passthrough (m)");
+    }
+    let title = Paragraph::new(title_text)
         .style(Style::default().fg(c.title).add_modifier(Modifier::BOLD))
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
     // Table
-    let headers = Row::new(vec![
-        Cell::from("Audit ID").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
-        Cell::from("Timestamp").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
-        Cell::from("Domain").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
-        Cell::from("Client IP").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
-        Cell::from("Status").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
-        Cell::from("Rule IDs").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
-    ]);
+    let columns = app.visible_columns();
+    // A one-character ✔ column leads while marking, or while anything is marked
+    let show_marks = app.select_mode || !app.marked.is_empty();
+    let header_cells = columns.iter().map(|column| {
+        Cell::from(column.title()).style(Style::default().fg(c.header).add_modifier(Modifier::BOLD))
+    });
+    let headers = if show_marks {
+        Row::new(std::iter::once(Cell::from("")).chain(header_cells))
+    } else {
+        Row::new(header_cells)
+    };
 
     let visible_groups = app.visible_groups();
 
+    // Calculate dynamic column widths based on terminal width
+    let columns_width = if show_marks { chunks[1].width.saturating_sub(2) } else { chunks[1].width };
+    let mut constraints = calculate_column_widths(columns_width, app.dense, &columns, &app.column_widths);
+    let widths = resolve_column_widths(&constraints, columns_width, app.dense);
+    // Header separators the mouse can drag: one cell past each column but the last
+    let mut x = chunks[1].x + u16::from(!app.dense) + if show_marks { 2 } else { 0 };
+    let column_edges: Vec<(Column, u16, u16)> = columns
+        .iter()
+        .zip(&widths)
+        .take(columns.len().saturating_sub(1))
+        .map(|(&column, &width)| {
+            x += width as u16;
+            let edge = (column, x, width as u16);
+            x += 1;
+            edge
+        })
+        .collect();
+    if show_marks {
+        constraints.insert(0, Constraint::Length(1));
+    }
+
     // Calculate visible window - only render what fits on screen (performance optimization)
-    let available_height = chunks[1].height.saturating_sub(3) as usize; // Subtract borders and header
     let start_idx = app.scroll_offset;
 
+    // Show why a row matched: the searched text in the cells it was found in
+    let highlights = app.search_highlights();
+    let highlight_style = Style::default().fg(c.search_highlight).add_modifier(Modifier::BOLD);
+    let highlighted = |column: Column, text: String| {
+        let needles: Vec<&str> = highlights.iter()
+            .filter(|(c, _)| *c == column)
+            .map(|(_, needle)| needle.as_str())
+            .collect();
+        Cell::from(highlight_matches(text, &needles, highlight_style))
+    };
+
     let rows: Vec<Row> = visible_groups
         .iter()
         .enumerate()
         .skip(start_idx)
         .take(available_height)
         .map(|(idx, group)| {
+            // Selection always wins over the status tint
             let style = if idx == app.selected_index {
                 Style::default()
                     .bg(c.selected_bg)
                     .fg(c.selected_fg)
                     .add_modifier(Modifier::BOLD)
+            } else if let Some(tint) = c.row_tint(group.http_status.code()).filter(|_| app.row_tint) {
+                Style::default().bg(tint)
             } else {
                 Style::default()
             };
 
-            let rule_ids = if group.primary_rule_ids.len() > 3 {
-                format!("{} (+{})", group.primary_rule_ids[..3].join(", "), group.primary_rule_ids.len() - 3)
+            let shown_ids = app.display_rule_ids(&group.primary_rule_ids);
+            let rule_ids = if shown_ids.len() > 3 {
+                format!("{} (+{})", shown_ids[..3].join(", "), shown_ids.len() - 3)
             } else {
-                group.primary_rule_ids.join(", ")
+                shown_ids.join(", ")
             };
             let timestamp = group.first_timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
 
-            let status_text = group.http_status
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| "N/A".to_string());
-            let status_color = c.status_color(group.http_status);
+            let mut status_text = group.http_status.label();
+            // Blocked, as opposed to only detected
+            if group.blocked {
+                status_text.push_str(" ✖");
+            }
+            let status_color = c.status_color(group.http_status.code());
 
-            Row::new(vec![
-                Cell::from(group.base_id.clone()).style(Style::default().fg(c.audit_id)),
-                Cell::from(timestamp).style(Style::default().fg(c.timestamp)),
-                Cell::from(group.domain.clone()).style(Style::default().fg(c.domain)),
-                Cell::from(group.client_ip.clone()).style(Style::default().fg(c.client_ip)),
-                Cell::from(status_text).style(Style::default().fg(status_color)),
-                Cell::from(rule_ids).style(Style::default().fg(c.rule_id)),
-            ])
-            .style(style)
+            // Proxy/hosting/mobile flags and country, only once geolocation is cached
+            let geo = app.ip_api_cache.cached(&group.client_ip);
+            let flags = geo.as_ref()
+                .map(|geo| geo.flags())
+                .unwrap_or_default();
+
+            // Collapsed rows lead with how many transactions they stand for;
+            // `~` marks a chain the log cut off before its Z boundary
+            let id = if app.short_ids { group.display_id(app.short_id_len) } else { &group.base_id };
+            let mut audit_id = match app.similar_groups.get(idx).filter(|s| s.count() > 1) {
+                Some(similar) => format!("×{} {}", similar.count(), id),
+                None => id.to_string(),
+            };
+            if group.incomplete {
+                audit_id.insert(0, '~');
+            }
+            // `⚠`: more than one transaction seems to share the audit ID
+            if group.collided {
+                audit_id.insert(0, '⚠');
+            }
+
+            let cells = columns.iter().zip(&widths).map(|(&column, &width)| match column {
+                Column::AuditId => highlighted(column, ellipsize(&audit_id, width)).style(Style::default().fg(c.audit_id)),
+                Column::Timestamp => Cell::from(ellipsize(&timestamp, width)).style(Style::default().fg(c.timestamp)),
+                Column::Domain => highlighted(column, ellipsize(&group.domain, width)).style(Style::default().fg(c.domain)),
+                Column::ClientIp if app.is_noisy_ip(&group.client_ip) => {
+                    // The marker survives truncation, the address gives way
+                    let ip = ellipsize(&group.client_ip, width.saturating_sub(2));
+                    highlighted(column, format!("{} ▲", ip))
+                        .style(Style::default().fg(c.client_ip).add_modifier(Modifier::BOLD))
+                }
+                Column::ClientIp => highlighted(column, ellipsize(&group.client_ip, width)).style(Style::default().fg(c.client_ip)),
+                Column::Country => {
+                    let country = geo.as_ref().and_then(|geo| geo.country_code.clone())
+                        .unwrap_or_else(|| "??".to_string());
+                    Cell::from(country).style(Style::default().fg(c.domain))
+                }
+                Column::Status => highlighted(column, status_text.clone()).style(Style::default().fg(status_color)),
+                Column::Action => {
+                    let badge = action_badge(group.action.as_deref());
+                    let color = if badge == "block" { c.status_5xx } else { c.status_3xx };
+                    Cell::from(badge).style(Style::default().fg(color))
+                }
+                Column::Flags => Cell::from(flags.clone()).style(Style::default().fg(c.client_ip)),
+                Column::RuleIds => highlighted(column, ellipsize(&rule_ids, width)).style(Style::default().fg(c.rule_id)),
+                Column::Category => highlighted(column, ellipsize(&group_categories(group).join(", "), width))
+                    .style(Style::default().fg(c.rule_id)),
+            });
+            if show_marks {
+                let mark = if app.is_row_marked(idx) { "✔" } else { " " };
+                let mark = Cell::from(mark).style(Style::default().fg(c.header).add_modifier(Modifier::BOLD));
+                return Row::new(std::iter::once(mark).chain(cells)).style(style);
+            }
+            Row::new(cells).style(style)
         })
         .collect();
 
-    // Calculate dynamic column widths based on terminal width
-    let constraints = calculate_column_widths(chunks[1].width);
+    let mut table_title = format!(" Entries ({}) ", visible_groups.len());
+    if let Some(key) = &app.expanded_similar {
+        table_title.push_str(&format!("[similar: {} {}] ", key.client_ip, key.path.as_deref().unwrap_or("?")));
+    } else if app.collapsed {
+        table_title.push_str("[collapsed] ");
+    }
+    if app.fuzzy {
+        table_title.push_str("[fuzzy] ");
+    }
+    if show_marks {
+        let mode = if app.select_mode { "selecting, " } else { "" };
+        table_title.push_str(&format!("[{}{} marked] ", mode, app.marked.len()));
+    }
+    if app.has_status_filter() {
+        let min = app.min_status.map(|s| s.to_string()).unwrap_or_default();
+        let max = app.max_status.map(|s| s.to_string()).unwrap_or_default();
+        table_title.push_str(&format!("[status {}..{}] ", min, max));
+    }
+    if let Some(since) = app.since {
+        table_title.push_str(&format!("[since {}] ", since.format("%Y-%m-%d %H:%M")));
+    }
+    match (app.hosting_only, app.proxy_only) {
+        (true, true) => table_title.push_str("[hosting or proxy] "),
+        (true, false) => table_title.push_str("[hosting] "),
+        (false, true) => table_title.push_str("[proxy] "),
+        (false, false) => {}
+    }
+    // Keep the active query visible once the search bar is closed
+    if !app.search_mode && !app.search_query.is_empty() {
+        table_title.push_str(&format!("| filter: {} ", app.search_query));
+    }
 
-    let table = Table::new(rows, constraints)
+    let mut table = Table::new(rows, constraints)
         .header(headers)
-        .block(
+        .row_highlight_style(Style::default().bg(c.selected_bg));
+    if !app.dense {
+        table = table.block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!(" Entries ({}) ", visible_groups.len())),
-        )
-        .row_highlight_style(Style::default().bg(c.selected_bg));
+                .title(table_title),
+        );
+    }
 
     f.render_widget(table, chunks[1]);
+    if let Some(body) = app.table_body() {
+        // Over the right border (or the last column when dense), next to the rows
+        let track = Rect { x: chunks[1].x, width: chunks[1].width, ..body };
+        draw_scrollbar(f, track, app.filtered_groups.len(), body.height as usize, app.scroll_offset);
+    }
+
+    // Empty state: say why there is nothing to show instead of an empty box
+    if visible_groups.is_empty() {
+        if let Some(body) = app.table_body().filter(|b| b.height > 0) {
+            let message = if !app.search_query.is_empty() {
+                format!("No matches for '{}' (ESC clears the search)", app.search_query)
+            } else if app.has_status_filter() {
+                "No entries within the --min-status/--max-status range".to_string()
+            } else if app.since.is_some() {
+                "No entries within the --since window".to_string()
+            } else if app.has_geo_filter() {
+                "No entries from flagged IPs among those resolved so far (H/P turn the filter off)".to_string()
+            } else {
+                "No audit entries in this log".to_string()
+            };
+            let middle = Rect::new(body.x, body.y + body.height / 2, body.width, 1);
+            let empty = Paragraph::new(message)
+                .style(Style::default().fg(c.label))
+                .alignment(Alignment::Center);
+            f.render_widget(empty, middle);
+        }
+    }
 
     // File/Rule info bar
     let info_text = if let Some(group) = app.selected_group() {
         let rule_id = group.primary_rule_ids.first()
-            .map(|r| r.as_str())
-            .unwrap_or("N/A");
-        let file = group.file_path.as_ref()
-            .map(|f| f.as_str())
+            .map(|r| crs::describe(r))
+            .unwrap_or_else(|| "N/A".to_string());
+        let file = group.file_path.as_deref()
             .unwrap_or("N/A");
-        format!("File: {} | Rule ID: {}", file, rule_id)
+        match app.selected_similar().filter(|s| s.count() > 1) {
+            Some(similar) => format!(
+                "Seen {} times ({} .. {}) | File: {} | Rule ID: {}",
+                similar.count(),
+                similar.first_seen.format("%Y-%m-%d %H:%M:%S"),
+                similar.last_seen.format("%Y-%m-%d %H:%M:%S"),
+                file,
+                rule_id,
+            ),
+            None if group.collided && !app.parser.split_on_ip() => format!(
+                "⚠ Audit ID shared by different transactions (client IPs or times differ); --split-on-ip separates them | Rule ID: {}",
+                rule_id
+            ),
+            None if group.collided => format!(
+                "⚠ Split from a shared audit ID (--split-on-ip) | File: {} | Rule ID: {}",
+                file, rule_id
+            ),
+            None if group.incomplete => format!(
+                "Incomplete: the log ends mid-entry (r refreshes) | File: {} | Rule ID: {}",
+                file, rule_id
+            ),
+            None => format!("File: {} | Rule ID: {}", file, rule_id),
+        }
     } else {
         "No entry selected".to_string()
     };
@@ -192,34 +667,56 @@ fn draw_table_view(f: &mut Frame, app: &mut App) {
     f.render_widget(info_bar, chunks[2]);
 
     // Help/keybinds bar
-    let help_text = if app.search_mode {
-        "ESC: Exit search | Enter: Apply search"
+    let help = if let Some(note) = &app.status_message {
+        Paragraph::new(note.as_str()).style(Style::default().fg(c.label))
+    } else if app.search_mode {
+        Paragraph::new("ESC: Exit search | Enter: Apply search").style(Style::default().fg(c.help_text))
+    } else if app.select_mode {
+        Paragraph::new("SELECT | Space: Mark/unmark and move down | ↑/↓: Navigate | M: Copy marked as Markdown | v: Done (keep marks) | ESC: Drop marks")
+            .style(Style::default().fg(c.help_text))
     } else {
-        "↑/↓: Navigate | Enter: Details | /: Search | r/F5: Refresh | q: Quit"
+        Paragraph::new("↑/↓: Navigate | n/p: Next/prev error | Enter: Details | /: Search | f: Filters | d: Domains | s: Stats | c: Collapse | g: Country | a: Short IDs | O: Sort rule IDs | H/P: Hosting/Proxy only | o: Open IP | v: Select | M: Copy as Markdown | t: Tint | b: Borders | r/F5: Refresh | F: Follow | ?: Legend | q: Quit")
+            .style(Style::default().fg(c.help_text))
     };
-
-    let help = Paragraph::new(help_text)
-        .style(Style::default().fg(c.help_text))
+    let help = help
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(help, chunks[3]);
 
     // Search bar
     if app.search_mode {
-        let search_text = format!("Search: {}", app.search_query);
-        let search = Paragraph::new(search_text)
+        let prompt = if app.fuzzy { "Fuzzy search: " } else { "Search: " };
+        let search_text = format!("{}{}", prompt, app.search_query);
+        // Terminal cursor at the edit position, inside the border
+        let cursor_x = Line::from(&search_text[..prompt.len() + app.search_cursor()]).width() as u16;
+        f.set_cursor_position((
+            (chunks[4].x + 1 + cursor_x).min(chunks[4].right().saturating_sub(2)),
+            chunks[4].y + 1,
+        ));
+        // Live feedback while typing; a dead-end query stands out in red
+        let matches = app.filtered_groups.len();
+        let count_style = if matches == 0 {
+            Style::default().fg(c.no_matches).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(c.help_text)
+        };
+        let search = Paragraph::new(Line::from(vec![
+            Span::raw(search_text),
+            Span::styled(format!(" → {} {}", matches, if matches == 1 { "match" } else { "matches" }), count_style),
+        ]))
             .style(Style::default().fg(c.search_highlight))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(" Search (domain:, ip:, id:, auditid:, status:) ")
+                    .title(" Search (domain:, ip:, id:, auditid:, status:4xx,403, flag:, asn:, country:, reqsize:>N, blocked:, mode:, content:, data:, -token: to exclude) ")
                     .style(Style::default().fg(c.title)),
             );
         f.render_widget(search, chunks[4]);
     }
+    app.column_edges = column_edges;
 }
 
-fn draw_detail_view(f: &mut Frame, app: &App) {
+fn draw_detail_view(f: &mut Frame, app: &mut App) {
     let c = colors();
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -231,31 +728,146 @@ fn draw_detail_view(f: &mut Frame, app: &App) {
         ])
         .split(f.area());
 
+    let (mut line_count, mut viewport, mut scroll, mut hscroll) = (0, 0, 0, app.detail_hscroll);
     if let Some(group) = app.selected_group() {
         // Title
-        let title_text = format!(
+        let mut title_text = format!(
             "Audit Chain: {} | {} | {}",
             group.base_id, group.domain, group.client_ip
         );
+        // `--trust-xff` took the address from a header; name the proxy too
+        if let Some(peer) = group.entries.iter().find_map(|e| e.peer_ip.as_deref()) {
+            title_text.push_str(&format!(" via {}", peer));
+        }
+        if let Some(action) = &group.action {
+            title_text.push_str(&format!(" | {}", action));
+        }
+        if let Some(mode) = &group.engine_mode {
+            title_text.push_str(&format!(" | engine {}", mode));
+        }
+        if group.request_size.is_some() || group.response_size.is_some() {
+            let size = |s: Option<u64>| s.map_or_else(|| "?".to_string(), format_bytes);
+            title_text.push_str(&format!(
+                " | req {} / resp {}",
+                size(group.request_size),
+                size(group.response_size)
+            ));
+        }
         let title = Paragraph::new(title_text)
             .style(Style::default().fg(c.title).add_modifier(Modifier::BOLD))
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(title, chunks[0]);
 
+        // Timeline above the content, at most half of its height
+        let content_area = if app.detail_timeline {
+            let height = (chain_timeline_height(group) as u16).min(chunks[1].height / 2);
+            let [timeline_area, content_area] = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(height), Constraint::Min(0)])
+                .areas(chunks[1]);
+            draw_chain_timeline(f, timeline_area, group, c);
+            content_area
+        } else {
+            chunks[1]
+        };
+
         // Detail content
         let mut lines = Vec::new();
 
-        for entry in &group.entries {
-            // Parse and color-code content
-            let content_lines = colorize_content(&entry.raw_content, c);
+        // Rule hit counts across the whole chain, with CRS descriptions
+        let rule_counts = group.rule_id_counts();
+        if !rule_counts.is_empty() && !app.detail_raw {
+            lines.push(Line::from(Span::styled(
+                "Rule Hits",
+                Style::default().fg(c.label).add_modifier(Modifier::BOLD),
+            )));
+            for (rule_id, count) in &rule_counts {
+                let mut spans = vec![Span::styled(
+                    format!("  {} ×{}", rule_id, count),
+                    Style::default().fg(c.rule_id_detail).add_modifier(Modifier::BOLD),
+                )];
+                if let Some(desc) = crs::rule_description(rule_id) {
+                    spans.push(Span::raw(format!("  {}", desc)));
+                }
+                lines.push(Line::from(spans));
+            }
+            lines.push(Line::from(""));
+        }
+
+        // What the rules matched, once per distinct value across the chain
+        let mut matched_data: Vec<&str> = Vec::new();
+        for data in group.entries.iter().flat_map(|e| &e.matched_data) {
+            if !matched_data.contains(&data.as_str()) {
+                matched_data.push(data);
+            }
+        }
+        if !matched_data.is_empty() && !app.detail_raw {
+            lines.push(Line::from(Span::styled(
+                "Matched Data",
+                Style::default().fg(c.label).add_modifier(Modifier::BOLD),
+            )));
+            for data in matched_data {
+                let (text, truncated) = matched_data_display(data, app.detail_data_expanded);
+                let mut spans = vec![
+                    Span::raw("  "),
+                    Span::styled(text, Style::default().fg(c.search_highlight).add_modifier(Modifier::BOLD)),
+                ];
+                if truncated {
+                    spans.push(Span::styled(" … (x: expand)", Style::default().fg(c.help_text)));
+                }
+                lines.push(Line::from(spans));
+            }
+            lines.push(Line::from(""));
+        }
+
+        // Line of the selected entry's summary in the parts layout
+        let mut selected_part_line = None;
+        for (i, entry) in group.entries.iter().enumerate() {
+            let open = app.detail_part_open == Some(i);
+            if app.detail_parts {
+                if i == app.detail_part {
+                    selected_part_line = Some(lines.len());
+                }
+                let rules = app.display_rule_ids(&entry.rule_ids).join(", ");
+                lines.push(part_summary(entry, &rules, open, i == app.detail_part, c));
+                if !open {
+                    continue;
+                }
+            }
+
+            // Parse and color-code content, or show it untouched in raw mode
+            let sections = app.detail_sections_filter;
+            let bytes = entry.raw_bytes.as_deref().unwrap_or(entry.raw_content.as_bytes());
+            let content_lines = if app.detail_raw {
+                plain_content(&entry.raw_content, sections)
+            } else if app.detail_header_table {
+                let render = |chunk| colorize_with_header_table(chunk, c, sections);
+                if app.detail_hexdump {
+                    with_hexdump(&entry.raw_content, bytes, c, sections, render)
+                } else {
+                    render(&entry.raw_content)
+                }
+            } else {
+                let render = |chunk| colorize_content(chunk, c, sections);
+                if app.detail_hexdump {
+                    with_hexdump(&entry.raw_content, bytes, c, sections, render)
+                } else {
+                    render(&entry.raw_content)
+                }
+            };
+            if content_lines.is_empty() {
+                continue;
+            }
             lines.extend(content_lines);
 
             lines.push(Line::from(""));
         }
 
-        // Add IP API information if available
-        if let Some(ref ip_info) = app.current_ip_info {
+        // Add IP lookup information when lookups are enabled
+        if app.detail_raw {
+            // Nothing but the log text
+        } else if app.ip_api_enabled {
             lines.push(Line::from(""));
             lines.push(Line::from(vec![
                 Span::styled(
@@ -265,29 +877,125 @@ fn draw_detail_view(f: &mut Frame, app: &App) {
             ]));
             lines.push(Line::from(""));
 
-            // Syntax highlight the JSON
-            let json_lines = colorize_json(ip_info, c);
-            lines.extend(json_lines);
+            if app.ip_info_pending {
+                lines.push(Line::from(Span::styled("Fetching geolocation…", Style::default().fg(c.help_text))));
+            } else {
+                let ptr = app.current_ptr.as_deref().unwrap_or("(no PTR)");
+                lines.push(Line::from(vec![
+                    Span::styled("Reverse DNS: ", Style::default().fg(c.label).add_modifier(Modifier::BOLD)),
+                    Span::styled(ptr, Style::default().fg(c.host_header)),
+                ]));
+                let label = Span::styled("Abuse Contact: ", Style::default().fg(c.label).add_modifier(Modifier::BOLD));
+                match &app.current_abuse {
+                    Some(abuse) => {
+                        let emails = if abuse.emails.is_empty() {
+                            "(none listed)".to_string()
+                        } else {
+                            abuse.emails.join(", ")
+                        };
+                        lines.push(Line::from(vec![label, Span::styled(emails, Style::default().fg(c.host_header))]));
+                        let network = [abuse.network.clone(), abuse.handle.clone().map(|h| format!("({})", h)), Some(abuse.range_text())]
+                            .into_iter()
+                            .flatten()
+                            .filter(|part| !part.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        if !network.is_empty() {
+                            lines.push(Line::from(vec![
+                                Span::styled("Network: ", Style::default().fg(c.label).add_modifier(Modifier::BOLD)),
+                                Span::raw(network),
+                            ]));
+                        }
+                    }
+                    None => lines.push(Line::from(vec![
+                        label,
+                        Span::styled("press a to look up", Style::default().fg(c.help_text)),
+                    ])),
+                }
+
+                // Syntax highlight the JSON
+                if let Some(ref ip_info) = app.current_ip_info {
+                    let json_lines = colorize_json(ip_info, c);
+                    lines.extend(json_lines);
+                }
+            }
+        } else {
+            lines.push(Line::from(Span::styled(
+                "IP lookups are off (press I to enable)",
+                Style::default().fg(c.help_text),
+            )));
         }
 
+        // Rendered (wrapped) rows above the selected summary line of the parts layout
+        let inner_width = content_area.width.saturating_sub(2);
+        let rows_above_part = selected_part_line.map(|selected| {
+            wrapped(Paragraph::new(Text::from(lines[..selected].to_vec())), app.detail_wrap)
+                .line_count(inner_width)
+        });
+        // Unwrapped, the sideways offset stops where the widest line ends
+        let widest = lines.iter().map(Line::width).max().unwrap_or(0);
+        hscroll = hscroll.min(widest.saturating_sub(inner_width as usize));
         let detail_text = Text::from(lines);
         let detail = Paragraph::new(detail_text)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(format!(" Chain Details ({} parts) ", group.entries.len())),
-            )
-            .wrap(Wrap { trim: false })
-            .scroll((app.detail_scroll as u16, 0));
+                    .title({
+                        let mut title = format!(" Chain Details ({} parts) ", group.entries.len());
+                        if let Some(sections) = app.detail_sections_filter {
+                            title.push_str(&format!("[sections {}] ", sections.iter().collect::<String>()));
+                        }
+                        if app.detail_raw {
+                            title.push_str("[raw] ");
+                        } else if app.detail_header_table {
+                            title.push_str("[header table] ");
+                        }
+                        if app.detail_parts {
+                            title.push_str("[parts] ");
+                        }
+                        if !app.detail_wrap {
+                            match hscroll {
+                                0 => title.push_str("[no wrap] "),
+                                column => title.push_str(&format!("[no wrap, col {}] ", column + 1)),
+                            }
+                        }
+                        if group.incomplete {
+                            title.push_str("[incomplete] ");
+                        }
+                        if group.collided {
+                            title.push_str("[⚠ shared audit ID] ");
+                        }
+                        title.push_str(if group.blocked { "[blocked] " } else { "[detection only] " });
+                        title
+                    }),
+            );
+        let detail = wrapped(detail, app.detail_wrap);
 
-        f.render_widget(detail, chunks[1]);
+        // Rendered (wrapped) line count, without the block's borders
+        line_count = detail.line_count(inner_width).saturating_sub(2);
+
+        let track = Rect {
+            y: content_area.y + 1,
+            height: content_area.height.saturating_sub(2),
+            ..content_area
+        };
+        viewport = track.height as usize;
+        scroll = app.detail_scroll;
+        // Keep the selected summary line of the parts layout on screen
+        if let Some(above) = rows_above_part {
+            scroll = scroll.clamp((above + 1).saturating_sub(viewport), above);
+        }
+        // A remembered scroll or a resize can leave the offset past the end
+        scroll = scroll.min(line_count.saturating_sub(viewport));
+
+        f.render_widget(detail.scroll((scroll as u16, hscroll as u16)), content_area);
+        draw_scrollbar(f, track, line_count, viewport, scroll);
 
         // File/Rule info bar
         let rule_id = group.primary_rule_ids.first()
-            .map(|r| r.as_str())
-            .unwrap_or("N/A");
-        let file = group.file_path.as_ref()
-            .map(|f| f.as_str())
+            .map(|r| crs::describe(r))
+            .unwrap_or_else(|| "N/A".to_string());
+        let file = group.file_path.as_deref()
             .unwrap_or("N/A");
         let info_text = format!("File: {} | Rule ID: {}", file, rule_id);
 
@@ -298,18 +1006,259 @@ fn draw_detail_view(f: &mut Frame, app: &App) {
         f.render_widget(info_bar, chunks[2]);
 
         // Help bar
-        let help = Paragraph::new("↑/↓: Scroll | ←/→: Prev/Next Entry | PgUp/PgDn: Page | ESC/q: Back")
-            .style(Style::default().fg(c.help_text))
+        let fetching = app.ip_info_pending.then(|| format!("Fetching geolocation for {}…", group.client_ip));
+        let help = match app.status_message.as_ref().or(fetching.as_ref()) {
+            Some(note) => Paragraph::new(note.as_str()).style(Style::default().fg(c.label)),
+            None => Paragraph::new("↑/↓: Scroll | ←/→: Prev/Next Entry | PgUp/PgDn: Page | c: Copy | C: Copy as curl | f: H/K only | r: Raw | t: Header table | x: Full data | H: Hex dump | w: Timeline | W: Wrap | </>: Sideways | i/d/R: Pivot on IP/domain/rule | p: Parts | o: Open IP | a: Abuse contact | I: IP lookups | ?: Legend | ESC/q: Back")
+                .style(Style::default().fg(c.help_text)),
+        };
+        let help = help
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(help, chunks[3]);
     }
+    app.detail_line_count = line_count;
+    app.detail_viewport = viewport;
+    app.detail_scroll = scroll;
+    app.detail_hscroll = hscroll;
+}
+
+/// Wraps the detail text unless the view shows it unwrapped
+fn wrapped(paragraph: Paragraph, wrap: bool) -> Paragraph {
+    if wrap { paragraph.wrap(Wrap { trim: false }) } else { paragraph }
+}
+
+fn draw_domain_view(f: &mut Frame, app: &mut App) {
+    let c = colors();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),  // Help/keybinds bar
+        ])
+        .split(f.area());
+
+    let title_text = if app.search_query.is_empty() {
+        "Domain Rollup".to_string()
+    } else {
+        format!("Domain Rollup | filter: {}", app.search_query)
+    };
+    let title = Paragraph::new(title_text)
+        .style(Style::default().fg(c.title).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let headers = Row::new(vec![
+        Cell::from("Domain").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
+        Cell::from("Hits").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
+        Cell::from("Distinct IPs").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
+        Cell::from("Top Rule").style(Style::default().fg(c.header).add_modifier(Modifier::BOLD)),
+    ]);
+
+    // Only render the rows that fit, like the main table
+    let available_height = chunks[1].height.saturating_sub(3) as usize;
+    app.visible_height = available_height.max(1);
+    app.clamp_scroll();
+    let rows: Vec<Row> = app.domain_summaries
+        .iter()
+        .enumerate()
+        .skip(app.domain_scroll)
+        .take(available_height)
+        .map(|(idx, summary)| {
+            let style = if idx == app.domain_selected {
+                Style::default()
+                    .bg(c.selected_bg)
+                    .fg(c.selected_fg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+
+            Row::new(vec![
+                Cell::from(summary.domain.clone()).style(Style::default().fg(c.domain)),
+                Cell::from(summary.hits.to_string()).style(Style::default().fg(c.timestamp)),
+                Cell::from(summary.distinct_ips.to_string()).style(Style::default().fg(c.client_ip)),
+                Cell::from(summary.top_rule.clone().unwrap_or_else(|| "N/A".to_string()))
+                    .style(Style::default().fg(c.rule_id)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let table = Table::new(rows, [
+        Constraint::Min(20),
+        Constraint::Length(8),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ])
+        .header(headers)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Domains ({}) ", app.domain_summaries.len())),
+        );
+    f.render_widget(table, chunks[1]);
+
+    let help = Paragraph::new("↑/↓: Navigate | Enter: Show domain in table | ESC/q/d: Back")
+        .style(Style::default().fg(c.help_text))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(help, chunks[2]);
 }
 
-fn colorize_content<'a>(content: &'a str, c: &ColorScheme) -> Vec<Line<'a>> {
+fn draw_stats_view(f: &mut Frame, app: &mut App) {
+    let c = colors();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),  // Help/keybinds bar
+        ])
+        .split(f.area());
+
+    let stats = &app.stats;
+    let title_text = if app.search_query.is_empty() {
+        format!("Statistics | {} entries", stats.total)
+    } else {
+        format!("Statistics | {} entries | filter: {}", stats.total, app.search_query)
+    };
+    let title = Paragraph::new(title_text)
+        .style(Style::default().fg(c.title).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
     let mut lines = Vec::new();
+    // Rows that pivot (`stats_pivots`) are counted in the same order, so the
+    // cursor lands on the right one; `selected_line` is where it was drawn
+    let mut pivot = 0;
+    let mut selected_line = None;
+    let selected_style = Style::default().bg(c.selected_bg).fg(c.selected_fg).add_modifier(Modifier::BOLD);
+    let mut row = |lines: &mut Vec<Line<'static>>, field: &str, key: &str, spans: Vec<Span<'static>>| {
+        let mut line = Line::from(spans);
+        if stats_pivot_value(field, key).is_some() {
+            if pivot == app.stats_selected {
+                line = Line::from(line.spans.into_iter().map(|span| span.style(selected_style)).collect::<Vec<_>>());
+                selected_line = Some(lines.len());
+            }
+            pivot += 1;
+        }
+        lines.push(line);
+    };
+    // `describe` adds a note after each key (a rule's or category's description)
+    let mut section = |lines: &mut Vec<Line<'static>>, heading: String, field: &str, rows: &[(String, usize)], color: Color, describe: fn(&str) -> Option<&'static str>| {
+        lines.push(Line::from(Span::styled(
+            heading,
+            Style::default().fg(c.label).add_modifier(Modifier::BOLD),
+        )));
+        if rows.is_empty() {
+            lines.push(Line::from(Span::styled("  (none)", Style::default().fg(c.boundary))));
+        }
+        for (key, count) in rows {
+            let mut spans = vec![
+                Span::styled(format!("  {:>7}  ", count), Style::default().fg(c.timestamp)),
+                Span::styled(key.clone(), Style::default().fg(color)),
+            ];
+            if let Some(desc) = describe(key) {
+                spans.push(Span::styled(format!("  {}", desc), Style::default().fg(c.help_text)));
+            }
+            row(lines, field, key, spans);
+        }
+        lines.push(Line::from(""));
+    };
+
+    let plain = |_: &str| None;
+    section(&mut lines, "Status Classes".to_string(), "status", &stats.status_classes, c.http_status, plain);
+    section(&mut lines, "Top Client IPs".to_string(), "ip", &stats.top_ips, c.client_ip, plain);
+    section(&mut lines, "Top Rules".to_string(), "rule", &stats.top_rules, c.rule_id, crs::rule_description);
+    section(&mut lines, "Attacks by Category".to_string(), "cat", &stats.top_categories, c.rule_id, crs::category_description);
+    section(&mut lines, "Top Domains".to_string(), "domain", &stats.top_domains, c.domain, plain);
+    let asn_heading = if stats.unresolved_geo > 0 {
+        format!("Top ASNs ({} entries without geolocation yet)", stats.unresolved_geo)
+    } else {
+        "Top ASNs".to_string()
+    };
+    section(&mut lines, asn_heading, "asn", &stats.top_asns, c.header_name, plain);
 
-    for line in content.lines() {
+    lines.push(Line::from(Span::styled(
+        format!("Noisiest IPs (peak requests/s over {}s, ▲ from {})", RATE_WINDOW.as_secs(), app.rate_threshold),
+        Style::default().fg(c.label).add_modifier(Modifier::BOLD),
+    )));
+    if stats.noisiest_ips.is_empty() {
+        lines.push(Line::from(Span::styled("  (none)", Style::default().fg(c.boundary))));
+    }
+    for (ip, rate) in &stats.noisiest_ips {
+        let marker = if *rate >= app.rate_threshold { " ▲" } else { "" };
+        row(&mut lines, "ip", ip, vec![
+            Span::styled(format!("  {:>7.1}  ", rate), Style::default().fg(c.timestamp)),
+            Span::styled(format!("{}{}", ip, marker), Style::default().fg(c.client_ip)),
+        ]);
+    }
+
+    // Scroll just enough to keep the cursor in view; the top of the list
+    // (the first heading) shows while the cursor is on the first rows
+    let body_height = chunks[1].height.saturating_sub(2) as usize;
+    if let Some(line) = selected_line {
+        let line = if app.stats_selected == 0 { 0 } else { line };
+        app.stats_scroll = app.stats_scroll.min(line).max((line + 1).saturating_sub(body_height));
+    }
+    let body = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL))
+        .scroll((app.stats_scroll as u16, 0));
+    f.render_widget(body, chunks[1]);
+
+    let help = Paragraph::new("↑/↓: Move | Enter: Show in table | ESC/q/s: Back")
+        .style(Style::default().fg(c.help_text))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(help, chunks[2]);
+}
+
+/// Lines of raw audit log text. With `sections`, only lines of those
+/// sections (including their boundary lines) are kept.
+fn section_lines<'a>(content: &'a str, sections: Option<&'a [char]>) -> impl Iterator<Item = &'a str> {
+    let mut current_section = None;
+    content
+        .lines()
+        // Content is normalized by the parser, but never render a stray \r
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(move |line| {
+            if let Some(letter) = section_marker(line) {
+                current_section = Some(letter);
+            }
+            sections.is_none_or(|sections| current_section.is_some_and(|s| sections.contains(&s)))
+        })
+}
+
+/// Matched data with control bytes escaped, cut at `MATCHED_DATA_WIDTH` chars
+/// unless expanded; the flag tells whether anything was cut
+fn matched_data_display(data: &str, expanded: bool) -> (String, bool) {
+    const MATCHED_DATA_WIDTH: usize = 120;
+
+    let escaped: String = data
+        .chars()
+        .map(|ch| if ch.is_control() { ch.escape_default().to_string() } else { ch.to_string() })
+        .collect();
+    if expanded || escaped.chars().count() <= MATCHED_DATA_WIDTH {
+        return (escaped, false);
+    }
+    (escaped.chars().take(MATCHED_DATA_WIDTH).collect(), true)
+}
+
+/// Raw audit log text as unstyled lines, for the raw detail mode
+fn plain_content<'a>(content: &'a str, sections: Option<&'a [char]>) -> Vec<Line<'a>> {
+    section_lines(content, sections).map(Line::raw).collect()
+}
+
+/// Color-code raw audit log text. With `sections`, only lines of those
+/// sections are kept (see `section_lines`).
+fn colorize_content<'a>(content: &'a str, c: &ColorScheme, sections: Option<&'a [char]>) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+
+    for line in section_lines(content, sections) {
         let line_lower = line.to_lowercase();
 
         if line.starts_with("GET") || line.starts_with("POST") || line.starts_with("PUT") ||
@@ -357,9 +1306,16 @@ fn colorize_content<'a>(content: &'a str, c: &ColorScheme) -> Vec<Line<'a>> {
                 Span::styled(line, Style::default().fg(c.boundary)),
             ]));
         } else if line.starts_with("HTTP/") {
-            lines.push(Line::from(vec![
-                Span::styled(line, Style::default().fg(c.http_status).add_modifier(Modifier::BOLD)),
-            ]));
+            // Status line: color by class like the table, and label the class
+            let code = line.split_whitespace().nth(1).and_then(|s| s.parse::<u16>().ok());
+            let color = code.map_or(c.http_status, |code| c.status_color(Some(code)));
+            let mut spans = vec![
+                Span::styled(line, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            ];
+            if let Some(label) = code.and_then(status_class_label) {
+                spans.push(Span::styled(format!("  ({})", label), Style::default().fg(color)));
+            }
+            lines.push(Line::from(spans));
         } else if line.ends_with(':') && !line.contains(' ') {
             // Other header names
             lines.push(Line::from(vec![
@@ -373,6 +1329,213 @@ fn colorize_content<'a>(content: &'a str, c: &ColorScheme) -> Vec<Line<'a>> {
     lines
 }
 
+/// One-line summary of a chain entry for the parts layout: its sections,
+/// timestamp, status and `rules` (joined rule IDs, in display order)
+fn part_summary<'a>(entry: &AuditEntry, rules: &str, open: bool, selected: bool, c: &ColorScheme) -> Line<'a> {
+    let sections: String = entry.raw_content.lines().filter_map(section_marker).collect();
+    let status = entry.http_status.label();
+    let rules = if rules.is_empty() { "-" } else { rules };
+    // Continuation parts have no A header, so no timestamp of their own
+    let timestamp = if sections.contains('A') {
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
+    } else {
+        "-".to_string()
+    };
+    let line = Line::from(vec![
+        Span::raw(if open { "▾ " } else { "▸ " }),
+        Span::styled(format!("{:<8} ", sections), Style::default().fg(c.boundary)),
+        Span::styled(format!("{:<19}  ", timestamp), Style::default().fg(c.timestamp)),
+        Span::styled(format!("{:<4} ", status), Style::default().fg(c.status_color(entry.http_status.code()))),
+        Span::styled(rules.to_string(), Style::default().fg(c.rule_id)),
+    ]);
+    if selected {
+        line.style(Style::default().bg(c.selected_bg).fg(c.selected_fg).add_modifier(Modifier::BOLD))
+    } else {
+        line
+    }
+}
+
+/// Rows `draw_chain_timeline` needs: one per entry, the processing time
+/// when logged, and the borders
+fn chain_timeline_height(group: &AuditGroup) -> usize {
+    group.entries.len() + usize::from(group.processing_time().is_some()) + 2
+}
+
+/// Waterfall of the chain's entries: their sections, time since the first
+/// timestamp, and a bar from there to the next timestamped entry.
+/// Continuation parts have no timestamp of their own and get no bar.
+/// Timestamps have second resolution, so near-instant chains are all `+0s`;
+/// the Stopwatch line below is the finer measure.
+fn draw_chain_timeline(f: &mut Frame, area: Rect, group: &AuditGroup, c: &ColorScheme) {
+    let offsets = group.entry_offsets();
+    let span = offsets.iter().flatten().max().copied().unwrap_or_default();
+
+    let label_width = 8 + 8; // Sections, offset
+    let bar_width = (area.width.saturating_sub(2) as usize).saturating_sub(label_width).max(1);
+    let column = |at: Duration| {
+        if span.is_zero() {
+            0
+        } else {
+            ((at.as_secs_f64() / span.as_secs_f64()) * (bar_width - 1) as f64).round() as usize
+        }
+    };
+
+    let mut lines = Vec::new();
+    for (i, entry) in group.entries.iter().enumerate() {
+        let sections: String = entry.raw_content.lines().filter_map(section_marker).collect();
+        let mut spans = vec![Span::styled(format!("{:<8}", ellipsize(&sections, 7)), Style::default().fg(c.boundary))];
+        match offsets[i] {
+            Some(offset) => {
+                let next = offsets[i + 1..].iter().flatten().next().copied().unwrap_or(offset);
+                let (from, to) = (column(offset), column(next.max(offset)));
+                spans.push(Span::styled(format!("{:<8}", format!("+{}s", offset.as_secs())), Style::default().fg(c.timestamp)));
+                spans.push(Span::raw(" ".repeat(from)));
+                spans.push(Span::styled("█".repeat(to - from + 1), Style::default().fg(c.timestamp)));
+            }
+            None => spans.push(Span::styled("-       (continuation part)", Style::default().fg(c.help_text))),
+        }
+        lines.push(Line::from(spans));
+    }
+    if let Some(time) = group.processing_time() {
+        let ms = time.as_secs_f64() * 1000.0;
+        lines.push(Line::from(vec![
+            Span::styled("ModSecurity processing: ", Style::default().fg(c.label)),
+            Span::raw(if ms < 1000.0 { format!("{:.3} ms", ms) } else { format!("{:.3} s", ms / 1000.0) }),
+            Span::styled(" (Stopwatch)", Style::default().fg(c.help_text)),
+        ]));
+    }
+
+    let title = format!(" Timeline (span {}s) ", span.as_secs());
+    let timeline = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(timeline, area);
+}
+
+/// Chunks of raw audit log text, each starting at a section boundary line
+fn split_sections(content: &str) -> Vec<&str> {
+    let mut starts = Vec::new();
+    let mut pos = 0;
+    for line in content.split_inclusive('\n') {
+        if pos == 0 || section_marker(line.trim_end()).is_some() {
+            starts.push(pos);
+        }
+        pos += line.len();
+    }
+    starts.push(content.len());
+    starts.windows(2).map(|w| &content[w[0]..w[1]]).filter(|chunk| !chunk.is_empty()).collect()
+}
+
+/// Render `content` chunk by chunk (see `split_sections`) with `render`,
+/// except that sections whose body looks binary in the undecoded `bytes`
+/// become a hex dump below their boundary line
+fn with_hexdump<'a>(
+    content: &'a str,
+    bytes: &[u8],
+    c: &ColorScheme,
+    sections: Option<&'a [char]>,
+    render: impl Fn(&'a str) -> Vec<Line<'a>>,
+) -> Vec<Line<'a>> {
+    // Decoding keeps line breaks, so text and byte lines correspond
+    let byte_lines: Vec<&[u8]> = bytes.split(|&b| b == b'\n').collect();
+    let mut lines = Vec::new();
+    let mut line_no = 0;
+    for chunk in split_sections(content) {
+        let count = chunk.split_inclusive('\n').count();
+        let marker = chunk.lines().next().unwrap_or_default();
+        let mut body_lines = byte_lines.get(line_no + 1..line_no + count).unwrap_or_default();
+        // Not the blank line that separates sections
+        if body_lines.last().is_some_and(|line| line.is_empty()) {
+            body_lines = &body_lines[..body_lines.len() - 1];
+        }
+        let body = body_lines.join(&b'\n');
+        line_no += count;
+
+        match section_marker(marker).filter(|_| looks_binary(&body)) {
+            Some(section) if sections.is_none_or(|sections| sections.contains(&section)) => {
+                lines.extend(colorize_content(marker, c, None));
+                lines.extend(hexdump(&body, c));
+                lines.push(Line::from(""));
+            }
+            Some(_) => {}
+            None => lines.extend(render(chunk)),
+        }
+    }
+    lines
+}
+
+/// Classic hex dump, 16 bytes per row: offset, the bytes in two groups of
+/// eight and their printable ASCII (`.` for the rest). Stops after
+/// `HEXDUMP_MAX_BYTES`, as a dump is four lines per 64 bytes.
+fn hexdump(bytes: &[u8], c: &ColorScheme) -> Vec<Line<'static>> {
+    const HEXDUMP_MAX_BYTES: usize = 64 * 1024;
+
+    let shown = &bytes[..bytes.len().min(HEXDUMP_MAX_BYTES)];
+    let mut lines: Vec<Line> = shown
+        .chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let mut hex = String::with_capacity(49);
+            for i in 0..16 {
+                match chunk.get(i) {
+                    Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                    None => hex.push_str("   "),
+                }
+                if i == 7 {
+                    hex.push(' ');
+                }
+            }
+            let ascii: String = chunk.iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            Line::from(vec![
+                Span::styled(format!("{:08x}  ", row * 16), Style::default().fg(c.boundary)),
+                Span::raw(hex),
+                Span::styled(format!(" |{}|", ascii), Style::default().fg(c.label)),
+            ])
+        })
+        .collect();
+    if bytes.len() > shown.len() {
+        lines.push(Line::from(Span::styled(
+            format!("… {} more bytes (r: raw text)", bytes.len() - shown.len()),
+            Style::default().fg(c.help_text),
+        )));
+    }
+    lines
+}
+
+/// `colorize_content`, except that the request headers of section B are laid
+/// out as an aligned name/value table. The request line and the body render
+/// as usual.
+fn colorize_with_header_table<'a>(content: &'a str, c: &ColorScheme, sections: Option<&'a [char]>) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+    for chunk in split_sections(content) {
+        let (marker, body) = chunk.split_once('\n').unwrap_or((chunk, ""));
+        let is_request = section_marker(marker) == Some('B');
+        if !is_request || sections.is_some_and(|sections| !sections.contains(&'B')) {
+            lines.extend(colorize_content(chunk, c, sections));
+            continue;
+        }
+
+        lines.extend(colorize_content(marker, c, None));
+        let width = parse_headers(body).iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+        let mut in_headers = true;
+        for (i, line) in body.lines().enumerate() {
+            if line.trim().is_empty() {
+                in_headers = false;
+                lines.push(Line::from(""));
+                continue;
+            }
+            match split_header(line).filter(|_| in_headers && i > 0) {
+                Some((name, value)) => lines.push(Line::from(vec![
+                    Span::styled(format!("{:<width$}  ", name), Style::default().fg(c.label).add_modifier(Modifier::BOLD)),
+                    Span::styled(value, Style::default().fg(c.host_header)),
+                ])),
+                None => lines.extend(colorize_content(line, c, None)),
+            }
+        }
+    }
+    lines
+}
+
 fn colorize_json<'a>(json: &'a str, c: &ColorScheme) -> Vec<Line<'a>> {
     let mut lines = Vec::new();
 
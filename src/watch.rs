@@ -0,0 +1,115 @@
+//! `--watch-dir`: find the log to follow in a directory of rotating logs.
+//!
+//! The newest file whose name matches a glob pattern is the live log. A file
+//! is told apart from the one that replaced it under the same name by its
+//! identity (device and inode on Unix), which survives a rename.
+
+use anyhow::{Context, Result};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `--pattern` when not given: every file in the directory
+pub const DEFAULT_PATTERN: &str = "*";
+
+/// A directory and the file name pattern of the logs in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchDir {
+    pub dir: PathBuf,
+    pub pattern: String,
+}
+
+impl WatchDir {
+    pub fn new(dir: impl Into<PathBuf>, pattern: impl Into<String>) -> Self {
+        Self { dir: dir.into(), pattern: pattern.into() }
+    }
+
+    /// Files in the directory whose name matches the pattern, unordered
+    pub fn matches(&self) -> Result<Vec<PathBuf>> {
+        let read_error = || format!("Failed to read directory {}", self.dir.display());
+        let mut files = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir).with_context(read_error)? {
+            let dir_entry = dir_entry.with_context(read_error)?;
+            let name = dir_entry.file_name();
+            let is_match = name.to_str().is_some_and(|name| glob_matches(&self.pattern, name));
+            if is_match && dir_entry.path().is_file() {
+                files.push(dir_entry.path());
+            }
+        }
+        Ok(files)
+    }
+
+    /// The most recently modified match. On a tie the shorter, then
+    /// alphabetically first name wins (`audit.log` over `audit.log.1`).
+    pub fn newest(&self) -> Result<Option<PathBuf>> {
+        Ok(self.matches()?
+            .into_iter()
+            .filter_map(|path| {
+                let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                Some((modified, path))
+            })
+            .max_by_key(|(modified, path)| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                (*modified, Reverse(name.len()), Reverse(name))
+            })
+            .map(|(_, path)| path))
+    }
+
+    /// The match that is the file `id` (see `file_id`), e.g. the old log
+    /// after rotation renamed it
+    pub fn find(&self, id: FileId) -> Result<Option<PathBuf>> {
+        Ok(self.matches()?.into_iter().find(|path| file_id(path) == Some(id)))
+    }
+}
+
+/// Device and inode of a file
+pub type FileId = (u64, u64);
+
+/// Identity of the file at `path`. `None` when it is missing, and always on
+/// platforms without inodes, where rotation is only noticed by the name or
+/// the size going down.
+pub fn file_id(path: &Path) -> Option<FileId> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = fs::metadata(path).ok()?;
+        Some((metadata.dev(), metadata.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Shell-style match of a whole file name: `*` is any run of characters,
+/// `?` any one character, everything else matches itself
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was and how much of the name it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the `*` take one more character and try again
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,823 @@
+use waflens::app::{fired_rule, format_duration, ip_lookup_url, ip_rates, is_error_status, parse_columns, parse_duration, sorted_rule_ids, App, AppOptions, AppView, Column, DEFAULT_IP_URL_TEMPLATE, AUDIT_SECTIONS, DETAIL_HSCROLL_STEP, MIN_COLUMN_WIDTH};
+use waflens::ipapi::IpApiResponse;
+use waflens::parser::AuditLogParser;
+use waflens::watch::WatchDir;
+
+const MULTIPART: &str = include_str!("fixtures/multipart.log");
+const IPV6: &str = include_str!("fixtures/ipv6.log");
+
+fn app_with(content: &str) -> App {
+    let groups = AuditLogParser::new().parse_str(content);
+    App::from_groups(groups, &["test.log"], &AppOptions::default())
+}
+
+/// Draw the app on a `width` x `height` test terminal and return the screen text
+fn render(app: &mut App, width: u16, height: u16) -> String {
+    use ratatui::{backend::TestBackend, Terminal};
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| waflens::ui::draw(f, app)).unwrap();
+    terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+}
+
+#[test]
+fn rolls_up_groups_by_domain() {
+    let content = format!("{}{}", MULTIPART, IPV6.replace("wiki.example.org", "shop.example.com"));
+    let app = app_with(&content);
+    let summaries = app.group_by_domain();
+
+    assert_eq!(summaries.len(), 3);
+    let shop = &summaries[0];
+    assert_eq!(shop.domain, "shop.example.com");
+    assert_eq!(shop.hits, 2);
+    assert_eq!(shop.distinct_ips, 2);
+    // 3002 and 920350 both fire once per group; ties go to the lower ID
+    assert_eq!(shop.top_rule.as_deref(), Some("3002"));
+}
+
+#[test]
+fn breaks_down_one_rule_by_domain_and_ip() {
+    let content = format!("{}{}", MULTIPART, IPV6.replace("wiki.example.org", "shop.example.com"));
+    let app = app_with(&content);
+    let stats = app.stats_for_rule("3002");
+
+    assert_eq!(stats.transactions, 1);
+    assert_eq!(stats.blocked, 1);
+    assert_eq!(stats.domains.len(), 1);
+    assert_eq!(stats.domains[0].domain, "shop.example.com");
+    // One of the two shop.example.com transactions fired it
+    assert_eq!(stats.domains[0].share, 0.5);
+    assert_eq!(stats.client_ips[0].client_ip, "2a03:2880:f800:23::");
+    // Rule IDs match exactly, unlike `rule:`
+    assert_eq!(app.stats_for_rule("300").transactions, 0);
+    assert!(!fired_rule(&app.audit_groups[0], "300"));
+}
+
+#[test]
+fn sorts_rule_ids_numerically_for_display_only() {
+    let ids: Vec<String> = ["949110", "my-rule", "942100", "920280"].map(String::from).to_vec();
+    assert_eq!(sorted_rule_ids(&ids), ["920280", "942100", "949110", "my-rule"]);
+
+    let groups = AuditLogParser::new().parse_str(&MULTIPART.replace("949110", "900990"));
+    let options = AppOptions { columns: Some(vec![Column::RuleIds]), ..AppOptions::default() };
+    let mut app = App::from_groups(groups, &["test.log"], &options);
+    app.search_query = "rule:942100".to_string();
+    app.apply_search();
+    assert_eq!(app.export_markdown(), "| Rule IDs |\n| --- |\n| 942100, 900990 |\n");
+    app.toggle_sort_rule_ids();
+    assert_eq!(app.export_markdown(), "| Rule IDs |\n| --- |\n| 900990, 942100 |\n");
+    // The first rule that fired stays first underneath
+    assert_eq!(app.selected_group().unwrap().primary_rule_ids[0], "942100");
+}
+
+#[test]
+fn exports_the_visible_columns_as_markdown() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let options = AppOptions { columns: Some(vec![Column::Domain, Column::Status, Column::RuleIds]), ..AppOptions::default() };
+    let mut app = App::from_groups(groups, &["test.log"], &options);
+    app.search_query = "status:403".to_string();
+    app.apply_search();
+
+    assert_eq!(
+        app.export_markdown(),
+        "| Domain | Status | Rule IDs |\n| --- | --- | --- |\n| blog.example.org | 403 ✖ | 942100, 949110 |\n"
+    );
+}
+
+#[test]
+fn exports_only_the_marked_rows_when_any() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    assert_eq!(app.export_groups().len(), 4);
+
+    app.toggle_select_mode();
+    app.toggle_mark();
+    assert_eq!(app.selected_index, 1);
+    assert!(app.is_row_marked(0));
+    // Marks survive a search that hides them
+    app.search_query = "status:403".to_string();
+    app.apply_search();
+    let exported: Vec<&str> = app.export_groups().iter().map(|g| g.base_id.as_str()).collect();
+    assert_eq!(exported, ["b44f0e1d22aa9c10"]);
+    assert_eq!(app.export_markdown().lines().count(), 3);
+
+    app.clear_marks();
+    assert!(!app.select_mode);
+    assert_eq!(app.export_groups().len(), 1);
+}
+
+#[test]
+fn the_selected_row_is_always_drawn() {
+    let mut app = app_with(&format!("{}{}{}", MULTIPART, IPV6, include_str!("fixtures/scan.log")));
+    // Terminals of any height, not just what the keys assumed before a draw
+    for height in [14, 17, 40] {
+        app.selected_index = app.visible_groups().len() - 1;
+        app.scroll_offset = 0;
+        let screen = render(&mut app, 120, height);
+
+        let selected = app.selected_group().unwrap().base_id.clone();
+        assert!(screen.contains(&selected), "height {}: {} not drawn", height, selected);
+        assert!(app.selected_index < app.scroll_offset + app.visible_height);
+    }
+}
+
+#[test]
+fn the_searched_text_stands_out_in_its_column() {
+    use ratatui::{backend::TestBackend, Terminal};
+    let mut app = app_with(MULTIPART);
+    app.search_query = "domain:SHOP".to_string();
+    app.apply_search();
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    terminal.draw(|f| waflens::ui::draw(f, &mut app)).unwrap();
+
+    // The row's domain cell: `shop` in the highlight, `.example.com` not
+    let buffer = terminal.backend().buffer();
+    let cells = buffer.content();
+    let row = (0..buffer.area.height)
+        .find(|&y| {
+            let line: String = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+            line.contains("shop.example.com") && line.contains("1a2b3c4d")
+        })
+        .unwrap();
+    let line: String = (0..buffer.area.width).map(|x| buffer[(x, row)].symbol()).collect();
+    let x = line[..line.find("shop.example.com").unwrap()].chars().count() as u16;
+    let fg = |x: u16| cells[(row * buffer.area.width + x) as usize].fg;
+    assert_eq!(fg(x), fg(x + 3));
+    assert_ne!(fg(x), fg(x + 4));
+}
+
+#[test]
+fn stats_rows_drill_into_the_table() {
+    let mut app = app_with(MULTIPART);
+    app.show_stats_view();
+    let pivots = app.stats_pivots();
+    assert_eq!(pivots[0], ("status", "2xx".to_string()));
+    let (field, last) = pivots.last().unwrap().clone();
+    assert_eq!(field, "domain");
+
+    // The cursor stops at the last row and the view scrolls to it
+    app.move_stats_selection(isize::MAX);
+    assert_eq!(app.stats_selected, pivots.len() - 1);
+    let screen = render(&mut app, 80, 14);
+    assert!(app.stats_scroll > 0);
+    assert!(screen.contains(&last));
+
+    let rule = pivots.iter().position(|pivot| *pivot == ("rule", "942100".to_string())).unwrap();
+    app.move_stats_selection(isize::MIN);
+    app.move_stats_selection(rule as isize);
+    app.drill_into_stats();
+    assert_eq!(app.current_view, AppView::TableView);
+    assert_eq!(app.search_query, "rule=942100");
+    assert_eq!(app.visible_groups().len(), 1);
+
+    // Every row drills into exactly as many transactions as it counts, also
+    // next to an IP and categories that share a prefix with another row's
+    let variant = |id: &str, ip: &str, rule: &str| {
+        MULTIPART.replace("5e6f7a8b", id).replace("198.51.100.23 ", ip).replace("942100", rule)
+    };
+    let log = format!(
+        "{}{}{}",
+        MULTIPART,
+        variant("5e6f7a8c", "198.51.100.230 ", "951120"),
+        variant("5e6f7a8d", "198.51.100.23 ", "950100")
+    );
+    let mut app = app_with(&log);
+    app.show_stats_view();
+    let stats = app.stats.clone();
+    let counts = [
+        ("status", &stats.status_classes),
+        ("ip", &stats.top_ips),
+        ("rule", &stats.top_rules),
+        ("cat", &stats.top_categories),
+        ("domain", &stats.top_domains),
+    ];
+    for (row, (field, value)) in app.stats_pivots().into_iter().enumerate() {
+        let (_, rows) = counts.iter().find(|(name, _)| *name == field).unwrap();
+        let count = rows.iter().find(|(key, _)| *key == value).unwrap().1;
+        app.search_query.clear();
+        app.apply_search();
+        app.show_stats_view();
+        app.stats_selected = row;
+        app.drill_into_stats();
+        assert_eq!(app.visible_groups().len(), count, "{}={}", field, value);
+    }
+}
+
+#[test]
+fn a_tiny_terminal_gets_a_message_instead_of_the_view() {
+    use waflens::ui::{MIN_HEIGHT, MIN_WIDTH};
+    let mut app = app_with(MULTIPART);
+    for (width, height) in [(MIN_WIDTH - 1, 30), (120, MIN_HEIGHT - 1), (12, 3)] {
+        let screen = render(&mut app, width, height);
+        assert!(screen.contains("small"), "{}x{}: {}", width, height, screen);
+        assert!(!screen.contains("Entries"));
+    }
+    // At the minimum the table has room for a row
+    assert!(render(&mut app, MIN_WIDTH, MIN_HEIGHT).contains("Entries (2)"));
+    assert_eq!(app.table_body().unwrap().height, 1);
+}
+
+#[test]
+fn resizing_keeps_the_selection_on_screen() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    app.selected_index = 3;
+    app.scroll_offset = 0;
+
+    // Shorter: scroll down just enough to show the selected row
+    app.visible_height = 2;
+    app.clamp_scroll();
+    assert_eq!((app.selected_index, app.scroll_offset), (3, 2));
+
+    // Taller: scroll back up rather than leave rows empty
+    app.visible_height = 20;
+    app.clamp_scroll();
+    assert_eq!((app.selected_index, app.scroll_offset), (3, 0));
+}
+
+#[test]
+fn parses_short_durations() {
+    use std::time::Duration;
+    assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+    assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(30 * 60));
+    assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+    assert_eq!(parse_duration("2d").unwrap(), Duration::from_secs(2 * 86400));
+    for bad in ["", "h", "1", "1y", "-1h", "1.5h"] {
+        assert!(parse_duration(bad).is_err(), "{:?}", bad);
+    }
+}
+
+#[test]
+fn formats_durations_like_they_are_parsed() {
+    for text in ["500ms", "1s", "90s", "5m", "2h"] {
+        assert_eq!(format_duration(parse_duration(text).unwrap()), text);
+    }
+}
+
+#[test]
+fn follow_reloads_only_when_the_log_changed() {
+    let path = std::env::temp_dir().join(format!("waflens-follow-{}.log", std::process::id()));
+    std::fs::write(&path, MULTIPART).unwrap();
+    let mut app = App::from_groups(Vec::new(), &[path.to_str().unwrap()], &AppOptions::default());
+
+    assert!(app.poll_log().unwrap());
+    assert_eq!(app.audit_groups.len(), 2);
+    assert!(!app.poll_log().unwrap());
+
+    std::fs::write(&path, format!("{}{}", MULTIPART, IPV6)).unwrap();
+    assert!(app.poll_log().unwrap());
+    assert_eq!(app.audit_groups.len(), 4);
+
+    // A rewritten log (rotation) is re-parsed rather than appended to
+    std::fs::write(&path, format!("{}{}", IPV6, IPV6.replace("a3734c1a04ffce27", "c0ffee0000000001"))).unwrap();
+    assert!(app.poll_log().unwrap());
+    assert_eq!(app.audit_groups.len(), 3);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn appended_entries_are_parsed_with_the_same_options() {
+    let path = std::env::temp_dir().join(format!("waflens-append-{}.log", std::process::id()));
+    std::fs::write(&path, MULTIPART).unwrap();
+    let options = AppOptions { trusted_ip_headers: vec!["X-Real-IP".to_string()], ..AppOptions::default() };
+    let mut app = App::from_groups(Vec::new(), &[path.to_str().unwrap()], &options);
+    assert!(app.poll_log().unwrap());
+
+    let appended = IPV6.replace("Host: www.example.net", "Host: www.example.net\nX-Real-IP: 8.8.4.4");
+    std::fs::write(&path, format!("{}{}", MULTIPART, appended)).unwrap();
+    assert!(app.poll_log().unwrap());
+    assert!(app.audit_groups.iter().any(|g| g.domain == "www.example.net" && g.client_ip == "8.8.4.4"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn watch_dir_switches_to_the_new_log_and_keeps_the_old_entries() {
+    use std::io::Write;
+    let dir = std::env::temp_dir().join(format!("waflens-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("audit.log");
+    let append = |path: &std::path::Path, content: &[u8]| {
+        std::fs::OpenOptions::new().append(true).open(path).unwrap().write_all(content).unwrap();
+    };
+    std::fs::write(&log, MULTIPART).unwrap();
+    let options = AppOptions { watch: Some(WatchDir::new(&dir, "audit.log*")), ..AppOptions::default() };
+    let mut app = App::from_groups(Vec::new(), &[log.to_str().unwrap()], &options);
+    assert!(app.poll_log().unwrap());
+    assert_eq!(app.audit_groups.len(), 2);
+
+    // Rotation renames the log, which still gets the last writes, and
+    // starts a new one under the name
+    std::fs::rename(&log, dir.join("audit.log.1")).unwrap();
+    append(&dir.join("audit.log.1"), IPV6.as_bytes());
+    std::fs::write(dir.join("notes.txt"), "not a log").unwrap();
+    std::fs::write(&log, include_bytes!("fixtures/binary.log")).unwrap();
+    assert!(app.poll_log().unwrap());
+    assert_eq!(app.audit_groups.len(), 5);
+    assert!(app.status_message.as_deref().unwrap().contains("rotated"));
+    assert!(!app.poll_log().unwrap());
+
+    // The new log is followed like any other
+    append(&log, include_bytes!("fixtures/scan.log"));
+    assert!(app.poll_log().unwrap());
+    assert_eq!(app.audit_groups.len(), 8);
+
+    // Truncated in place (copytruncate): nothing read so far is lost
+    std::fs::write(&log, include_str!("fixtures/bodies.log")).unwrap();
+    assert!(app.poll_log().unwrap());
+    assert_eq!(app.audit_groups.len(), 9);
+    assert_eq!(app.log_paths, [log.to_str().unwrap()]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn since_hides_older_groups_and_survives_clearing_search() {
+    let mut groups = AuditLogParser::new().parse_str(MULTIPART);
+    groups[0].first_timestamp = chrono::Utc::now();
+    let recent = groups[0].base_id.clone();
+    let options = AppOptions { since: Some(parse_duration("1h").unwrap()), ..AppOptions::default() };
+    let mut app = App::from_groups(groups, &["test.log"], &options);
+
+    assert_eq!(app.visible_groups().len(), 1);
+    assert_eq!(app.visible_groups()[0].base_id, recent);
+    app.search_query = "status:403".to_string();
+    app.apply_search();
+    app.clear_search();
+    assert_eq!(app.visible_groups().len(), 1);
+
+    // Launched two hours ago: a refresh measures the hour from now, so a
+    // group that was inside the window then falls out of it
+    app.since = Some(chrono::Utc::now() - chrono::Duration::hours(3));
+    let mut groups = app.audit_groups.clone();
+    groups.iter_mut().find(|g| g.base_id == recent).unwrap().first_timestamp = chrono::Utc::now() - chrono::Duration::hours(2);
+    app.replace_groups(groups);
+    assert!(app.visible_groups().is_empty());
+}
+
+#[test]
+fn drilling_into_domain_filters_table() {
+    let mut app = app_with(MULTIPART);
+    app.show_domain_view();
+    assert_eq!(app.current_view, AppView::DomainView);
+
+    let target = app.domain_summaries[1].domain.clone();
+    app.move_domain_selection(1, 20);
+    app.drill_into_domain();
+
+    assert_eq!(app.current_view, AppView::TableView);
+    assert_eq!(app.search_query, format!("domain={}", target));
+    assert_eq!(app.visible_groups().len(), 1);
+    assert_eq!(app.visible_groups()[0].domain, target);
+}
+
+#[test]
+fn pivots_from_detail_to_the_ip_footprint() {
+    let scan = include_str!("fixtures/scan.log");
+    // An address and a host that merely start with the pivot values
+    let neighbour = MULTIPART.replace("5e6f7a8b", "5e6f7a8c")
+        .replace("198.51.100.23 ", "198.51.100.230 ")
+        .replace("Host: blog.example.org", "Host: wiki.example.org.evil");
+    let mut app = app_with(&format!("{}{}{}{}", MULTIPART, IPV6, scan, neighbour));
+    let target = app.visible_groups().iter().position(|g| g.base_id == "aa000002").unwrap();
+    app.selected_index = target;
+    app.show_detail_view();
+
+    app.pivot_on_selected("ip");
+    assert_eq!(app.current_view, AppView::TableView);
+    assert_eq!(app.search_query, "ip=198.51.100.23");
+    // Three scan requests plus the multipart chain from the same address
+    assert_eq!(app.visible_groups().len(), 4);
+    assert!(app.visible_groups().iter().all(|g| g.client_ip == "198.51.100.23"));
+    assert_eq!(app.selected_group().unwrap().base_id, "aa000002");
+
+    app.pivot_on_selected("rule");
+    assert_eq!(app.search_query, "rule=942100");
+    app.pivot_to("domain", "wiki.example.org");
+    assert_eq!(app.visible_groups().len(), 1);
+    assert_eq!(app.visible_groups()[0].domain, "wiki.example.org");
+}
+
+#[test]
+fn page_down_keeps_selection_in_view() {
+    let content = format!("{}{}", MULTIPART, IPV6);
+    let mut app = app_with(&content);
+    let last = app.filtered_groups.len() - 1;
+
+    app.page_down(20);
+    app.page_down(20);
+    assert_eq!(app.selected_index, last);
+    assert!(app.scroll_offset <= app.selected_index);
+}
+
+#[test]
+fn refresh_keeps_selection_on_same_transaction() {
+    let mut app = app_with(MULTIPART);
+    app.selected_index = 1;
+    let base_id = app.selected_group().unwrap().base_id.clone();
+
+    // Newer entries sort first, shifting the old index
+    let newer = IPV6.replace("[17/Nov/2025", "[17/Nov/2030");
+    let groups = AuditLogParser::new().parse_str(&format!("{}{}", MULTIPART, newer));
+    app.replace_groups(groups);
+
+    assert_eq!(app.selected_group().unwrap().base_id, base_id);
+    assert_ne!(app.selected_index, 1);
+}
+
+#[test]
+fn navigation_is_safe_when_nothing_matches() {
+    let mut app = app_with(MULTIPART);
+    app.search_query = "domain:nomatch".to_string();
+    app.apply_search();
+
+    app.move_selection_down(20);
+    app.page_down(20);
+    app.move_selection_up();
+    app.show_detail_view();
+
+    assert_eq!(app.selected_index, 0);
+    assert_eq!(app.scroll_offset, 0);
+    assert_eq!(app.current_view, AppView::TableView);
+    assert!(app.status_message.is_some());
+}
+
+#[test]
+fn computes_stats_for_filtered_groups() {
+    let content = format!("{}{}", MULTIPART, IPV6);
+    let mut app = app_with(&content);
+    let stats = app.compute_stats();
+
+    assert_eq!(stats.total, 4);
+    assert_eq!(stats.status_classes[0], ("2xx".to_string(), 2));
+    assert_eq!(stats.top_ips.len(), 4);
+    assert_eq!(stats.unresolved_geo, 4);
+    assert!(stats.top_asns.is_empty());
+
+    app.search_query = "status:403".to_string();
+    app.apply_search();
+    app.show_stats_view();
+    assert_eq!(app.current_view, AppView::StatsView);
+    assert_eq!(app.stats.total, 1);
+    assert_eq!(app.stats.top_rules, vec![("942100".to_string(), 1), ("949110".to_string(), 1)]);
+    assert_eq!(app.stats.top_categories, vec![("sqli".to_string(), 1)]);
+}
+
+#[test]
+fn fills_ip_url_template() {
+    assert_eq!(ip_lookup_url(DEFAULT_IP_URL_TEMPLATE, "203.0.113.7"), "https://ip-api.com/#203.0.113.7");
+    assert_eq!(
+        ip_lookup_url("https://www.abuseipdb.com/check/{ip}", "2001:db8::1"),
+        "https://www.abuseipdb.com/check/2001:db8::1"
+    );
+}
+
+#[test]
+fn detail_scroll_is_remembered_per_group() {
+    let mut app = app_with(MULTIPART);
+    app.show_detail_view();
+    // As measured by the last draw
+    app.detail_line_count = 50;
+    app.detail_viewport = 20;
+    app.page_detail_down(10);
+
+    app.step_detail_entry(true);
+    assert_eq!(app.selected_index, 1);
+    assert_eq!(app.detail_scroll, 0);
+
+    app.step_detail_entry(false);
+    assert_eq!(app.selected_index, 0);
+    assert_eq!(app.detail_scroll, 10);
+
+    // Back from the table, the same transaction keeps its place
+    app.show_table_view();
+    app.show_detail_view();
+    assert_eq!(app.detail_scroll, 10);
+
+    // Another one starts at the top
+    app.show_table_view();
+    app.move_selection_down(20);
+    app.show_detail_view();
+    assert_eq!(app.detail_scroll, 0);
+}
+
+#[test]
+fn detail_reading_mode_survives_the_table() {
+    let mut app = app_with(MULTIPART);
+    app.show_detail_view();
+    // Lines wrap until told otherwise; sideways scrolling needs them cut
+    app.scroll_detail_sideways(DETAIL_HSCROLL_STEP);
+    assert_eq!(app.detail_hscroll, 0);
+    app.toggle_detail_wrap();
+    app.scroll_detail_sideways(DETAIL_HSCROLL_STEP);
+    app.scroll_detail_sideways(DETAIL_HSCROLL_STEP);
+    app.scroll_detail_sideways(-DETAIL_HSCROLL_STEP);
+    assert_eq!(app.detail_hscroll, DETAIL_HSCROLL_STEP as usize);
+    app.toggle_detail_raw();
+
+    app.show_table_view();
+    app.move_selection_down(20);
+    app.show_detail_view();
+    assert!(!app.detail_wrap);
+    assert!(app.detail_raw);
+    assert_eq!(app.detail_hscroll, DETAIL_HSCROLL_STEP as usize);
+
+    app.toggle_detail_wrap();
+    assert_eq!((app.detail_wrap, app.detail_hscroll), (true, 0));
+}
+
+#[test]
+fn collapses_similar_transactions() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, include_str!("fixtures/scan.log")));
+    let similar = app.collapse_similar();
+    assert_eq!(similar.len(), 2);
+    // Same IP, rules and path; the query string differs
+    assert_eq!(similar[0].count(), 4);
+    assert_eq!(similar[0].key.path.as_deref(), Some("/index.php"));
+    assert!(similar[0].first_seen < similar[0].last_seen);
+    assert_eq!(similar[1].count(), 1);
+
+    app.toggle_collapse();
+    assert_eq!(app.visible_groups().len(), 2);
+    assert_eq!(app.selected_group().unwrap().base_id, "aa000003");
+    // Stats still count every transaction behind the rows
+    assert_eq!(app.compute_stats().total, 5);
+
+    assert!(app.expand_similar());
+    assert_eq!(app.visible_groups().len(), 4);
+    app.move_selection_down(10);
+    assert!(app.close_similar());
+    assert_eq!(app.visible_groups().len(), 2);
+    assert_eq!(app.selected_index, 0);
+
+    app.toggle_collapse();
+    assert_eq!(app.visible_groups().len(), 5);
+}
+
+#[test]
+fn detail_section_filter_toggles_and_resets_scroll() {
+    let mut app = app_with(MULTIPART);
+    app.show_detail_view();
+    app.detail_line_count = 50;
+    app.detail_viewport = 20;
+    app.page_detail_down(10);
+    assert_eq!(app.detail_scroll, 10);
+    app.toggle_detail_sections();
+    assert_eq!(app.detail_sections_filter, Some(AUDIT_SECTIONS));
+    assert_eq!(app.detail_scroll, 0);
+    app.toggle_detail_sections();
+    assert_eq!(app.detail_sections_filter, None);
+}
+
+#[test]
+fn raw_detail_mode_toggles_and_resets_scroll() {
+    let mut app = app_with(MULTIPART);
+    app.show_detail_view();
+    app.detail_line_count = 50;
+    app.detail_viewport = 20;
+    app.page_detail_down(10);
+    app.toggle_detail_raw();
+    assert!(app.detail_raw);
+    assert_eq!(app.detail_scroll, 0);
+    app.toggle_detail_raw();
+    assert!(!app.detail_raw);
+}
+
+#[test]
+fn a_cache_miss_is_drawn_as_fetching_before_the_lookup() {
+    let mut app = app_with(&MULTIPART.replace("203.0.113.7", "8.8.4.4").replace("198.51.100.23", "8.8.4.4"));
+    app.ip_api_enabled = true;
+    app.show_detail_view();
+    assert!(app.ip_info_pending);
+
+    assert!(render(&mut app, 200, 60).contains("Fetching geolocation for 8.8.4.4"));
+
+    // Answered from the cache here, so the test makes no request
+    app.ip_api_cache.insert("8.8.4.4", IpApiResponse::failed("8.8.4.4", "reserved range"));
+    app.load_pending_ip_info();
+    assert!(!app.ip_info_pending);
+    assert!(app.current_ip_info.is_some());
+    app.load_pending_ip_info(); // Nothing left to do
+
+    // A cached subnet needs no request, so there is nothing to wait for
+    app.show_table_view();
+    app.show_detail_view();
+    assert!(!app.ip_info_pending);
+    assert!(app.current_ip_info.is_some());
+}
+
+#[test]
+fn disabling_ip_api_clears_shown_info() {
+    let mut app = app_with(MULTIPART);
+    app.ip_api_enabled = true;
+    app.current_ip_info = Some("{}".to_string());
+    app.current_ptr = Some("host.example".to_string());
+    app.toggle_ip_api();
+    assert!(!app.ip_api_enabled);
+    assert_eq!(app.current_ip_info, None);
+    assert_eq!(app.current_ptr, None);
+    assert_eq!(app.status_message.as_deref(), Some("IP lookups disabled"));
+}
+
+#[test]
+fn detail_scroll_stops_at_the_last_page() {
+    let mut app = app_with(MULTIPART);
+    app.show_detail_view();
+    app.detail_line_count = 25;
+    app.detail_viewport = 20;
+    app.page_detail_down(10);
+    assert_eq!(app.detail_scroll, 5);
+    app.scroll_detail_down();
+    assert_eq!(app.detail_scroll, 5);
+    // No overshoot to work back through
+    app.scroll_detail_up();
+    assert_eq!(app.detail_scroll, 4);
+    app.scroll_detail_end();
+    assert_eq!(app.detail_scroll, 5);
+}
+
+#[test]
+fn double_click_needs_the_same_row_within_the_window() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    app.double_click_ms = 300;
+    let start = std::time::Instant::now();
+    let at = |ms| start + std::time::Duration::from_millis(ms);
+
+    assert!(!app.handle_click_at(0, 10, at(0)));
+    assert!(!app.handle_click_at(1, 10, at(100)));
+    assert!(app.handle_click_at(1, 10, at(200)));
+    // The third click of a triple-click starts over
+    assert!(!app.handle_click_at(1, 10, at(250)));
+    assert!(!app.handle_click_at(1, 10, at(600)));
+}
+
+#[test]
+fn mouse_passthrough_releases_capture_in_the_table() {
+    let mut app = app_with(MULTIPART);
+    assert!(app.wants_mouse());
+    app.toggle_mouse_passthrough();
+    assert!(!app.wants_mouse());
+    app.toggle_mouse_passthrough();
+    assert!(app.wants_mouse());
+    app.show_detail_view();
+    assert!(!app.wants_mouse());
+}
+
+#[test]
+fn parts_layout_selects_and_expands_one_entry() {
+    let mut app = app_with(MULTIPART);
+    app.search_query = "auditid:1a2b".to_string();
+    app.apply_search();
+    app.show_detail_view();
+    app.toggle_detail_parts();
+    assert!(app.detail_parts);
+
+    app.move_detail_part(true);
+    app.move_detail_part(true);
+    // The chain has two parts; the selection stops at the last
+    assert_eq!(app.detail_part, 1);
+    app.toggle_detail_part_open();
+    assert_eq!(app.detail_part_open, Some(1));
+    app.move_detail_part(false);
+    app.toggle_detail_part_open();
+    assert_eq!(app.detail_part_open, Some(0));
+    app.toggle_detail_part_open();
+    assert_eq!(app.detail_part_open, None);
+
+    // The same chain keeps its layout; a fresh selection resets it
+    app.toggle_detail_part_open();
+    app.show_table_view();
+    app.show_detail_view();
+    assert_eq!((app.detail_part, app.detail_part_open), (0, Some(0)));
+    app.clear_search();
+    app.show_detail_view();
+    assert_eq!((app.detail_part, app.detail_part_open), (0, None));
+}
+
+#[test]
+fn debug_overlay_is_opt_in_and_counts_frames() {
+    let mut app = app_with(MULTIPART);
+    assert!(!app.debug);
+    app.toggle_debug();
+    assert!(app.debug);
+
+    app.debug_stats.record_frame(std::time::Duration::from_millis(2));
+    app.debug_stats.record_frame(std::time::Duration::from_millis(3));
+    assert_eq!(app.debug_stats.fps(), 2);
+    assert_eq!(app.debug_stats.draw_time, std::time::Duration::from_millis(3));
+    // At least the raw text of every part
+    let raw: usize = app.audit_groups.iter().flat_map(|g| &g.entries).map(|e| e.raw_content.len()).sum();
+    assert!(app.debug_stats.memory >= raw);
+}
+
+#[test]
+fn jumps_between_error_rows() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    let errors: Vec<usize> = (0..app.filtered_groups.len())
+        .filter(|&row| is_error_status(&app.audit_groups[app.filtered_groups[row]]))
+        .collect();
+    assert_eq!(errors.len(), 2);
+
+    app.selected_index = errors[0];
+    assert!(app.jump_to_next(is_error_status));
+    assert_eq!(app.selected_index, errors[1]);
+    assert!(!app.jump_to_next(is_error_status));
+    assert_eq!(app.selected_index, errors[1]);
+
+    assert!(app.jump_to_previous(is_error_status));
+    assert_eq!(app.selected_index, errors[0]);
+
+    // Scrolled into view when the target is off screen
+    app.visible_height = 1;
+    app.selected_index = 0;
+    app.scroll_offset = 0;
+    assert!(app.jump_to_next(|_| true));
+    assert_eq!((app.selected_index, app.scroll_offset), (1, 1));
+    assert!(app.jump_to_previous(|_| true));
+    assert_eq!((app.selected_index, app.scroll_offset), (0, 0));
+}
+
+#[test]
+fn parses_column_lists() {
+    assert_eq!(
+        parse_columns("domain, Rule-IDs,status").unwrap(),
+        vec![Column::Domain, Column::RuleIds, Column::Status]
+    );
+    assert!(parse_columns("domain,domain").is_err());
+    // Not shown by default, but can be listed
+    assert_eq!(parse_columns("category").unwrap(), vec![Column::Category]);
+    let unknown = parse_columns("domain,referer").unwrap_err().to_string();
+    assert!(unknown.contains("category"), "{}", unknown);
+    assert!(parse_columns(" , ").is_err());
+}
+
+#[test]
+fn country_column_follows_its_toggle() {
+    let mut app = app_with(MULTIPART);
+    assert!(!app.visible_columns().contains(&Column::Country));
+    app.toggle_country();
+    assert_eq!(app.visible_columns()[4], Column::Country);
+
+    // A list without Country still gets it, right after Client IP
+    app.columns = vec![Column::ClientIp, Column::RuleIds];
+    assert_eq!(app.visible_columns(), vec![Column::ClientIp, Column::Country, Column::RuleIds]);
+    app.toggle_country();
+    assert_eq!(app.visible_columns(), vec![Column::ClientIp, Column::RuleIds]);
+}
+
+#[test]
+fn measures_peak_request_rates_per_ip() {
+    let scan = include_str!("fixtures/scan.log");
+    // Three requests within three seconds, and the same again 10s later
+    let later = scan.replace("04:50:0", "04:50:1").replace("aa00000", "bb00000");
+    let mut app = app_with(&format!("{}{}{}", scan, later, IPV6));
+    let rates = app.client_ip_rates();
+    // The window is half-open, so 04:50:03 and 04:50:13 never meet
+    assert_eq!(rates["198.51.100.23"], 0.3);
+    assert_eq!(rates["2a03:2880:f800:23::"], 0.1);
+    assert_eq!(rates, ip_rates(&app.audit_groups));
+
+    assert!(!app.is_noisy_ip("198.51.100.23"));
+    app.rate_threshold = 0.3;
+    assert!(app.is_noisy_ip("198.51.100.23"));
+    assert!(!app.is_noisy_ip("2a03:2880:f800:23::"));
+
+    let stats = app.compute_stats();
+    assert_eq!(stats.noisiest_ips, vec![("198.51.100.23".to_string(), 0.3)]);
+}
+
+#[test]
+fn legend_starts_open_with_the_option() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let mut app = App::from_groups(groups, &["test.log"], &AppOptions { legend: true, ..AppOptions::default() });
+    assert!(app.show_legend);
+    app.toggle_legend();
+    assert!(!app.show_legend);
+}
+
+#[test]
+fn dragging_a_header_separator_resizes_its_column() {
+    let mut app = app_with(MULTIPART);
+    app.double_click_ms = 300;
+    app.table_area = Some(ratatui::layout::Rect::new(0, 3, 100, 20));
+    app.column_edges = vec![(Column::AuditId, 15, 14), (Column::Timestamp, 34, 18)];
+    let start = std::time::Instant::now();
+    let at = |ms| start + std::time::Duration::from_millis(ms);
+
+    // Only the header row (below the top border) and near a separator
+    assert!(!app.start_column_drag_at(15, 5, at(0)));
+    assert!(!app.start_column_drag_at(20, 4, at(0)));
+
+    assert!(app.start_column_drag_at(16, 4, at(0)));
+    app.drag_column(26);
+    app.end_column_drag();
+    assert_eq!(app.column_widths.get(&Column::AuditId), Some(&24));
+    // Moving the mouse afterwards changes nothing
+    app.drag_column(40);
+    assert_eq!(app.column_widths.get(&Column::AuditId), Some(&24));
+
+    app.start_column_drag_at(34, 4, at(1000));
+    app.drag_column(0);
+    assert_eq!(app.column_widths.get(&Column::Timestamp), Some(&MIN_COLUMN_WIDTH));
+
+    // A double-click hands the column back to automatic sizing
+    assert!(app.start_column_drag_at(15, 4, at(2000)));
+    app.end_column_drag();
+    assert!(app.start_column_drag_at(15, 4, at(2100)));
+    assert!(!app.column_widths.contains_key(&Column::AuditId));
+}
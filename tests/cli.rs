@@ -0,0 +1,38 @@
+use std::process::Command;
+
+/// Run the binary on the multipart fixture in headless mode, without any
+/// user config file
+fn waflens(args: &[&str]) -> std::process::Output {
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/multipart.log");
+    let config_home = std::env::temp_dir().join(format!("waflens-cli-{}", std::process::id()));
+    Command::new(env!("CARGO_BIN_EXE_waflens"))
+        .args(args)
+        .arg(fixture)
+        .env("XDG_CONFIG_HOME", config_home)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn exit_code_reports_whether_anything_matched() {
+    let matched = waflens(&["--format", "json", "--exit-code", "--filter", "rule:942100"]);
+    assert_eq!(matched.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&matched.stdout).contains("5e6f7a8b"));
+
+    let none = waflens(&["--format", "json", "--exit-code", "--filter", "domain:nomatch"]);
+    assert_eq!(none.status.code(), Some(0));
+
+    // Without the flag, matches are not a failure
+    assert_eq!(waflens(&["--format", "json"]).status.code(), Some(0));
+}
+
+#[test]
+fn ip_api_switches_take_no_value_from_the_file_after_them() {
+    for switch in ["--ip-api", "--no-ip-api"] {
+        let output = waflens(&["--format", "json", switch]);
+        assert!(output.status.success(), "{}: {}", switch, String::from_utf8_lossy(&output.stderr));
+    }
+    // The bool form still works with `=`, and the switches override each other
+    assert!(waflens(&["--format", "json", "--ip-api=false"]).status.success());
+    assert!(waflens(&["--format", "json", "--ip-api", "--no-ip-api"]).status.success());
+}
@@ -0,0 +1,6 @@
+use waflens::clipboard::osc52_sequence;
+
+#[test]
+fn wraps_base64_payload_in_osc52() {
+    assert_eq!(osc52_sequence("hi\n"), "\x1b]52;c;aGkK\x07");
+}
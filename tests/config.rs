@@ -0,0 +1,74 @@
+use waflens::config::Config;
+use waflens::ipapi::GeoProviderKind;
+
+#[test]
+fn parses_kebab_case_keys() {
+    let config = Config::parse(
+        r#"
+        ip-api = false
+        dense = true
+        min-status = 400
+        ip-url = "https://www.abuseipdb.com/check/{ip}"
+        light = true
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.ip_api, Some(false));
+    assert_eq!(config.light, Some(true));
+    assert_eq!(config.dense, Some(true));
+    assert_eq!(config.min_status, Some(400));
+    assert_eq!(config.ip_url.as_deref(), Some("https://www.abuseipdb.com/check/{ip}"));
+    assert_eq!(config.max_status, None);
+}
+
+#[test]
+fn rejects_unknown_keys_and_out_of_range_values() {
+    assert!(Config::parse("dens = true").is_err());
+    assert!(Config::parse("min-status = 42").is_err());
+    assert!(Config::parse("max-entries = 0").is_err());
+    assert!(Config::parse("since = \"yesterday\"").is_err());
+    assert!(Config::parse("poll-interval = \"0s\"").is_err());
+    assert!(Config::parse("columns = \"domain,nope\"").is_err());
+    assert!(Config::parse("a-section-format = \"id server-ip\"").is_err());
+}
+
+#[test]
+fn parses_geo_provider() {
+    let config = Config::parse("geo-provider = \"ipinfo\"\ngeo-token = \"abc123\"").unwrap();
+    assert_eq!(config.geo_provider, Some(GeoProviderKind::Ipinfo));
+    assert_eq!(config.geo_token.as_deref(), Some("abc123"));
+    assert_eq!(Config::parse("geo-provider = \"ip-api\"").unwrap().geo_provider, Some(GeoProviderKind::IpApi));
+    assert!(Config::parse("geo-provider = \"maxmind\"").is_err());
+    assert!(Config::parse("geo-fields = \"country,lat,lon\"").is_ok());
+    assert!(Config::parse("geo-fields = \"country,elevation\"").is_err());
+}
+
+#[test]
+fn parses_trusted_ip_headers() {
+    let config = Config::parse("trust-xff = true\ntrusted-ip-headers = \"CF-Connecting-IP, X-Forwarded-For\"").unwrap();
+    assert_eq!(config.trust_xff, Some(true));
+    assert_eq!(config.trusted_ip_headers.as_deref(), Some("CF-Connecting-IP, X-Forwarded-For"));
+    assert!(Config::parse("trusted-ip-headers = \"\"").is_err());
+    assert!(Config::parse("trusted-ip-headers = \"X-Forwarded-For:\"").is_err());
+}
+
+#[test]
+fn missing_file_means_defaults() {
+    let config = Config::load(std::path::Path::new("tests/fixtures/no-such-config.toml")).unwrap();
+    assert_eq!(config, Config::default());
+}
+
+#[test]
+fn saved_filters_round_trip() {
+    let dir = std::env::temp_dir().join(format!("waflens-filters-{}", std::process::id()));
+    let path = dir.join("waflens").join("filters.toml");
+    assert!(waflens::config::load_filters(&path).unwrap().is_empty());
+
+    let filters = vec![
+        ("sqli".to_string(), "rule:942 blocked:true".to_string()),
+        ("api \"errors\"".to_string(), "domain:api. status:5xx".to_string()),
+    ];
+    waflens::config::save_filters(&path, &filters).unwrap();
+    assert_eq!(waflens::config::load_filters(&path).unwrap(), filters);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
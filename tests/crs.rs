@@ -0,0 +1,34 @@
+use waflens::crs::{category_description, describe, rule_category, rule_description};
+
+#[test]
+fn describes_known_rules() {
+    assert_eq!(rule_description("942100"), Some("SQL Injection Attack Detected via libinjection"));
+    assert_eq!(rule_description("949110"), Some("Inbound Anomaly Score Exceeded"));
+}
+
+#[test]
+fn falls_back_to_category_range() {
+    assert_eq!(rule_description("942999"), Some("SQL injection"));
+    assert_eq!(rule_description("941555"), Some("XSS"));
+}
+
+#[test]
+fn unknown_ids_have_no_description() {
+    assert_eq!(rule_description("3002"), None);
+    assert_eq!(rule_description("77350636"), None);
+    assert_eq!(rule_description("990000"), None);
+    assert_eq!(describe("3002"), "3002");
+    assert_eq!(describe("920280"), "920280 (Request Missing a Host Header)");
+}
+
+#[test]
+fn maps_rule_ids_to_attack_categories() {
+    assert_eq!(rule_category("942100"), Some("sqli"));
+    assert_eq!(rule_category("941999"), Some("xss"));
+    assert_eq!(rule_category("913100"), Some("scanner"));
+    assert_eq!(category_description("sqli"), Some("SQL injection"));
+    // Scoring and correlation rules only echo another category
+    assert_eq!(rule_category("949110"), None);
+    assert_eq!(rule_category("980130"), None);
+    assert_eq!(rule_category("3002"), None);
+}
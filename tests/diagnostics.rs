@@ -0,0 +1,42 @@
+use waflens::app::AppOptions;
+use waflens::colors::{color_support, palette, ColorSupport};
+use waflens::diagnostics::write_report;
+
+#[test]
+fn explains_the_color_choice() {
+    assert_eq!(color_support(Some("xterm"), Some("truecolor")), (ColorSupport::Colors256, "COLORTERM=truecolor".to_string()));
+    assert_eq!(color_support(Some("screen-256color"), None).0, ColorSupport::Colors256);
+    assert_eq!(color_support(Some("xterm-color"), None), (ColorSupport::Colors16, "TERM=xterm-color".to_string()));
+    assert_eq!(color_support(Some("dumb"), None).0, ColorSupport::Colors16);
+    assert!(color_support(None, None).1.starts_with("default"));
+}
+
+#[test]
+fn light_flag_beats_colorfgbg_beats_term() {
+    assert_eq!(palette(true, Some("15;0"), Some("xterm-256color"), None), (ColorSupport::Light, "--light".to_string()));
+    assert_eq!(palette(false, Some("0;15"), Some("xterm-256color"), None), (ColorSupport::Light, "COLORFGBG=0;15".to_string()));
+    assert_eq!(palette(false, Some("0;default;7"), None, None).0, ColorSupport::Light);
+    // Dark backgrounds, dark gray included, leave the choice to TERM
+    assert_eq!(palette(false, Some("15;0"), Some("xterm-256color"), None).0, ColorSupport::Colors256);
+    assert_eq!(palette(false, Some("7;8"), None, None).0, ColorSupport::Colors16);
+    assert_eq!(palette(false, Some("garbage"), Some("xterm-color"), None).0, ColorSupport::Colors16);
+}
+
+#[test]
+fn report_covers_config_geolocation_and_patterns() {
+    let options = AppOptions { ip_api_enabled: false, ..AppOptions::default() };
+    let path = std::path::Path::new("tests/fixtures/no-such-config.toml");
+    let mut out = Vec::new();
+    write_report(&mut out, Some(path), None, &options).unwrap();
+    let report = String::from_utf8(out).unwrap();
+
+    assert!(report.starts_with(&format!("waflens {}", env!("CARGO_PKG_VERSION"))));
+    assert!(report.contains("no-such-config.toml (not found, using defaults)"));
+    assert!(report.contains("Geolocation: ip-api, not checked"));
+    assert!(report.contains("boundary"));
+    assert!(report.contains("--([a-zA-Z0-9]+)-([A-Z])--"));
+
+    let mut out = Vec::new();
+    write_report(&mut out, Some(path), Some("since: invalid duration"), &options).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains("(invalid: since: invalid duration)"));
+}
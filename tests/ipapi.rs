@@ -0,0 +1,202 @@
+use anyhow::Result;
+use reqwest::blocking::Client;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use waflens::ipapi::{is_offline, is_private_or_reserved, parse_geo_fields, GeoProvider, IpApiCache, IpApiResponse, Ipinfo, DEFAULT_FIELDS, USER_AGENT};
+use waflens::app::{App, AppOptions};
+use waflens::parser::AuditLogParser;
+
+/// A provider whose server is never there: port 1 on loopback refuses
+struct Unreachable;
+
+impl GeoProvider for Unreachable {
+    fn lookup(&self, client: &Client, _ip: &str, _fields: &str) -> Result<IpApiResponse> {
+        Ok(client.get("http://127.0.0.1:1/").send()?.json()?)
+    }
+}
+
+fn response(proxy: bool, hosting: bool, mobile: bool) -> IpApiResponse {
+    serde_json::from_value(serde_json::json!({
+        "status": "success",
+        "proxy": proxy,
+        "hosting": hosting,
+        "mobile": mobile,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn formats_threat_flags() {
+    assert_eq!(response(true, true, false).flags(), "PH");
+    assert_eq!(response(false, false, true).flags(), "M");
+    assert_eq!(response(false, false, false).flags(), "-");
+}
+
+#[test]
+fn caches_by_ipv4_subnet() {
+    assert_eq!(IpApiCache::get_subnet_24("203.0.113.7").as_deref(), Some("203.0.113.0"));
+    assert_eq!(IpApiCache::get_subnet_24("2001:db8::1").as_deref(), Some("2001:db8::1"));
+    assert_eq!(IpApiCache::get_subnet_24("not-an-ip"), None);
+}
+
+#[test]
+fn prefetch_with_everything_cached_makes_no_requests() {
+    let cache = IpApiCache::new();
+    let mut calls = Vec::new();
+    let resolved = cache.prefetch(["not-an-ip"], |done, total| {
+        calls.push((done, total));
+        true
+    }).unwrap();
+    assert_eq!(resolved, 0);
+    assert_eq!(calls, vec![(0, 0)]);
+    assert!(cache.cached("203.0.113.7").is_none());
+}
+
+#[test]
+fn reverse_dns_skips_invalid_ips() {
+    let rdns = waflens::rdns::ReverseDnsCache::new();
+    assert_eq!(rdns.lookup("not-an-ip"), None);
+}
+
+#[test]
+fn lookup_rejects_invalid_ips_without_a_request() {
+    let cache = IpApiCache::new();
+    assert!(cache.get_ip_info("unknown").is_err());
+}
+
+#[test]
+fn recognises_private_and_reserved_addresses() {
+    for ip in ["10.1.2.3", "192.168.0.1", "127.0.0.1", "0.0.0.0", "169.254.1.1", "100.64.0.1", "203.0.113.7", "::1", "fd00::1", "fe80::1", "::ffff:10.0.0.1"] {
+        assert!(is_private_or_reserved(ip), "{}", ip);
+    }
+    for ip in ["8.8.8.8", "100.128.0.1", "2606:4700::1111", "not-an-ip"] {
+        assert!(!is_private_or_reserved(ip), "{}", ip);
+    }
+}
+
+#[test]
+fn private_addresses_fail_locally_and_are_cached() {
+    let cache = IpApiCache::new();
+    let info = cache.get_ip_info("10.1.2.3").unwrap();
+    assert!(info.contains("private range"));
+    let cached = cache.cached("10.1.2.200").unwrap();
+    assert_eq!(cached.status, "fail");
+
+    let resolved = cache.prefetch(["127.0.0.1", "0.0.0.0"], |_, _| true).unwrap();
+    assert_eq!(resolved, 0);
+    assert_eq!(cache.cached("127.0.0.1").unwrap().message.as_deref(), Some("reserved range"));
+}
+
+#[test]
+fn maps_ipinfo_answers_onto_ip_api_fields() {
+    let geo = Ipinfo::parse(r#"{
+        "ip": "8.8.8.0", "city": "Mountain View", "region": "California", "country": "US",
+        "loc": "37.4056,-122.0775", "org": "AS15169 Google LLC", "postal": "94043",
+        "timezone": "America/Los_Angeles",
+        "privacy": {"vpn": false, "proxy": false, "tor": false, "hosting": true}
+    }"#).unwrap();
+    assert_eq!(geo.status, "success");
+    assert_eq!(geo.country_code.as_deref(), Some("US"));
+    assert_eq!(geo.region_name.as_deref(), Some("California"));
+    assert_eq!(geo.as_info.as_deref(), Some("AS15169 Google LLC"));
+    assert_eq!(geo.isp.as_deref(), Some("Google LLC"));
+    assert_eq!((geo.lat, geo.lon), (Some(37.4056), Some(-122.0775)));
+    assert_eq!(geo.flags(), "H");
+}
+
+#[test]
+fn ipinfo_errors_and_bogons_fail_like_ip_api() {
+    let bogon = Ipinfo::parse(r#"{"ip": "10.0.0.0", "bogon": true}"#).unwrap();
+    assert_eq!(bogon.status, "fail");
+    assert_eq!(bogon.message.as_deref(), Some("reserved range"));
+
+    let error = Ipinfo::parse(r#"{"status": 403, "error": {"title": "Unknown token", "message": "Please ensure you've entered your token correctly"}}"#).unwrap();
+    assert_eq!(error.status, "fail");
+    assert!(error.message.unwrap().contains("token"));
+
+    assert!(Ipinfo::parse("Too Many Requests").is_err());
+}
+
+#[test]
+fn field_lists_always_carry_the_status_fields() {
+    assert_eq!(parse_geo_fields("country, lat,lon,lat").unwrap(), "query,status,message,country,lat,lon");
+    assert_eq!(parse_geo_fields(DEFAULT_FIELDS).unwrap(), DEFAULT_FIELDS);
+    assert!(parse_geo_fields("country,elevation").is_err());
+    assert_eq!(IpApiCache::new().fields(), DEFAULT_FIELDS);
+}
+
+#[test]
+fn cached_json_shows_only_the_fields_that_came_back() {
+    let cache = IpApiCache::new();
+    cache.insert("203.0.113.7", response(false, true, false));
+    let json = cache.get_ip_info("203.0.113.7").unwrap();
+    assert!(json.contains("\"hosting\": true"));
+    assert!(!json.contains("timezone"));
+}
+
+/// A provider that asks a local server
+struct Local(String);
+
+impl GeoProvider for Local {
+    fn lookup(&self, client: &Client, ip: &str, _fields: &str) -> Result<IpApiResponse> {
+        Ok(client.get(format!("{}/{}", self.0, ip)).send()?.json()?)
+    }
+}
+
+#[test]
+fn lookups_share_one_client_with_the_user_agent() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    // Answer on one connection only: the second lookup has to reuse it
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut agents = Vec::new();
+        for ip in ["8.8.4.0", "1.1.1.0"] {
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                if let Some(agent) = line.to_ascii_lowercase().strip_prefix("user-agent: ") {
+                    agents.push(agent.trim().to_string());
+                }
+                line.clear();
+            }
+            let body = format!(r#"{{"status":"success","query":"{}"}}"#, ip);
+            write!(&stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        }
+        agents
+    });
+
+    let cache = IpApiCache::with_provider(Box::new(Local(url)));
+    assert!(cache.get_ip_info("8.8.4.4").unwrap().contains("8.8.4.0"));
+    assert!(cache.get_ip_info("1.1.1.1").unwrap().contains("1.1.1.0"));
+    assert_eq!(server.join().unwrap(), vec![USER_AGENT; 2]);
+}
+
+#[test]
+fn an_unreachable_provider_counts_as_offline() {
+    let cache = IpApiCache::with_provider(Box::new(Unreachable));
+    let error = cache.get_ip_info("8.8.4.4").unwrap_err();
+    assert!(is_offline(&error));
+    // A bad answer is not the network being down
+    assert!(!is_offline(&cache.get_ip_info("not-an-ip").unwrap_err()));
+}
+
+#[test]
+fn going_offline_turns_ip_lookups_off_once() {
+    let log = include_str!("fixtures/multipart.log")
+        .replace("203.0.113.7", "8.8.4.4")
+        .replace("198.51.100.23", "8.8.4.4");
+    let groups = AuditLogParser::new().parse_str(&log);
+    let mut app = App::from_groups(groups, &["test.log"], &AppOptions::default());
+    app.ip_api_cache = IpApiCache::with_provider(Box::new(Unreachable));
+    app.ip_api_enabled = true;
+    app.show_detail_view();
+    app.load_pending_ip_info();
+    assert!(!app.ip_api_enabled);
+    assert_eq!(app.current_ip_info, None);
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("Geolocation unavailable (offline); IP lookups are off (press I to retry)")
+    );
+}
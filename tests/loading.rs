@@ -0,0 +1,10 @@
+use waflens::loading::band_progress;
+
+#[test]
+fn band_progress_stays_inside_its_band() {
+    assert_eq!(band_progress(0.2, 0.6, 0.0), 0.2);
+    assert!((band_progress(0.2, 0.6, 0.5) - 0.4).abs() < 1e-9);
+    // A file that grew while being read reports more than 100%
+    assert_eq!(band_progress(0.05, 0.6, 1.3), 0.6);
+    assert_eq!(band_progress(0.05, 0.6, -0.1), 0.05);
+}
@@ -0,0 +1,92 @@
+use waflens::app::{App, AppOptions};
+use waflens::output::{markdown_table, write_groups, write_rule_report, OutputFormat};
+use waflens::parser::AuditLogParser;
+
+const MULTIPART: &str = include_str!("fixtures/multipart.log");
+
+fn render(format: OutputFormat) -> String {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let groups: Vec<_> = groups.iter().collect();
+    let mut out = Vec::new();
+    write_groups(format, &groups, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn table_output_aligns_columns_and_counts() {
+    let table = render(OutputFormat::Table);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("Audit ID  Timestamp"));
+    assert!(lines[1].starts_with("5e6f7a8b  2025-11-17 02:47:02  blog.example.org"));
+    assert!(lines[1].contains("403 ✖"));
+    // Every column starts at the same offset on every row
+    let column = lines[0].find("Client IP").unwrap();
+    assert_eq!(&lines[2][column..column + 11], "203.0.113.7");
+    assert_eq!(lines[3], "2 transactions");
+}
+
+#[test]
+fn json_output_mirrors_group_fields() {
+    let json: serde_json::Value = serde_json::from_str(&render(OutputFormat::Json)).unwrap();
+    let records = json.as_array().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["audit_id"], "5e6f7a8b");
+    assert_eq!(records[0]["http_status"], 403);
+    assert_eq!(records[0]["blocked"], true);
+    assert_eq!(records[1]["rule_ids"], serde_json::json!(["920350", "920280"]));
+    assert_eq!(records[1]["parts"], 2);
+}
+
+#[test]
+fn rule_report_follows_the_matches_with_a_breakdown() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let app = App::from_groups(groups, &["test.log"], &AppOptions::default());
+    let matched: Vec<_> = app.visible_groups().into_iter().filter(|g| g.base_id == "5e6f7a8b").collect();
+    let mut out = Vec::new();
+    write_rule_report(OutputFormat::Table, &matched, &app.stats_for_rule("942100"), &mut out).unwrap();
+    let table = String::from_utf8(out).unwrap();
+
+    assert!(table.contains("1 transaction\n\nRule 942100: 1 transaction (1 blocked) on 1 domain from 1 client IP"));
+    assert!(table.lines().any(|line| line == "blog.example.org  1     1        1    100%"));
+    assert!(table.lines().any(|line| line == "198.51.100.23  1     1"));
+}
+
+#[test]
+fn markdown_escapes_pipes_and_line_breaks() {
+    let rows = vec![vec!["a|b".to_string(), "two\nlines".to_string()]];
+    assert_eq!(markdown_table(&["X", "Y"], &rows), "| X | Y |\n| --- | --- |\n| a\\|b | two lines |\n");
+
+    let markdown = render(OutputFormat::Markdown);
+    assert!(markdown.starts_with("| Audit ID | Timestamp |"));
+    assert!(markdown.contains("| 5e6f7a8b | 2025-11-17 02:47:02 | blog.example.org |"));
+    assert_eq!(markdown.lines().count(), 4);
+}
+
+#[test]
+fn ndjson_prints_one_record_per_line() {
+    let ndjson = render(OutputFormat::Ndjson);
+    let records: Vec<serde_json::Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["audit_id"], "5e6f7a8b");
+    assert_eq!(records[1]["parts"], 2);
+}
+
+#[test]
+fn report_summarizes_the_filtered_transactions_as_json() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let app = App::from_groups(groups, &["test.log"], &AppOptions::default());
+    let path = std::env::temp_dir().join(format!("waflens-report-{}.json", std::process::id()));
+    app.write_report(&path).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(report["files"], serde_json::json!(["test.log"]));
+    assert_eq!(report["filter"], serde_json::Value::Null);
+    assert_eq!((report["total"].as_u64(), report["blocked"].as_u64(), report["detection_only"].as_u64()), (Some(2), Some(1), Some(1)));
+    assert_eq!(report["first_seen"], "2025-11-17T02:46:27+00:00");
+    assert_eq!(report["last_seen"], "2025-11-17T02:47:02+00:00");
+    assert_eq!(report["status_classes"]["4xx"], 1);
+    assert_eq!(report["top_domains"][0], serde_json::json!({"value": "blog.example.org", "count": 1}));
+    assert_eq!(report["top_rules"].as_array().unwrap().len(), 4);
+}
@@ -0,0 +1,640 @@
+use waflens::parser::{action_badge, check_log_path, ensure_audit_entries, is_blocked, looks_binary, parse_a_section_format, parse_headers, parse_trusted_ip_headers, short_id_len, AuditLogParser, HttpStatus, DEFAULT_A_SECTION_FORMAT, UNKNOWN_IP};
+use std::time::Duration;
+
+const MULTIPART: &str = include_str!("fixtures/multipart.log");
+const IPV6: &str = include_str!("fixtures/ipv6.log");
+
+#[test]
+fn groups_multi_part_chain_by_boundary_id() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    assert_eq!(groups.len(), 2);
+
+    let chain = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    assert_eq!(chain.entries.len(), 2);
+    assert_eq!(chain.domain, "shop.example.com");
+    assert_eq!(chain.client_ip, "203.0.113.7");
+    // Status and rule IDs come from the second part of the chain
+    assert_eq!(chain.http_status, HttpStatus::Code(200));
+    assert_eq!(chain.primary_rule_ids, vec!["920350", "920280"]);
+    assert_eq!(
+        chain.file_path.as_deref(),
+        Some("/etc/modsecurity/crs/REQUEST-920-PROTOCOL-ENFORCEMENT.conf")
+    );
+}
+
+#[test]
+fn extracts_fields_from_single_transaction() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let group = groups.iter().find(|g| g.base_id == "5e6f7a8b").unwrap();
+
+    assert_eq!(group.entries.len(), 1);
+    assert_eq!(group.domain, "blog.example.org");
+    assert_eq!(group.client_ip, "198.51.100.23");
+    assert_eq!(group.http_status, HttpStatus::Code(403));
+    assert_eq!(group.primary_rule_ids, vec!["942100", "949110"]);
+    assert_eq!(
+        group.first_timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+        "2025-11-17 02:47:02"
+    );
+}
+
+#[test]
+fn keeps_duplicate_rule_ids_on_entries() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let chain = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    let fired: Vec<&str> = chain
+        .entries
+        .iter()
+        .flat_map(|e| e.rule_ids.iter().map(String::as_str))
+        .collect();
+    assert_eq!(fired, vec!["920350", "920280", "920350"]);
+}
+
+#[test]
+fn trusted_headers_replace_the_peer_address() {
+    let log = MULTIPART.replace(
+        "Host: blog.example.org\n",
+        "Host: blog.example.org\nX-Forwarded-For: garbage, 8.8.4.4, 10.0.0.1\ncf-connecting-ip: 1.1.1.1\n",
+    );
+    let blog = |groups: &[waflens::parser::AuditGroup]| {
+        groups.iter().find(|g| g.domain == "blog.example.org").unwrap().clone()
+    };
+
+    // Off by default: the address from section A stays
+    let group = blog(&AuditLogParser::new().parse_str(&log));
+    assert_eq!(group.client_ip, "198.51.100.23");
+    assert_eq!(group.entries[0].peer_ip, None);
+
+    // Names match case-insensitively and the list order decides
+    let headers = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let parser = AuditLogParser::new().with_trusted_ip_headers(headers(&["CF-Connecting-IP", "X-Forwarded-For"]));
+    let group = blog(&parser.parse_str(&log));
+    assert_eq!(group.client_ip, "1.1.1.1");
+    assert_eq!(group.entries[0].peer_ip.as_deref(), Some("198.51.100.23"));
+
+    // Malformed values are skipped in favour of the next address
+    let parser = AuditLogParser::new().with_trusted_ip_headers(headers(&["X-Forwarded-For"]));
+    assert_eq!(blog(&parser.parse_str(&log)).client_ip, "8.8.4.4");
+
+    // A header that is missing leaves the peer address alone
+    let parser = AuditLogParser::new().with_trusted_ip_headers(headers(&["X-Real-IP"]));
+    assert_eq!(blog(&parser.parse_str(&log)).client_ip, "198.51.100.23");
+}
+
+#[test]
+fn a_section_format_picks_the_client_ip_field() {
+    // Destination first: the default layout would take the server address
+    let log = MULTIPART.replace(
+        "aGVsbG8td29ybGQtMDAwMDAx 203.0.113.7 42724 192.168.0.1 80",
+        "aGVsbG8td29ybGQtMDAwMDAx 192.168.0.1 80 203.0.113.7 42724",
+    );
+    let shop = |parser: AuditLogParser| {
+        parser.parse_str(&log).into_iter().find(|g| g.base_id == "1a2b3c4d").unwrap().client_ip
+    };
+    assert_eq!(shop(AuditLogParser::new()), "192.168.0.1");
+
+    let field = parse_a_section_format("id server-ip server-port client-ip client-port").unwrap();
+    assert_eq!(field, 3);
+    assert_eq!(shop(AuditLogParser::new().with_a_section_ip_field(Some(field))), "203.0.113.7");
+    // A field that holds no address falls back to the first one on the line
+    assert_eq!(shop(AuditLogParser::new().with_a_section_ip_field(Some(2))), "192.168.0.1");
+
+    assert_eq!(parse_a_section_format(DEFAULT_A_SECTION_FORMAT).unwrap(), 1);
+    assert_eq!(parse_a_section_format("timestamp id client-ip").unwrap(), 1);
+    assert!(parse_a_section_format("id server-ip").is_err());
+    assert!(parse_a_section_format("client-ip id client-ip").is_err());
+}
+
+#[test]
+fn lines_outside_entries_are_skipped_and_counted() {
+    let parser = AuditLogParser::new();
+    let clean = parser.parse_str(MULTIPART);
+    assert_eq!(parser.stray_bytes(), 0);
+
+    // A comment before the first boundary, another log's line after a Z
+    let messy = format!(
+        "# rotated by cron\n\n{}",
+        MULTIPART.replacen("--1a2b3c4d-Z--\n", "--1a2b3c4d-Z--\nkernel: eth0 link up\n", 1)
+    );
+    let parser = AuditLogParser::new();
+    let groups = parser.parse_str(&messy);
+    assert_eq!(parser.stray_bytes(), "# rotated by cron\n".len() + "kernel: eth0 link up\n".len());
+    assert_eq!(groups.len(), clean.len());
+    assert!(groups.iter().all(|g| g.entries.iter().all(|e| !e.raw_content.contains("kernel:"))));
+}
+
+#[test]
+fn parses_ipv6_sources() {
+    let groups = AuditLogParser::new().parse_str(IPV6);
+    assert_eq!(groups.len(), 2);
+
+    // Most recent first
+    assert_eq!(groups[0].base_id, "b44f0e1d22aa9c10");
+    assert_eq!(groups[0].client_ip, "2001:0db8:85a3:0000:0000:8a2e:0370:7334");
+    assert_eq!(groups[0].http_status, HttpStatus::Code(200));
+
+    assert_eq!(groups[1].client_ip, "2a03:2880:f800:23::");
+    assert_eq!(groups[1].domain, "wiki.example.org");
+    assert_eq!(groups[1].http_status, HttpStatus::Code(429));
+    assert_eq!(groups[1].primary_rule_ids, vec!["3002"]);
+}
+
+#[test]
+fn ignores_content_without_boundaries() {
+    let groups = AuditLogParser::new().parse_str("just some text\nnot an audit log\n");
+    assert!(groups.is_empty());
+}
+
+#[test]
+fn parses_timestamp_with_offset() {
+    let parser = AuditLogParser::new();
+    let ts = parser
+        .parse_timestamp("[27/Jul/2016:05:46:16 +0200] V5guiH8AAQEAADTeJ2wAAAAK")
+        .unwrap();
+    assert_eq!(ts.format("%Y-%m-%d %H:%M:%S").to_string(), "2016-07-27 03:46:16");
+    assert!(parser.parse_timestamp("no timestamp here").is_none());
+}
+
+#[test]
+fn normalizes_crlf_and_bare_cr_line_endings() {
+    let groups = AuditLogParser::new().parse_str(include_str!("fixtures/crlf.log"));
+    assert_eq!(groups.len(), 1);
+
+    let group = &groups[0];
+    assert_eq!(group.domain, "wiki.example.org");
+    assert_eq!(group.client_ip, "2a03:2880:f800:23::");
+    assert_eq!(group.http_status, HttpStatus::Code(429));
+    assert_eq!(group.primary_rule_ids, vec!["3002"]);
+
+    let raw = &group.entries[0].raw_content;
+    assert!(!raw.contains('\r'));
+    // The bare CR between the two headers became a line break
+    assert!(raw.lines().any(|l| l == "Accept: */*"));
+    assert!(raw.lines().any(|l| l == "host: wiki.example.org"));
+}
+
+#[test]
+fn reads_concurrent_index_and_skips_missing_files() {
+    let parser = AuditLogParser::new();
+    let dir = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/concurrent"));
+
+    // The third index line references a transaction file that was pruned
+    let files = parser.concurrent_part_files(&dir.join("index")).unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(parser.concurrent_part_files(dir).unwrap(), files);
+
+    let groups = parser.parse_files(&files).unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].client_ip, "2001:0db8:85a3:0000:0000:8a2e:0370:7334");
+    assert_eq!(groups[1].domain, "wiki.example.org");
+    assert_eq!(groups[1].http_status, HttpStatus::Code(429));
+}
+
+#[test]
+fn walks_concurrent_storage_dir_without_index() {
+    let parser = AuditLogParser::new();
+    let dir = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/concurrent/20251117"));
+    let files = parser.concurrent_part_files(dir).unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(parser.parse_files(&files).unwrap().len(), 2);
+}
+
+#[test]
+fn merges_a_transaction_split_by_log_rotation() {
+    let parser = AuditLogParser::new();
+    let dir = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rotated"));
+    let rotated = dir.join("modsec_audit.log.1");
+    let current = dir.join("modsec_audit.log");
+
+    // Either order: the parts are sorted by section when merged
+    for files in [[rotated.clone(), current.clone()], [current.clone(), rotated.clone()]] {
+        let groups = parser.parse_files(&files).unwrap();
+        assert_eq!(groups.len(), 3);
+        let split = groups.iter().find(|g| g.base_id == "5e6f7a8b").unwrap();
+        assert_eq!(split.entries.len(), 2);
+        assert!(split.entries[0].raw_content.starts_with("--5e6f7a8b-A--"));
+        assert!(!split.incomplete);
+        assert!(split.blocked);
+        assert_eq!(split.domain, "blog.example.org");
+        assert_eq!(split.http_status, HttpStatus::Code(403));
+        assert_eq!(split.primary_rule_ids, ["942100", "949110"]);
+    }
+
+    // Alone, the rotated file ends mid-transaction
+    let groups = parser.parse_files(&[rotated]).unwrap();
+    assert!(groups[0].incomplete);
+}
+
+#[test]
+fn shortens_audit_ids_while_keeping_them_unique() {
+    let parser = AuditLogParser::new();
+    let groups = parser.parse_str(&format!("{}{}", MULTIPART, IPV6));
+    assert_eq!(short_id_len(&groups), 8);
+    let long = groups.iter().find(|g| g.base_id == "a3734c1a04ffce27").unwrap();
+    assert_eq!(long.display_id(8), "a3734c1a");
+    let short = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    assert_eq!(short.display_id(8), "1a2b3c4d");
+
+    // Two IDs sharing their first 8 characters need a 9th
+    let clashing = IPV6.replace("b44f0e1d22aa9c10", "a3734c1a94ffce27");
+    assert_eq!(short_id_len(&parser.parse_str(&clashing)), 9);
+}
+
+#[test]
+fn counts_rule_hits_across_chain() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let chain = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    assert_eq!(
+        chain.rule_id_counts(),
+        vec![("920350".to_string(), 2), ("920280".to_string(), 1)]
+    );
+}
+
+#[test]
+fn raw_chain_concatenates_every_entry() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let chain = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    let raw = chain.raw_chain();
+
+    for entry in &chain.entries {
+        assert!(raw.contains(entry.raw_content.trim_end()));
+    }
+    assert!(raw.ends_with('\n'));
+}
+
+#[test]
+fn rejects_content_that_is_not_an_audit_log() {
+    let parser = AuditLogParser::new();
+    let access_log = "127.0.0.1 - - [17/Nov/2025:10:00:00 +0000] \"GET / HTTP/1.1\" 200 512\n";
+    assert!(parser.parse_str(access_log).is_empty());
+    assert!(ensure_audit_entries(true, 0).is_err());
+
+    // Empty logs and real audit logs are fine
+    assert!(ensure_audit_entries(false, 0).is_ok());
+    assert!(ensure_audit_entries(true, parser.parse_str(MULTIPART).len()).is_ok());
+}
+
+#[test]
+fn max_groups_keeps_most_recent() {
+    let content = format!("{}{}", MULTIPART, IPV6);
+    let all = AuditLogParser::new().parse_str(&content);
+    let kept = AuditLogParser::new().with_max_groups(Some(2)).parse_str(&content);
+
+    assert_eq!(all.len(), 4);
+    assert_eq!(kept.len(), 2);
+    let newest: Vec<_> = all.iter().take(2).map(|g| &g.base_id).collect();
+    assert_eq!(kept.iter().map(|g| &g.base_id).collect::<Vec<_>>(), newest);
+    // Multi-part chains stay intact
+    assert!(kept.iter().all(|g| all.iter().any(|a| a.base_id == g.base_id && a.entries.len() == g.entries.len())));
+}
+
+#[test]
+fn extracts_body_sizes() {
+    let groups = AuditLogParser::new().parse_str(include_str!("fixtures/bodies.log"));
+    let group = &groups[0];
+    // Declared Content-Length for the request, measured E section for the chunked response
+    assert_eq!(group.request_size, Some(27));
+    assert_eq!(group.response_size, Some(19));
+
+    // No bodies logged and no Content-Length: unknown
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    assert!(groups.iter().all(|g| g.request_size.is_none() && g.response_size.is_none()));
+}
+
+#[test]
+fn validates_client_ip_from_a_section() {
+    let parse_ip = |a_line: &str| {
+        let log = format!("--c0ffee00-A--\n{}\n--c0ffee00-Z--\n\n", a_line);
+        AuditLogParser::new().parse_str(&log)[0].client_ip.clone()
+    };
+    let ts = "[17/Nov/2025:04:46:27 +0200] aGVsbG8td29ybGQ";
+
+    assert_eq!(parse_ip(&format!("{} 203.0.113.7 42724 192.168.0.1 80", ts)), "203.0.113.7");
+    assert_eq!(parse_ip(&format!("{} 203.0.113.7:42724 192.168.0.1 80", ts)), "203.0.113.7");
+    assert_eq!(parse_ip(&format!("{} [2001:db8::1] 42724 192.168.0.1 80", ts)), "2001:db8::1");
+    // Extra token where the client IP usually is: fall back to the first address
+    assert_eq!(parse_ip(&format!("{} - 198.51.100.7 42724 192.168.0.1 80", ts)), "198.51.100.7");
+    assert_eq!(parse_ip(&format!("{} garbage", ts)), UNKNOWN_IP);
+}
+
+#[test]
+fn marks_a_trailing_entry_without_z_boundary_incomplete() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    // The first part of 1a2b3c4d has no Z either, but its chain continues
+    assert!(groups.iter().all(|g| !g.incomplete));
+
+    // Cut the log off in the middle of the last part
+    let truncated = &MULTIPART[..MULTIPART.rfind("--1a2b3c4d-H--").unwrap()];
+    let groups = AuditLogParser::new().parse_str(truncated);
+    let chain = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    assert!(chain.incomplete);
+    assert!(!chain.entries[0].incomplete && chain.entries[1].incomplete);
+    assert!(!groups.iter().find(|g| g.base_id == "5e6f7a8b").unwrap().incomplete);
+}
+
+#[test]
+fn tells_blocked_from_detection_only() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let blocked = |id: &str| groups.iter().find(|g| g.base_id == id).unwrap().blocked;
+    assert!(blocked("5e6f7a8b"));
+    // Rules matched, but the request went through with a 200
+    assert!(!blocked("1a2b3c4d"));
+
+    let rules = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+    assert!(is_blocked(Some(429), false, &[]));
+    assert!(is_blocked(Some(200), true, &[]));
+    assert!(is_blocked(None, false, &rules(&["942100", "980130"])));
+    assert!(!is_blocked(Some(404), false, &rules(&["942100"])));
+}
+
+#[test]
+fn extracts_headers_from_a_section() {
+    let parser = AuditLogParser::new();
+    let log = include_str!("fixtures/bodies.log");
+    let request = parser.section(log, 'B').unwrap();
+    assert_eq!(
+        parse_headers(request),
+        vec![
+            ("Host", "files.example.com"),
+            ("Content-Type", "application/x-www-form-urlencoded"),
+            ("Content-Length", "27"),
+        ]
+    );
+    // The status line is not a header, the block ends at the blank line
+    let response = parser.section(log, 'F').unwrap();
+    assert_eq!(parse_headers(response)[0], ("Transfer-Encoding", "chunked"));
+    assert_eq!(parse_headers(response).len(), 2);
+}
+
+#[test]
+fn reads_the_engine_mode_trailer() {
+    let log = MULTIPART.replace(
+        "--5e6f7a8b-Z--",
+        "Engine-Mode: \"DETECTION_ONLY\"\n\n--5e6f7a8b-Z--",
+    );
+    let groups = AuditLogParser::new().parse_str(&log);
+    let mode = |id: &str| groups.iter().find(|g| g.base_id == id).unwrap().engine_mode.clone();
+    assert_eq!(mode("5e6f7a8b").as_deref(), Some("DETECTION_ONLY"));
+    // Older logs have no trailer
+    assert_eq!(mode("1a2b3c4d"), None);
+}
+
+#[test]
+fn request_headers_cannot_set_the_engine_mode() {
+    let log = MULTIPART
+        .replace("Host: blog.example.org", "Host: blog.example.org\nEngine-Mode: DETECTION_ONLY")
+        .replace("--1a2b3c4d-F--", "--1a2b3c4d-C--\nEngine-Mode: \"DETECTION_ONLY\"\n\n--1a2b3c4d-F--");
+    let groups = AuditLogParser::new().parse_str(&log);
+    assert!(groups.iter().all(|g| g.engine_mode.is_none()));
+}
+
+#[test]
+fn reads_the_disruptive_action() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let action = |id: &str| groups.iter().find(|g| g.base_id == id).unwrap().action.clone();
+    // Without an `Action:` trailer it comes from the messages
+    assert_eq!(action("5e6f7a8b").as_deref(), Some("Intercepted"));
+    assert_eq!(action("1a2b3c4d").as_deref(), Some("Warning"));
+    assert_eq!(action_badge(Some("Intercepted (phase 2)")), "block");
+    assert_eq!(action_badge(None), "");
+
+    let log = MULTIPART.replace(
+        "--5e6f7a8b-Z--",
+        "Action: Intercepted (phase 2)\n\n--5e6f7a8b-Z--",
+    );
+    let groups = AuditLogParser::new().parse_str(&log);
+    let group = groups.iter().find(|g| g.base_id == "5e6f7a8b").unwrap();
+    assert_eq!(group.action.as_deref(), Some("Intercepted (phase 2)"));
+    assert!(group.blocked);
+}
+
+#[test]
+fn request_body_cannot_set_the_action() {
+    let log = MULTIPART.replace(
+        "--5e6f7a8b-F--",
+        "--5e6f7a8b-C--\nAction: Warning\nMessage: Warning. spoofed\n\n--5e6f7a8b-F--",
+    ).replace("HTTP/1.1 403 Forbidden", "HTTP/1.1 200 OK");
+    let groups = AuditLogParser::new().parse_str(&log);
+    let group = groups.iter().find(|g| g.base_id == "5e6f7a8b").unwrap();
+    // The H trailer's "Access denied" still decides
+    assert_eq!(group.action.as_deref(), Some("Intercepted"));
+    assert!(group.blocked);
+
+    // Nor can it make a warning-only chain look intercepted
+    let log = MULTIPART.replace(
+        "--1a2b3c4d-F--",
+        "--1a2b3c4d-C--\nAction: Intercepted\nAccess denied with code 403\n\n--1a2b3c4d-F--",
+    );
+    let groups = AuditLogParser::new().parse_str(&log);
+    let group = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    assert_eq!(group.action.as_deref(), Some("Warning"));
+    assert!(!group.blocked);
+}
+
+#[test]
+fn captures_matched_data() {
+    let log = MULTIPART.replace(
+        r#"[id "942100"]"#,
+        r#"[id "942100"] [data "Matched Data: 1' OR \"1\"=\"1 found within ARGS:q"] [data ""]"#,
+    );
+    let groups = AuditLogParser::new().parse_str(&log);
+    let data = |id: &str| {
+        let group = groups.iter().find(|g| g.base_id == id).unwrap();
+        group.entries.iter().flat_map(|e| e.matched_data.clone()).collect::<Vec<_>>()
+    };
+    // Escaped quotes are restored, empty payloads dropped
+    assert_eq!(data("5e6f7a8b"), vec![r#"Matched Data: 1' OR "1"="1 found within ARGS:q"#]);
+    assert!(data("1a2b3c4d").is_empty());
+}
+
+#[test]
+fn keeps_the_bytes_of_binary_entries() {
+    let path = std::path::PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/binary.log"));
+    let groups = AuditLogParser::new().parse_files(&[path]).unwrap();
+    let entry = &groups[0].entries[0];
+    let bytes = entry.raw_bytes.as_deref().unwrap();
+    assert!(entry.raw_content.contains('\u{FFFD}'));
+    // The invalid bytes survive, the decoded text lines up with them
+    assert!(bytes.windows(4).any(|w| w == b"\xf3\xffa<"));
+    assert_eq!(bytes.iter().filter(|&&b| b == b'\n').count(), entry.raw_content.lines().count());
+
+    // Valid UTF-8 needs no copy
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    assert!(groups.iter().flat_map(|g| &g.entries).all(|e| e.raw_bytes.is_none()));
+}
+
+#[test]
+fn tells_binary_bodies_from_text() {
+    assert!(looks_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+    assert!(looks_binary(b"GIF89a\xff\xfe\xfd\xfc"));
+    assert!(!looks_binary(b"name=report&data=AAAAAAAAAA\n"));
+    // A few accented characters are still text
+    assert!(!looks_binary("q=caf\u{e9} cr\u{e8}me br\u{fb}l\u{e9}e".as_bytes()));
+    assert!(!looks_binary(b""));
+}
+
+#[test]
+fn explains_unusable_log_paths() {
+    let fixtures = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"));
+    assert!(check_log_path(&fixtures.join("multipart.log"), false).is_ok());
+
+    let missing = check_log_path(&fixtures.join("missing.log"), false).unwrap_err();
+    assert!(missing.to_string().contains("does not exist"));
+    // A directory only makes sense as concurrent-mode storage
+    let dir = check_log_path(&fixtures.join("concurrent"), false).unwrap_err();
+    assert!(dir.to_string().contains("is a directory"));
+    assert!(check_log_path(&fixtures.join("concurrent"), true).is_ok());
+}
+
+#[test]
+fn completes_a_cut_off_entry_when_merging_an_append() {
+    let path = std::env::temp_dir().join(format!("waflens-append-{}.log", std::process::id()));
+    let full = format!("{}{}", MULTIPART, IPV6);
+    let cut = full.find("--a3734c1a04ffce27-F--").unwrap();
+    std::fs::write(&path, &full[..cut]).unwrap();
+    let parser = AuditLogParser::new();
+    let mut groups = parser.parse_str(&full[..cut]);
+    assert_eq!(groups.len(), 3);
+
+    // The append is read from the start of the half-written entry on
+    let offset = parser.last_entry_offset(&path, cut as u64).unwrap();
+    assert_eq!(offset, MULTIPART.len() as u64);
+    std::fs::write(&path, &full).unwrap();
+    let appended = parser.parse_file_from(&path, offset).unwrap();
+    parser.merge_entries(&mut groups, appended);
+    std::fs::remove_file(&path).unwrap();
+
+    let expected = parser.parse_str(&full);
+    let ids = |groups: &[waflens::parser::AuditGroup]| groups.iter()
+        .map(|g| (g.base_id.clone(), g.entries.len(), g.http_status, g.incomplete))
+        .collect::<Vec<_>>();
+    assert_eq!(ids(&groups), ids(&expected));
+}
+
+#[test]
+fn flags_and_splits_audit_ids_shared_by_different_transactions() {
+    let other_ip = MULTIPART.replace("203.0.113.7", "192.0.2.55").replace("5e6f7a8b", "5e6f7a8c");
+    let content = format!("{}{}", MULTIPART, other_ip);
+    let groups = AuditLogParser::new().parse_str(&content);
+    assert_eq!(groups.len(), 3);
+    let collided: Vec<&str> = groups.iter().filter(|g| g.collided).map(|g| g.base_id.as_str()).collect();
+    assert_eq!(collided, ["1a2b3c4d"]);
+
+    // The same log read twice is not a collision; an hour between the A sections is
+    assert!(AuditLogParser::new().parse_str(&format!("{}{}", MULTIPART, MULTIPART)).iter().all(|g| !g.collided));
+    let later = MULTIPART.replace("04:46:27", "05:46:27").replace("5e6f7a8b", "5e6f7a8c");
+    assert!(AuditLogParser::new().parse_str(&format!("{}{}", MULTIPART, later)).iter().any(|g| g.collided));
+
+    // Each part follows the A section logged before it
+    let split = AuditLogParser::new().with_split_on_ip(true).parse_str(&content);
+    assert_eq!(split.len(), 4);
+    for ip in ["203.0.113.7", "192.0.2.55"] {
+        let group = split.iter().find(|g| g.base_id == format!("1a2b3c4d@{}", ip)).unwrap();
+        assert_eq!(group.client_ip, ip);
+        assert_eq!(group.entries.len(), 2);
+        assert_eq!(group.http_status, HttpStatus::Code(200));
+        assert!(group.collided);
+    }
+}
+
+#[test]
+fn times_the_parts_of_a_chain() {
+    let content = "--c0ffee01-A--\n[17/Nov/2025:04:46:27 +0200] Y2hhaW4 203.0.113.7 42724 192.168.0.1 80\n\
+--c0ffee01-B--\nGET /slow HTTP/1.1\nHost: shop.example.com\n\n\
+--c0ffee02-A--\n[17/Nov/2025:04:46:28 +0200] b3RoZXI 198.51.100.23 51000 192.168.0.1 443\n--c0ffee02-Z--\n\n\
+--c0ffee01-H--\nStopwatch: 1763347587000000 1715 (- - -)\n--c0ffee01-Z--\n\n\
+--c0ffee01-A--\n[17/Nov/2025:04:46:29 +0200] Y2hhaW4 203.0.113.7 42724 192.168.0.1 80\n--c0ffee01-Z--\n";
+    let groups = AuditLogParser::new().parse_str(content);
+    let chain = groups.iter().find(|g| g.base_id == "c0ffee01").unwrap();
+    // Parts are ordered by their first section, so the H part comes last
+    // and, without an A section, has no time of its own
+    assert_eq!(
+        chain.entry_offsets(),
+        [Some(Duration::ZERO), Some(Duration::from_secs(2)), None]
+    );
+    assert_eq!(chain.processing_time(), Some(Duration::from_micros(1715)));
+
+    let other = groups.iter().find(|g| g.base_id == "c0ffee02").unwrap();
+    assert_eq!(other.processing_time(), None);
+}
+
+#[test]
+fn tells_a_missing_response_from_an_unreadable_status_line() {
+    let groups = AuditLogParser::new().parse_str(&MULTIPART.replace("HTTP/1.1 200 OK", "HTTP/2 200"));
+    let chain = groups.iter().find(|g| g.base_id == "1a2b3c4d").unwrap();
+    assert_eq!(chain.http_status, HttpStatus::Unparsed);
+    assert_eq!(chain.http_status.label(), "?");
+
+    let no_response = "--0badf00d-A--\n[17/Nov/2025:04:46:27 +0200] eA 203.0.113.7 42724 192.168.0.1 80\n\
+--0badf00d-B--\nGET / HTTP/1.1\nHost: shop.example.com\n\n--0badf00d-Z--\n";
+    let groups = AuditLogParser::new().parse_str(no_response);
+    assert_eq!(groups[0].http_status, HttpStatus::Missing);
+    assert_eq!(groups[0].http_status.label(), "—");
+    assert_eq!(groups[0].http_status.code(), None);
+}
+
+#[test]
+fn streams_each_transaction_once_it_is_complete() {
+    let path = std::env::temp_dir().join(format!("waflens-stream-{}.log", std::process::id()));
+    // The last transaction is still being written
+    let cut = IPV6.find("-Z--").unwrap();
+    std::fs::write(&path, format!("{}{}", MULTIPART, &IPV6[..cut])).unwrap();
+    let parser = AuditLogParser::new();
+
+    let mut streamed = Vec::new();
+    parser.stream_groups(std::slice::from_ref(&path), |group| {
+        streamed.push((group.base_id, group.entries.len(), group.incomplete));
+        Ok(())
+    }).unwrap();
+    // Log order: 5e6f7a8b ends before 1a2b3c4d's second part does
+    assert_eq!(streamed[0], ("5e6f7a8b".to_string(), 1, false));
+    assert_eq!(streamed[1], ("1a2b3c4d".to_string(), 2, false));
+    assert_eq!(streamed.len(), 3);
+    assert!(streamed[2].2);
+
+    // A failing consumer stops the read with its own error
+    let mut calls = 0;
+    let error = parser.stream_groups(std::slice::from_ref(&path), |_| {
+        calls += 1;
+        anyhow::bail!("pipe closed")
+    }).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(error.to_string(), "pipe closed");
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn rebuilds_the_request_as_a_curl_command() {
+    let parser = AuditLogParser::new();
+    let bodies = include_str!("fixtures/bodies.log");
+    // Server port 443 in section A: https
+    assert_eq!(
+        parser.parse_str(bodies)[0].to_curl().unwrap(),
+        "curl -g --path-as-is -X POST 'https://files.example.com/upload.php' \\\n  \
+         -H 'Content-Type: application/x-www-form-urlencoded' \\\n  \
+         --data-binary 'name=report&data=AAAAAAAAAA'"
+    );
+
+    // Quotes survive the shell, what a URL can't carry is escaped, and a
+    // body shorter than declared is pointed out
+    let log = bodies
+        .replace("Content-Length: 27", "Content-Length: 4096")
+        .replace("/upload.php", "/q?s=<b>'é");
+    let curl = parser.parse_str(&log)[0].to_curl().unwrap();
+    assert!(curl.starts_with("# Request body truncated in the log: 27 of 4096 bytes\n"), "{}", curl);
+    assert!(curl.contains(r"'https://files.example.com/q?s=%3Cb%3E'\''%C3%A9'"), "{}", curl);
+
+    // GET without a body; the logged target is already encoded
+    let groups = parser.parse_str(include_str!("fixtures/multipart.log"));
+    assert_eq!(groups[0].to_curl().unwrap(), "curl -g --path-as-is 'https://blog.example.org/index.php?id=1%27%20OR%201=1'");
+}
+
+#[test]
+fn parses_trusted_ip_header_lists() {
+    assert_eq!(
+        parse_trusted_ip_headers(" CF-Connecting-IP, x-forwarded-for,X-Forwarded-For ").unwrap(),
+        vec!["CF-Connecting-IP", "x-forwarded-for"]
+    );
+    assert!(parse_trusted_ip_headers(",").is_err());
+    assert!(parse_trusted_ip_headers("X-Real IP").is_err());
+}
@@ -0,0 +1,51 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use waflens::rdap::{AbuseContact, RdapCache};
+
+const ARIN: &str = include_str!("fixtures/rdap-arin.json");
+
+#[test]
+fn finds_nested_abuse_entities() {
+    let contact = AbuseContact::parse(ARIN).unwrap();
+    assert_eq!(contact.emails, vec!["abuse@hosting.example"]);
+    assert_eq!(contact.network.as_deref(), Some("EXAMPLE-HOSTING"));
+    assert_eq!(contact.handle.as_deref(), Some("NET-185-220-101-0-1"));
+    assert_eq!(contact.range_text(), "185.220.101.0 - 185.220.101.255");
+    assert!(AbuseContact::parse("<html>").is_err());
+}
+
+#[test]
+fn one_request_covers_the_whole_network() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/ip/", listener.local_addr().unwrap());
+    // Answer one request; a second one would find nobody listening
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = String::new();
+        reader.read_line(&mut request).unwrap();
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+            line.clear();
+        }
+        write!(&stream, "HTTP/1.1 200 OK\r\nContent-Type: application/rdap+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", ARIN.len(), ARIN).unwrap();
+        request
+    });
+
+    let cache = RdapCache::new().with_base_url(&url);
+    assert!(cache.cached("185.220.101.23").is_none());
+    assert_eq!(cache.lookup("185.220.101.23").unwrap().emails, vec!["abuse@hosting.example"]);
+    assert!(server.join().unwrap().starts_with("GET /ip/185.220.101.23 "));
+
+    assert_eq!(cache.lookup("185.220.101.200").unwrap().network.as_deref(), Some("EXAMPLE-HOSTING"));
+    assert!(cache.cached("185.220.102.1").is_none());
+    assert!(cache.cached("2001:db8::1").is_none());
+}
+
+#[test]
+fn private_addresses_are_not_looked_up() {
+    let cache = RdapCache::new().with_base_url("http://127.0.0.1:1/ip/");
+    assert!(cache.lookup("10.0.0.1").unwrap_err().to_string().contains("private or reserved"));
+    assert!(cache.lookup("unknown").is_err());
+}
@@ -0,0 +1,358 @@
+use waflens::app::{App, AppOptions};
+use waflens::ipapi::IpApiResponse;
+use waflens::parser::AuditLogParser;
+
+const MULTIPART: &str = include_str!("fixtures/multipart.log");
+const IPV6: &str = include_str!("fixtures/ipv6.log");
+
+fn app_with(content: &str) -> App {
+    let groups = AuditLogParser::new().parse_str(content);
+    App::from_groups(groups, &["test.log"], &AppOptions::default())
+}
+
+fn search(app: &mut App, query: &str) -> Vec<String> {
+    app.search_query = query.to_string();
+    app.apply_search();
+    app.visible_groups().iter().map(|g| g.base_id.clone()).collect()
+}
+
+#[test]
+fn the_search_bar_counts_the_matches() {
+    use ratatui::{backend::TestBackend, Terminal};
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    app.search_mode = true;
+    let bar = |app: &mut App, query: &str| {
+        search(app, query);
+        let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+        terminal.draw(|f| waflens::ui::draw(f, app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        screen.split_once('→').map(|(_, count)| count.split('│').next().unwrap().trim().to_string())
+    };
+    assert_eq!(bar(&mut app, "status:4xx").as_deref(), Some("2 matches"));
+    assert_eq!(bar(&mut app, "domain:shop").as_deref(), Some("1 match"));
+    assert_eq!(bar(&mut app, "domain:nomatch").as_deref(), Some("0 matches"));
+}
+
+#[test]
+fn filters_by_token() {
+    let mut app = app_with(MULTIPART);
+    assert_eq!(search(&mut app, "domain:SHOP"), vec!["1a2b3c4d"]);
+    assert_eq!(search(&mut app, "ip:198.51"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "rule:942100"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "status:403"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "auditid:1a2b"), vec!["1a2b3c4d"]);
+}
+
+#[test]
+fn filters_by_crs_category() {
+    let mut app = app_with(MULTIPART);
+    assert_eq!(search(&mut app, "cat:sqli"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "category:protocol"), vec!["1a2b3c4d"]);
+    // A prefix of the category name, like `cat:sql`
+    assert_eq!(search(&mut app, "cat:sql"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "-cat:sqli"), vec!["1a2b3c4d"]);
+    assert!(search(&mut app, "cat:xss").is_empty());
+}
+
+#[test]
+fn filters_by_rule_file() {
+    let mut app = app_with(MULTIPART);
+    assert_eq!(search(&mut app, "file:ATTACK-SQLI.conf"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "file:protocol-enforcement"), vec!["1a2b3c4d"]);
+    assert_eq!(search(&mut app, "-file:942"), vec!["1a2b3c4d"]);
+    // Groups without a `[file]` never match
+    let mut app = app_with(&MULTIPART.replace("[file ", "[nofile "));
+    assert!(search(&mut app, "file:conf").is_empty());
+}
+
+#[test]
+fn highlights_search_terms_in_the_columns_they_matched() {
+    use waflens::app::Column;
+    let mut app = app_with(MULTIPART);
+    app.search_query = "domain:Shop ip:203 -rule:942 status:200 cat: 1a2b".to_string();
+    let highlights = app.search_highlights();
+    assert_eq!(highlights[..2], [(Column::Domain, "shop".to_string()), (Column::ClientIp, "203".to_string())]);
+    // Free text is looked for in every column it is matched against
+    assert_eq!(highlights[2..].iter().map(|(column, _)| *column).collect::<Vec<_>>(),
+        [Column::Domain, Column::ClientIp, Column::AuditId, Column::RuleIds, Column::Status]);
+    assert!(highlights[2..].iter().all(|(_, text)| text == "1a2b"));
+    app.fuzzy = true;
+    assert_eq!(app.search_highlights().len(), 2);
+}
+
+#[test]
+fn filters_by_matched_data() {
+    let log = MULTIPART.replace(r#"[id "942100"]"#, r#"[id "942100"] [data "Matched Data: UNION SELECT"]"#);
+    let mut app = app_with(&log);
+    assert_eq!(search(&mut app, "data:union"), vec!["5e6f7a8b"]);
+    // The rule message itself is not matched data
+    assert!(search(&mut app, "data:libinjection").is_empty());
+}
+
+#[test]
+fn free_text_searches_all_fields() {
+    let mut app = app_with(IPV6);
+    assert_eq!(search(&mut app, "2a03:2880"), vec!["a3734c1a04ffce27"]);
+    assert_eq!(search(&mut app, "913100"), vec!["b44f0e1d22aa9c10"]);
+    assert_eq!(search(&mut app, "example").len(), 2);
+}
+
+#[test]
+fn clearing_search_restores_all_groups() {
+    let mut app = app_with(MULTIPART);
+    assert!(search(&mut app, "domain:nomatch").is_empty());
+    app.clear_search();
+    assert_eq!(app.visible_groups().len(), 2);
+}
+
+#[test]
+fn status_prefilter_survives_clearing_search() {
+    let groups = AuditLogParser::new().parse_str(&format!("{}{}", MULTIPART, IPV6));
+    let options = AppOptions { min_status: Some(400), ..AppOptions::default() };
+    let mut app = App::from_groups(groups, &["test.log"], &options);
+
+    // 403 and 429 pass, the two 200s do not
+    assert_eq!(app.visible_groups().len(), 2);
+    assert_eq!(search(&mut app, "ip:2a03"), vec!["a3734c1a04ffce27"]);
+
+    app.clear_search();
+    assert_eq!(app.visible_groups().len(), 2);
+    assert!(app.visible_groups().iter().all(|g| g.http_status.code().unwrap() >= 400));
+}
+
+#[test]
+fn status_prefilter_bounds_are_inclusive() {
+    let groups = AuditLogParser::new().parse_str(&format!("{}{}", MULTIPART, IPV6));
+    let options = AppOptions { min_status: Some(403), max_status: Some(403), ..AppOptions::default() };
+    let app = App::from_groups(groups, &["test.log"], &options);
+    let ids: Vec<&str> = app.visible_groups().iter().map(|g| g.base_id.as_str()).collect();
+    assert_eq!(ids, vec!["5e6f7a8b"]);
+}
+
+#[test]
+fn negated_token_excludes_matches() {
+    let mut app = app_with(MULTIPART);
+    assert_eq!(search(&mut app, "rule:920350"), vec!["1a2b3c4d"]);
+    assert_eq!(search(&mut app, "-rule:920350"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "-domain:shop"), vec!["5e6f7a8b"]);
+    assert!(search(&mut app, "-rule:9").is_empty());
+}
+
+#[test]
+fn filters_by_cached_asn() {
+    let mut app = app_with(MULTIPART);
+    let geo: IpApiResponse = serde_json::from_value(serde_json::json!({
+        "status": "success",
+        "as": "AS13335 Cloudflare, Inc.",
+    }))
+    .unwrap();
+    app.ip_api_cache.insert("198.51.100.23", geo);
+
+    assert_eq!(search(&mut app, "asn:AS13335"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "asn:cloudflare"), vec!["5e6f7a8b"]);
+    // Unresolved IPs never match, but are kept by the negation
+    assert_eq!(search(&mut app, "-asn:as13335"), vec!["1a2b3c4d"]);
+}
+
+#[test]
+fn quick_filters_keep_hosting_and_proxy_ips() {
+    let mut app = app_with(MULTIPART);
+    let geo = |hosting: bool, proxy: bool| -> IpApiResponse {
+        serde_json::from_value(serde_json::json!({"status": "success", "hosting": hosting, "proxy": proxy})).unwrap()
+    };
+    app.ip_api_cache.insert("198.51.100.23", geo(true, false));
+    app.ip_api_cache.insert("203.0.113.7", geo(false, true));
+
+    app.toggle_hosting_only();
+    assert_eq!(search(&mut app, ""), vec!["5e6f7a8b"]);
+    // Independent of the query, and kept when it is cleared
+    assert!(search(&mut app, "domain:shop").is_empty());
+    app.clear_search();
+    assert_eq!(app.visible_groups().len(), 1);
+    // Both: either flag will do
+    app.toggle_proxy_only();
+    assert_eq!(app.visible_groups().len(), 2);
+    app.toggle_hosting_only();
+    assert_eq!(search(&mut app, ""), vec!["1a2b3c4d"]);
+    app.toggle_proxy_only();
+    assert_eq!(app.visible_groups().len(), 2);
+}
+
+#[test]
+fn filters_by_cached_country() {
+    let mut app = app_with(MULTIPART);
+    let geo: IpApiResponse = serde_json::from_value(serde_json::json!({
+        "status": "success",
+        "country": "United States",
+        "countryCode": "US",
+    }))
+    .unwrap();
+    app.ip_api_cache.insert("203.0.113.7", geo);
+
+    assert_eq!(search(&mut app, "country:US"), vec!["1a2b3c4d"]);
+    assert_eq!(search(&mut app, "country:united"), vec!["1a2b3c4d"]);
+    assert!(search(&mut app, "country:u").is_empty());
+}
+
+#[test]
+fn filters_by_body_size() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, include_str!("fixtures/bodies.log")));
+    assert_eq!(search(&mut app, "reqsize:>10"), vec!["9c8d7e6f"]);
+    assert_eq!(search(&mut app, "reqsize:27"), vec!["9c8d7e6f"]);
+    assert!(search(&mut app, "reqsize:<=26").is_empty());
+    assert_eq!(search(&mut app, "respsize:>=19"), vec!["9c8d7e6f"]);
+}
+
+#[test]
+fn filters_blocked_from_detection_only() {
+    let mut app = app_with(MULTIPART);
+    assert_eq!(search(&mut app, "blocked:true"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "blocked:no"), vec!["1a2b3c4d"]);
+    assert_eq!(search(&mut app, "-blocked:yes"), vec!["1a2b3c4d"]);
+    assert!(search(&mut app, "blocked:maybe").is_empty());
+}
+
+#[test]
+fn status_token_takes_lists_and_classes() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    assert_eq!(search(&mut app, "status:4xx").len(), 2);
+    assert_eq!(search(&mut app, "status:403,429").len(), 2);
+    assert_eq!(search(&mut app, "status:42x"), vec!["a3734c1a04ffce27"]);
+    assert_eq!(search(&mut app, "-status:4XX").len(), 2);
+    // Whole codes only: no more substring matches
+    assert!(search(&mut app, "status:40").is_empty());
+    assert!(search(&mut app, "status:20").is_empty());
+}
+
+#[test]
+fn filters_by_engine_mode() {
+    let log = MULTIPART.replace("--5e6f7a8b-Z--", "Engine-Mode: \"ENABLED\"\n\n--5e6f7a8b-Z--");
+    let mut app = app_with(&log);
+    assert_eq!(search(&mut app, "mode:enabled"), vec!["5e6f7a8b"]);
+    assert!(search(&mut app, "mode:detection").is_empty());
+    assert_eq!(search(&mut app, "-mode:enabled"), vec!["1a2b3c4d"]);
+}
+
+#[test]
+fn equals_matches_the_whole_value() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    assert_eq!(search(&mut app, "ip:198.51.100.2"), vec!["5e6f7a8b"]);
+    assert!(search(&mut app, "ip=198.51.100.2").is_empty());
+    assert_eq!(search(&mut app, "ip=198.51.100.23"), vec!["5e6f7a8b"]);
+    assert!(search(&mut app, "domain=example.com").is_empty());
+    assert_eq!(search(&mut app, "domain=SHOP.example.com"), vec!["1a2b3c4d"]);
+    assert!(search(&mut app, "rule=9421").is_empty());
+    assert_eq!(search(&mut app, "-cat=sqli").len(), 3);
+    // Only the first `:` or `=` splits the term
+    let ipv6 = app.audit_groups.iter().find(|g| g.client_ip.starts_with("2001:")).unwrap().client_ip.clone();
+    assert_eq!(search(&mut app, &format!("ip={}", ipv6)).len(), 1);
+}
+
+#[test]
+fn filters_by_action() {
+    let mut app = app_with(MULTIPART);
+    assert_eq!(search(&mut app, "action:intercepted"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "action:block"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "action:warn"), vec!["1a2b3c4d"]);
+    assert_eq!(search(&mut app, "-action:intercepted"), vec!["1a2b3c4d"]);
+}
+
+#[test]
+fn space_separated_terms_must_all_match() {
+    let mut app = app_with(MULTIPART);
+    assert_eq!(search(&mut app, "status:403 rule:942100"), vec!["5e6f7a8b"]);
+    assert!(search(&mut app, "status:403 domain:shop").is_empty());
+    assert_eq!(search(&mut app, "  -status:403   domain:shop "), vec!["1a2b3c4d"]);
+}
+
+#[test]
+fn startup_filter_is_applied() {
+    let groups = AuditLogParser::new().parse_str(MULTIPART);
+    let options = AppOptions { filter: Some("status:403".to_string()), ..AppOptions::default() };
+    let app = App::from_groups(groups, &["test.log"], &options);
+    assert_eq!(app.search_query, "status:403");
+    let ids: Vec<&str> = app.visible_groups().iter().map(|g| g.base_id.as_str()).collect();
+    assert_eq!(ids, vec!["5e6f7a8b"]);
+}
+
+#[test]
+fn fuzzy_search_matches_subsequences_best_first() {
+    let mut app = app_with(&format!("{}{}", MULTIPART, IPV6));
+    assert!(search(&mut app, "shpexmpl").is_empty());
+    app.toggle_fuzzy();
+    assert_eq!(search(&mut app, "shpexmpl"), vec!["1a2b3c4d"]);
+    // Field tokens stay exact
+    assert!(search(&mut app, "domain:shpexmpl").is_empty());
+    // Contiguous "20" (203.0.113.7, 920350) outranks the newer, scattered
+    // match in 198.51.100.23
+    let ranked = search(&mut app, "20");
+    let position = |id: &str| ranked.iter().position(|r| r == id).unwrap();
+    assert!(position("1a2b3c4d") < position("5e6f7a8b"));
+}
+
+#[test]
+fn content_token_scans_raw_entries() {
+    let mut app = app_with(MULTIPART);
+    // Only in the request line of the B section
+    assert_eq!(search(&mut app, "content:%27%20OR"), vec!["5e6f7a8b"]);
+    assert_eq!(search(&mut app, "raw:CURL/8"), vec!["1a2b3c4d"]);
+    assert_eq!(search(&mut app, "-content:libinjection"), vec!["1a2b3c4d"]);
+    assert!(search(&mut app, "content:/etc/passwd").is_empty());
+}
+
+#[test]
+fn search_input_edits_at_the_cursor() {
+    let mut app = app_with(MULTIPART);
+    app.search_query = "status:403 domain:blog".to_string();
+    app.enter_search_mode();
+    assert_eq!(app.search_cursor(), app.search_query.len());
+
+    app.delete_search_word();
+    assert_eq!(app.search_query, "status:403 ");
+    app.search_cursor_home();
+    app.add_search_char('-');
+    assert_eq!(app.search_query, "-status:403 ");
+    app.move_search_cursor(true);
+    app.remove_search_char();
+    assert_eq!(app.search_query, "-tatus:403 ");
+    assert_eq!(app.search_cursor(), 1);
+
+    app.search_cursor_end();
+    app.add_search_char('é');
+    app.move_search_cursor(false);
+    app.add_search_char('x');
+    assert_eq!(app.search_query, "-tatus:403 xé");
+
+    app.clear_search();
+    assert_eq!((app.search_query.as_str(), app.search_cursor()), ("", 0));
+}
+
+#[test]
+fn saves_applies_and_deletes_named_filters() {
+    let mut app = app_with(MULTIPART);
+    assert!(app.save_filter("blocks").is_err(), "empty search");
+
+    app.search_query = "status:403".to_string();
+    app.save_filter("blocks").unwrap();
+    app.search_query = "domain:shop".to_string();
+    app.save_filter("shop").unwrap();
+    app.search_query = "status:4xx".to_string();
+    app.save_filter("blocks").unwrap();
+    assert_eq!(app.saved_filters, [
+        ("blocks".to_string(), "status:4xx".to_string()),
+        ("shop".to_string(), "domain:shop".to_string()),
+    ]);
+    assert!(app.save_filter("  ").is_err());
+
+    app.clear_search();
+    app.open_filter_picker();
+    app.move_filter_selection(true);
+    app.apply_saved_filter(1);
+    assert_eq!(app.search_query, "domain:shop");
+    assert_eq!(app.filtered_groups.len(), 1);
+    assert!(app.filter_picker.is_none());
+
+    app.delete_saved_filter(0).unwrap();
+    assert_eq!(app.saved_filters.len(), 1);
+    assert_eq!(app.saved_filters[0].0, "shop");
+}
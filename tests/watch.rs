@@ -0,0 +1,43 @@
+use std::fs::File;
+use std::time::{Duration, SystemTime};
+use waflens::watch::{file_id, glob_matches, WatchDir};
+
+#[test]
+fn matches_file_names_against_shell_globs() {
+    assert!(glob_matches("modsec_audit.log*", "modsec_audit.log"));
+    assert!(glob_matches("modsec_audit.log*", "modsec_audit.log.1"));
+    assert!(glob_matches("*.log", "audit.log"));
+    assert!(glob_matches("audit-????.log", "audit-2025.log"));
+    assert!(glob_matches("*a*b*", "xaxxbx"));
+    assert!(glob_matches("*", ""));
+    assert!(!glob_matches("*.log", "audit.log.1"));
+    assert!(!glob_matches("audit-????.log", "audit-25.log"));
+    assert!(!glob_matches("modsec_audit.log*", "error.log"));
+}
+
+#[test]
+fn picks_the_newest_matching_file() {
+    let dir = std::env::temp_dir().join(format!("waflens-newest-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let now = SystemTime::now();
+    let touch = |name: &str, age: u64| {
+        let file = File::create(dir.join(name)).unwrap();
+        file.set_modified(now - Duration::from_secs(age)).unwrap();
+    };
+    touch("audit.log.2", 300);
+    touch("audit.log.1", 60);
+    touch("error.log", 0);
+    let watch = WatchDir::new(&dir, "audit.log*");
+    assert_eq!(watch.newest().unwrap(), Some(dir.join("audit.log.1")));
+
+    // Same time: the live name beats the rotated one
+    touch("audit.log", 60);
+    assert_eq!(watch.newest().unwrap(), Some(dir.join("audit.log")));
+
+    if let Some(id) = file_id(&dir.join("audit.log.2")) {
+        std::fs::rename(dir.join("audit.log.2"), dir.join("audit.log.3")).unwrap();
+        assert_eq!(watch.find(id).unwrap(), Some(dir.join("audit.log.3")));
+    }
+    assert_eq!(WatchDir::new(&dir, "*.gz").newest().unwrap(), None);
+    std::fs::remove_dir_all(&dir).unwrap();
+}